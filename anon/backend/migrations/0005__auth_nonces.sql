@@ -0,0 +1,16 @@
+-- Sign-In With Starknet nonce challenges
+CREATE TABLE IF NOT EXISTS auth_nonces (
+    id BIGSERIAL PRIMARY KEY,
+    wallet TEXT NOT NULL,
+    nonce TEXT NOT NULL UNIQUE,
+    issued_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Lookup of outstanding challenges for a wallet
+CREATE INDEX IF NOT EXISTS idx_auth_nonces_wallet ON auth_nonces(wallet);
+
+-- Sweeping expired challenges
+CREATE INDEX IF NOT EXISTS idx_auth_nonces_expires_at ON auth_nonces(expires_at);
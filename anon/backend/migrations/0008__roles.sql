@@ -0,0 +1,6 @@
+-- Role-based access control. Roles are copied into access tokens at issue
+-- time; a disabled user can no longer sign in, refresh or use API keys.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS roles TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE users ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS idx_users_roles ON users USING GIN (roles);
@@ -0,0 +1,2 @@
+-- Scarb.toml generated alongside the contract; NULL for contracts generated before it existed
+ALTER TABLE generated_contracts ADD COLUMN IF NOT EXISTS scarb_config TEXT;
@@ -0,0 +1,2 @@
+-- Which ContractGenerator produced the code ('template' or 'llm')
+ALTER TABLE generated_contracts ADD COLUMN IF NOT EXISTS generator TEXT NOT NULL DEFAULT 'template';
@@ -0,0 +1,24 @@
+-- Why a contract ended up `failed`
+ALTER TABLE generated_contracts ADD COLUMN IF NOT EXISTS error TEXT NULL;
+
+-- Queue of asynchronous generations. The contract row is created up front
+-- (status `queued`, empty code) and filled in by the worker that claims the
+-- job with FOR UPDATE SKIP LOCKED.
+CREATE TABLE IF NOT EXISTS generation_jobs (
+    id BIGSERIAL PRIMARY KEY,
+    contract_id BIGINT NOT NULL REFERENCES generated_contracts(id) ON DELETE CASCADE,
+    -- queued, running, succeeded or failed
+    status TEXT NOT NULL DEFAULT 'queued',
+    cairo_version TEXT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL,
+    -- Earliest time a queued job may be claimed; pushed back between retries
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_at TIMESTAMPTZ NULL,
+    last_error TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_generation_jobs_queued ON generation_jobs(run_at) WHERE status = 'queued';
+CREATE INDEX IF NOT EXISTS idx_generation_jobs_contract_id ON generation_jobs(contract_id);
@@ -0,0 +1,16 @@
+-- `scarb build` runs of generated contracts; the newest row is the current
+-- result. A successful build keeps its Sierra and CASM classes.
+CREATE TABLE IF NOT EXISTS contract_compilations (
+    id BIGSERIAL PRIMARY KEY,
+    contract_id BIGINT NOT NULL REFERENCES generated_contracts(id) ON DELETE CASCADE,
+    success BOOLEAN NOT NULL,
+    -- [{severity, file, line, column, message}]
+    diagnostics JSONB NOT NULL DEFAULT '[]',
+    sierra JSONB NULL,
+    casm JSONB NULL,
+    log TEXT NOT NULL DEFAULT '',
+    duration_ms BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_contract_compilations_contract_id ON contract_compilations(contract_id, id DESC);
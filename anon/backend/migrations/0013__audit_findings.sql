@@ -0,0 +1,15 @@
+-- Static analysis findings for generated contracts. Each audit replaces the
+-- contract's previous findings.
+CREATE TABLE IF NOT EXISTS audit_findings (
+    id BIGSERIAL PRIMARY KEY,
+    contract_id BIGINT NOT NULL REFERENCES generated_contracts(id) ON DELETE CASCADE,
+    rule TEXT NOT NULL,
+    severity TEXT NOT NULL CHECK (severity IN ('info', 'low', 'medium', 'high', 'critical')),
+    message TEXT NOT NULL,
+    function_name TEXT NULL,
+    line_start INTEGER NOT NULL,
+    line_end INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_findings_contract_id ON audit_findings(contract_id);
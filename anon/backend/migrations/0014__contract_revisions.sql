@@ -0,0 +1,34 @@
+-- Every version of a contract's code: generated, regenerated or edited by
+-- hand. generated_contracts keeps the latest one.
+CREATE TABLE IF NOT EXISTS contract_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    contract_id BIGINT NOT NULL REFERENCES generated_contracts(id) ON DELETE CASCADE,
+    -- 1, 2, ... per contract
+    revision INTEGER NOT NULL,
+    source TEXT NOT NULL CHECK (source IN ('generated', 'regenerated', 'edited')),
+    generated_code TEXT NOT NULL,
+    scarb_config TEXT NULL,
+    message TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (contract_id, revision)
+);
+
+-- Existing code becomes the first revision
+INSERT INTO contract_revisions (contract_id, revision, source, generated_code, scarb_config, created_at)
+SELECT id, 1, 'generated', generated_code, scarb_config, created_at
+FROM generated_contracts
+WHERE status NOT IN ('queued', 'generating', 'failed')
+ON CONFLICT DO NOTHING;
+
+-- Keep updated_at current however a contract is changed
+CREATE OR REPLACE FUNCTION touch_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS generated_contracts_touch_updated_at ON generated_contracts;
+CREATE TRIGGER generated_contracts_touch_updated_at
+    BEFORE UPDATE ON generated_contracts
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
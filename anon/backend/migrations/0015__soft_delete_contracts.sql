@@ -0,0 +1,5 @@
+-- Contracts deleted by their owner are kept, with the time of deletion, and
+-- hidden from every user-facing route
+ALTER TABLE generated_contracts ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS idx_generated_contracts_live ON generated_contracts(user_id, created_at DESC, id DESC) WHERE deleted_at IS NULL;
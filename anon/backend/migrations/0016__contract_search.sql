@@ -0,0 +1,11 @@
+-- Words of the name and description, for `q` on /generated_contracts
+ALTER TABLE generated_contracts ADD COLUMN IF NOT EXISTS search TSVECTOR
+    GENERATED ALWAYS AS (to_tsvector('simple', contract_name || ' ' || COALESCE(description, ''))) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_generated_contracts_search ON generated_contracts USING GIN (search);
+
+CREATE INDEX IF NOT EXISTS idx_generated_contracts_template_id ON generated_contracts(template_id);
+
+-- Keyset pagination under the other sort orders
+CREATE INDEX IF NOT EXISTS idx_generated_contracts_updated ON generated_contracts(user_id, updated_at DESC, id DESC) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_generated_contracts_name ON generated_contracts(user_id, contract_name, id) WHERE deleted_at IS NULL;
@@ -0,0 +1,19 @@
+-- Responses to requests sent with an Idempotency-Key header, replayed when
+-- the same caller retries with the same key
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    id BIGSERIAL PRIMARY KEY,
+    -- Keys are per caller; a wallet rather than a user id so /register works
+    wallet TEXT NOT NULL,
+    key TEXT NOT NULL,
+    -- SHA-256 of the method, path and body of the first request
+    request_hash BYTEA NOT NULL,
+    -- NULL while the first request is being handled
+    response_status SMALLINT NULL,
+    response_headers JSONB NULL,
+    response_body BYTEA NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (wallet, key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
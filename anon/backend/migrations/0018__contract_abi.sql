@@ -0,0 +1,3 @@
+-- ABI extracted from the current code (libs::abi::SourceAbi); NULL until the
+-- code is generated, or after a rename changed the package paths in it
+ALTER TABLE generated_contracts ADD COLUMN IF NOT EXISTS abi JSONB;
@@ -0,0 +1,18 @@
+-- Instances of generated contracts deployed on a Starknet network, with the
+-- class they were deployed from and the transactions that did it
+CREATE TABLE IF NOT EXISTS deployed_contracts (
+    id BIGSERIAL PRIMARY KEY,
+    contract_id BIGINT NOT NULL REFERENCES generated_contracts(id) ON DELETE CASCADE,
+    network TEXT NOT NULL CHECK (network IN ('mainnet', 'sepolia', 'devnet')),
+    contract_address TEXT NOT NULL,
+    class_hash TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    -- Set when the class was declared for this deployment
+    declare_transaction_hash TEXT NULL,
+    -- contract_revisions.revision of the deployed code
+    revision INTEGER NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (network, contract_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deployed_contracts_contract_id ON deployed_contracts(contract_id, id DESC);
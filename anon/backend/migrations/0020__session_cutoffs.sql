@@ -0,0 +1,6 @@
+-- Logging out everywhere: access tokens of the wallet issued before
+-- `revoked_before` are rejected, even though their jti was never listed
+CREATE TABLE IF NOT EXISTS session_cutoffs (
+    wallet TEXT PRIMARY KEY,
+    revoked_before TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Recorded addresses are only unique per contract: anyone may record any
+-- address, so a global constraint let one user block another's record and
+-- told them it exists, and devnet addresses recur after a node restart
+ALTER TABLE deployed_contracts
+    DROP CONSTRAINT IF EXISTS deployed_contracts_network_contract_address_key;
+ALTER TABLE deployed_contracts
+    ADD CONSTRAINT deployed_contracts_contract_network_address_key
+    UNIQUE (contract_id, network, contract_address);
@@ -0,0 +1,12 @@
+-- Server-side deployments are stored once their transaction is sent, before
+-- it is confirmed; recorded ones are confirmed already. A reverted
+-- transaction took no address, so it no longer holds one.
+ALTER TABLE deployed_contracts
+    ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'confirmed'
+    CHECK (status IN ('pending', 'confirmed', 'failed'));
+
+ALTER TABLE deployed_contracts
+    DROP CONSTRAINT IF EXISTS deployed_contracts_contract_network_address_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_deployed_contracts_address
+    ON deployed_contracts(contract_id, network, contract_address)
+    WHERE status <> 'failed';
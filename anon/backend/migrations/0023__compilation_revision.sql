@@ -0,0 +1,11 @@
+-- contract_revisions.revision of the code a build was made from; a build
+-- is current only while that revision is the latest. Earlier builds are
+-- taken to be of the latest revision that existed when they finished.
+ALTER TABLE contract_compilations ADD COLUMN IF NOT EXISTS revision INTEGER NULL;
+
+UPDATE contract_compilations c
+SET revision = (
+    SELECT MAX(r.revision) FROM contract_revisions r
+    WHERE r.contract_id = c.contract_id AND r.created_at <= c.created_at
+)
+WHERE c.revision IS NULL;
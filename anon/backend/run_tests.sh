@@ -19,6 +19,9 @@ if [ -z "$TEST_DATABASE_URL" ]; then
     echo "📝 Using default test database URL: $TEST_DATABASE_URL"
 fi
 
+# Tests sign their tokens with a per-process key
+export JWT_EPHEMERAL_KEY=true
+
 # Ensure DATABASE_URL is set for Rust/sqlx
 export DATABASE_URL="$TEST_DATABASE_URL"
 echo "🔗 DATABASE_URL set to: $DATABASE_URL"
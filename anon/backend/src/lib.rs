@@ -1,24 +1,54 @@
 pub mod libs {
+    pub mod abi;
+    pub mod api_keys;
     pub mod apispec;
+    pub mod archive;
+    pub mod audit;
+    pub mod cairo;
+    pub mod calldata;
+    pub mod compiler;
     pub mod config;
     pub mod db;
+    pub mod deployer;
+    pub mod deployments;
     pub mod error;
+    pub mod generator;
+    pub mod idempotency;
+    pub mod jobs;
     pub mod jwt;
     pub mod logging;
     pub mod pagination;
+    pub mod revisions;
+    pub mod roles;
+    pub mod scarb;
+    pub mod session;
+    pub mod siws;
+    pub mod templates;
+    pub mod verifier;
     pub mod wallet;
 }
 
 pub mod middlewares {
     pub mod auth;
+    pub mod idempotency;
     pub mod request_id;
 }
 
 pub mod routes {
+    pub mod abi;
+    pub mod admin;
+    pub mod api_keys;
+    pub mod audit;
+    pub mod auth;
+    pub mod compile;
+    pub mod deployments;
     pub mod generate;
     pub mod health;
+    pub mod jobs;
     pub mod register;
     pub mod reviews;
+    pub mod revisions;
+    pub mod templates;
     pub mod user;
 }
 
@@ -26,9 +56,9 @@ use axum::{
     Router,
     http::{
         Method,
-        header::{AUTHORIZATION, CONTENT_TYPE},
+        header::{AUTHORIZATION, CONTENT_TYPE, ETAG, HeaderName, IF_NONE_MATCH},
     },
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 use tower_http::{
     cors::{Any, CorsLayer},
@@ -42,18 +72,125 @@ use crate::libs::db::AppState;
 pub fn create_app(state: AppState) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([CONTENT_TYPE, AUTHORIZATION]);
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            CONTENT_TYPE,
+            AUTHORIZATION,
+            IF_NONE_MATCH,
+            HeaderName::from_static(libs::api_keys::API_KEY_HEADER),
+            HeaderName::from_static(libs::idempotency::IDEMPOTENCY_KEY_HEADER),
+        ])
+        .expose_headers([
+            ETAG,
+            HeaderName::from_static(libs::idempotency::REPLAYED_HEADER),
+        ]);
+
+    // Replays retried requests sent with an Idempotency-Key
+    let idempotent = || {
+        axum::middleware::from_fn_with_state(state.clone(), middlewares::idempotency::idempotency)
+    };
 
     // Router
     let app = Router::new()
-        .route("/register", post(routes::register::register))
+        .route("/auth/nonce", post(routes::auth::nonce))
+        .route("/auth/verify", post(routes::auth::verify))
+        .route("/auth/refresh", post(routes::auth::refresh))
+        .route("/auth/logout", post(routes::auth::logout))
+        .route("/.well-known/jwks.json", get(routes::auth::jwks))
+        .route(
+            "/register",
+            post(routes::register::register).layer(idempotent()),
+        )
         .route("/user", get(routes::user::me))
-        .route("/generate", post(routes::generate::generate_contract))
+        .route(
+            "/api_keys",
+            post(routes::api_keys::create_api_key).get(routes::api_keys::list_api_keys),
+        )
+        .route("/api_keys/{id}", delete(routes::api_keys::revoke_api_key))
+        .nest("/admin", routes::admin::router())
+        .route("/templates", get(routes::templates::list_templates))
+        .route("/templates/{id}", get(routes::templates::get_template))
+        .route(
+            "/generate",
+            post(routes::generate::generate_contract).layer(idempotent()),
+        )
         .route(
             "/generated_contracts",
             get(routes::generate::list_generated_contracts),
         )
+        .route(
+            "/generated_contracts/{id}",
+            get(routes::generate::get_generated_contract)
+                .patch(routes::generate::update_generated_contract)
+                .delete(routes::generate::delete_contract),
+        )
+        .route(
+            "/generated_contracts/{id}/archive",
+            get(routes::generate::download_archive),
+        )
+        .route(
+            "/generated_contracts/{id}/compile",
+            post(routes::compile::compile_contract),
+        )
+        .route(
+            "/generated_contracts/{id}/compilation",
+            get(routes::compile::get_compilation),
+        )
+        .route(
+            "/generated_contracts/{id}/compilation/{artifact}",
+            get(routes::compile::get_artifact),
+        )
+        .route(
+            "/generated_contracts/{id}/audit",
+            post(routes::audit::audit_contract),
+        )
+        .route("/generated_contracts/{id}/abi", get(routes::abi::get_abi))
+        .route(
+            "/generated_contracts/{id}/calldata",
+            post(routes::abi::encode_calldata),
+        )
+        .route(
+            "/generated_contracts/{id}/deployments",
+            post(routes::deployments::record_deployment),
+        )
+        .route(
+            "/generated_contracts/{id}/deploy",
+            post(routes::deployments::deploy_contract).layer(idempotent()),
+        )
+        .route("/deployments", get(routes::deployments::list_deployments))
+        .route(
+            "/deployments/{id}",
+            get(routes::deployments::get_deployment),
+        )
+        .route(
+            "/generated_contracts/{id}/code",
+            put(routes::revisions::update_code),
+        )
+        .route(
+            "/generated_contracts/{id}/regenerate",
+            post(routes::revisions::regenerate_contract).layer(idempotent()),
+        )
+        .route(
+            "/generated_contracts/{id}/revisions",
+            get(routes::revisions::list_revisions),
+        )
+        .route(
+            "/generated_contracts/{id}/revisions/{revision}",
+            get(routes::revisions::get_revision),
+        )
+        .route(
+            "/generated_contracts/{id}/diff",
+            get(routes::revisions::diff_revisions),
+        )
+        .route("/jobs/{id}", get(routes::jobs::get_job))
+        .route("/jobs/{id}/events", get(routes::jobs::job_events))
         .route("/reviews", get(routes::reviews::list_reviews))
         .route("/health", get(routes::health::health))
         // Swagger UI at /docs and OpenAPI JSON at /api-docs/openapi.json
@@ -0,0 +1,1086 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use utoipa::ToSchema;
+
+use crate::libs::{
+    cairo::{is_ident, strip_line},
+    scarb,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StorageVar {
+    pub name: String,
+    /// Fully qualified when the type is known, as written otherwise
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// ABI of a contract as far as its source tells
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceAbi {
+    /// Starknet ABI entries (`impl`, `interface`, `function`, `constructor`,
+    /// `l1_handler`, `struct`, `enum`, `event`)
+    pub abi: Vec<Value>,
+    /// Variables of the contract's `#[storage]` struct
+    pub storage: Vec<StorageVar>,
+}
+
+/// ABI of a generated contract's `code`, under the package its Scarb.toml
+/// names (or would name, for contracts stored without one).
+pub fn of_contract(contract_name: &str, scarb_config: Option<&str>, code: &str) -> SourceAbi {
+    let package = scarb_config
+        .and_then(scarb::package_name_of)
+        .unwrap_or_else(|| scarb::package_name(contract_name));
+    extract(&package, code)
+}
+
+/// ABI entries of a Sierra contract class, whether `abi` holds them as an
+/// array (Scarb) or as a JSON string (declare transactions).
+pub fn sierra_abi(sierra: &Value) -> Option<Vec<Value>> {
+    match sierra.get("abi")? {
+        Value::Array(entries) => Some(entries.clone()),
+        Value::String(s) => serde_json::from_str(s).ok(),
+        _ => None,
+    }
+}
+
+// Types the corelib exports into every module, by the name code uses.
+const CORE_TYPES: &[(&str, &str)] = &[
+    ("felt252", "core::felt252"),
+    ("bool", "core::bool"),
+    ("u8", "core::integer::u8"),
+    ("u16", "core::integer::u16"),
+    ("u32", "core::integer::u32"),
+    ("usize", "core::integer::u32"),
+    ("u64", "core::integer::u64"),
+    ("u128", "core::integer::u128"),
+    ("u256", "core::integer::u256"),
+    ("i8", "core::integer::i8"),
+    ("i16", "core::integer::i16"),
+    ("i32", "core::integer::i32"),
+    ("i64", "core::integer::i64"),
+    ("i128", "core::integer::i128"),
+    ("bytes31", "core::bytes_31::bytes31"),
+    ("ByteArray", "core::byte_array::ByteArray"),
+    ("Array", "core::array::Array"),
+    ("Span", "core::array::Span"),
+    ("Option", "core::option::Option"),
+    ("Result", "core::result::Result"),
+    (
+        "ContractAddress",
+        "core::starknet::contract_address::ContractAddress",
+    ),
+    ("ClassHash", "core::starknet::class_hash::ClassHash"),
+    ("EthAddress", "core::starknet::eth_address::EthAddress"),
+    ("Map", "core::starknet::storage::map::Map"),
+];
+
+fn core_type(name: &str) -> Option<&'static str> {
+    CORE_TYPES
+        .iter()
+        .find(|(short, _)| *short == name)
+        .map(|(_, full)| *full)
+}
+
+// Code as tokens: identifiers, `::`, `->`, `=>` and single characters.
+fn tokenize(source: &str) -> Vec<String> {
+    let code: Vec<String> = source.lines().map(strip_line).collect();
+    let code = code.join("\n");
+    let mut tokens = Vec::new();
+    let mut chars = code.char_indices().peekable();
+    while let Some((at, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if is_ident(c) {
+            let mut end = at + c.len_utf8();
+            while let Some(&(i, next)) = chars.peek() {
+                if !is_ident(next) {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+            tokens.push(code[at..end].to_string());
+            continue;
+        }
+        let next = chars.peek().map(|&(_, n)| n);
+        let pair = match (c, next) {
+            (':', Some(':')) => Some("::"),
+            ('-', Some('>')) => Some("->"),
+            ('=', Some('>')) => Some("=>"),
+            _ => None,
+        };
+        match pair {
+            Some(pair) => {
+                chars.next();
+                tokens.push(pair.to_string());
+            }
+            None => tokens.push(c.to_string()),
+        }
+    }
+    tokens
+}
+
+#[derive(Debug, Clone)]
+enum Ty {
+    Path {
+        segments: Vec<String>,
+        args: Vec<Ty>,
+    },
+    Tuple(Vec<Ty>),
+    Snapshot(Box<Ty>),
+    /// Anything else (fixed-size arrays), as written
+    Other(String),
+}
+
+#[derive(Debug, Clone)]
+struct Param {
+    name: String,
+    ty: Ty,
+}
+
+#[derive(Debug, Clone)]
+struct Func {
+    name: String,
+    attrs: Vec<String>,
+    inputs: Vec<Param>,
+    output: Option<Ty>,
+    /// Takes `self: @ContractState`
+    view: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Member {
+    name: String,
+    attrs: Vec<String>,
+    /// `None` for unit enum variants
+    ty: Option<Ty>,
+}
+
+#[derive(Debug, Clone)]
+struct TypeDef {
+    path: String,
+    attrs: Vec<String>,
+    is_enum: bool,
+    members: Vec<Member>,
+    /// Defined in the `#[starknet::contract]` module
+    in_contract: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Interface {
+    path: String,
+    functions: Vec<Func>,
+}
+
+#[derive(Debug, Clone)]
+struct Impl {
+    name: String,
+    attrs: Vec<String>,
+    /// Name of the implemented trait, without generics
+    of: String,
+    /// Module the impl is in
+    module: String,
+    functions: Vec<Func>,
+}
+
+#[derive(Default)]
+struct Source {
+    types: Vec<TypeDef>,
+    interfaces: Vec<Interface>,
+    impls: Vec<Impl>,
+    /// Functions directly in the contract module
+    functions: Vec<Func>,
+    imports: HashMap<String, String>,
+}
+
+fn has_attr(attrs: &[String], attr: &str) -> bool {
+    attrs.iter().any(|a| a == attr)
+}
+
+fn derives(attrs: &[String], name: &str) -> bool {
+    attrs.iter().any(|a| {
+        a.strip_prefix("derive(")
+            .is_some_and(|list| list.trim_end_matches(')').split(',').any(|d| d == name))
+    })
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Skips the group opened by the current token, up to its closing token.
+    fn skip_group(&mut self) {
+        let (open, close) = match self.peek() {
+            Some("{") => ("{", "}"),
+            Some("(") => ("(", ")"),
+            Some("[") => ("[", "]"),
+            Some("<") => ("<", ">"),
+            _ => return,
+        };
+        let mut depth = 0;
+        while let Some(token) = self.next() {
+            if token == open {
+                depth += 1;
+            } else if token == close {
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            }
+        }
+    }
+
+    // Skips to the end of the current item: a `;` or a `{ ... }` block.
+    fn skip_item(&mut self) {
+        while let Some(token) = self.peek() {
+            match token {
+                ";" => {
+                    self.pos += 1;
+                    return;
+                }
+                "{" => {
+                    self.skip_group();
+                    return;
+                }
+                "}" => return,
+                "(" | "[" => self.skip_group(),
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    // `#[...]` attributes, without spaces, e.g. `derive(Drop,starknet::Event)`.
+    fn attributes(&mut self) -> Vec<String> {
+        let mut attrs = Vec::new();
+        while self.peek() == Some("#")
+            && self.tokens.get(self.pos + 1).map(String::as_str) == Some("[")
+        {
+            self.pos += 2;
+            let mut depth = 1;
+            let mut attr = String::new();
+            while let Some(token) = self.next() {
+                match token.as_str() {
+                    "[" => depth += 1,
+                    "]" => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                attr.push_str(&token);
+            }
+            attrs.push(attr);
+        }
+        attrs
+    }
+
+    fn visibility(&mut self) {
+        if self.eat("pub") && self.peek() == Some("(") {
+            self.skip_group();
+        }
+    }
+
+    fn ty(&mut self) -> Ty {
+        if self.eat("@") {
+            return Ty::Snapshot(Box::new(self.ty()));
+        }
+        match self.peek() {
+            Some("(") => {
+                self.pos += 1;
+                return Ty::Tuple(self.ty_list(")"));
+            }
+            Some("[") => {
+                let start = self.pos;
+                self.skip_group();
+                return Ty::Other(self.tokens[start..self.pos].join(""));
+            }
+            _ => {}
+        }
+
+        let mut segments = Vec::new();
+        let mut args = Vec::new();
+        while let Some(token) = self.peek() {
+            if !token.starts_with(is_ident) {
+                break;
+            }
+            segments.push(self.next().unwrap_or_default());
+            // `Array<T>`, or `Array::<T>` in expressions
+            let turbofish = self.peek() == Some("::")
+                && self.tokens.get(self.pos + 1).map(String::as_str) == Some("<");
+            if turbofish {
+                self.pos += 1;
+            }
+            if self.eat("<") {
+                args = self.ty_list(">");
+                break;
+            }
+            if !self.eat("::") {
+                break;
+            }
+        }
+        Ty::Path { segments, args }
+    }
+
+    // Comma-separated types up to `close`. Stops at a token that starts no
+    // type, such as the `;` of `(u8; u16)`, so malformed code cannot stall
+    // the parser.
+    fn ty_list(&mut self, close: &str) -> Vec<Ty> {
+        let mut items = Vec::new();
+        while !self.eat(close) && self.peek().is_some() {
+            let start = self.pos;
+            let ty = self.ty();
+            if self.pos == start {
+                break;
+            }
+            items.push(ty);
+            self.eat(",");
+        }
+        items
+    }
+
+    // After `fn`: name, parameters, return type, and body or `;`.
+    fn function(&mut self, attrs: Vec<String>) -> Func {
+        let name = self.next().unwrap_or_default();
+        if self.peek() == Some("<") {
+            self.skip_group();
+        }
+        let mut func = Func {
+            name,
+            attrs,
+            inputs: Vec::new(),
+            output: None,
+            view: false,
+        };
+        if self.eat("(") {
+            while !self.eat(")") && self.peek().is_some() {
+                self.eat("ref");
+                self.eat("mut");
+                let name = self.next().unwrap_or_default();
+                self.eat(":");
+                let ty = self.ty();
+                if name == "self" {
+                    func.view = matches!(ty, Ty::Snapshot(_));
+                } else {
+                    func.inputs.push(Param { name, ty });
+                }
+                self.eat(",");
+            }
+        }
+        if self.eat("->") {
+            func.output = Some(self.ty());
+        }
+        self.skip_item();
+        func
+    }
+
+    // Fields of a struct or variants of an enum, after the name.
+    fn members(&mut self, is_enum: bool) -> Vec<Member> {
+        if self.peek() == Some("<") {
+            self.skip_group();
+        }
+        let mut members = Vec::new();
+        if !self.eat("{") {
+            self.skip_item();
+            return members;
+        }
+        while !self.eat("}") && self.peek().is_some() {
+            let attrs = self.attributes();
+            self.visibility();
+            let name = self.next().unwrap_or_default();
+            let ty = if self.eat(":") { Some(self.ty()) } else { None };
+            // Explicit discriminants
+            while !matches!(self.peek(), Some(",") | Some("}") | None) {
+                self.pos += 1;
+            }
+            self.eat(",");
+            if !name.is_empty() && (is_enum || ty.is_some()) {
+                members.push(Member { name, attrs, ty });
+            }
+        }
+        members
+    }
+
+    // `use` tree after `use`, into `imports` as name => full path.
+    fn use_tree(&mut self, mut prefix: Vec<String>, imports: &mut HashMap<String, String>) {
+        loop {
+            if self.eat("{") {
+                while !self.eat("}") && self.peek().is_some() {
+                    self.use_tree(prefix.clone(), imports);
+                    self.eat(",");
+                }
+                return;
+            }
+            let Some(segment) = self.next() else {
+                return;
+            };
+            if segment == "*" {
+                return;
+            }
+            if self.eat("::") {
+                prefix.push(segment);
+                continue;
+            }
+            let alias = if self.eat("as") {
+                self.next().unwrap_or_default()
+            } else {
+                segment.clone()
+            };
+            prefix.push(segment);
+            imports.insert(alias, prefix.join("::"));
+            return;
+        }
+    }
+
+    // Items up to the closing brace of the current module, or the end.
+    fn items(&mut self, module: &[String], in_contract: bool, out: &mut Source) {
+        loop {
+            match self.peek() {
+                None => return,
+                Some("}") => {
+                    self.pos += 1;
+                    return;
+                }
+                _ => {}
+            }
+            let attrs = self.attributes();
+            self.visibility();
+            let Some(keyword) = self.next() else {
+                return;
+            };
+            match keyword.as_str() {
+                "mod" => {
+                    let name = self.next().unwrap_or_default();
+                    if self.eat("{") {
+                        let mut path = module.to_vec();
+                        path.push(name);
+                        let contract = has_attr(&attrs, "starknet::contract");
+                        self.items(&path, contract, out);
+                    } else {
+                        self.skip_item();
+                    }
+                }
+                "use" => {
+                    self.use_tree(Vec::new(), &mut out.imports);
+                    self.skip_item();
+                }
+                "trait" => {
+                    let name = self.next().unwrap_or_default();
+                    if self.peek() == Some("<") {
+                        self.skip_group();
+                    }
+                    let functions = self.block_functions();
+                    if has_attr(&attrs, "starknet::interface") {
+                        out.interfaces.push(Interface {
+                            path: qualify(module, &name),
+                            functions,
+                        });
+                    }
+                }
+                "impl" => {
+                    let name = self.next().unwrap_or_default();
+                    if self.peek() == Some("<") {
+                        self.skip_group();
+                    }
+                    if !self.eat("of") {
+                        // `impl X = Component::Y<ContractState>;`
+                        self.skip_item();
+                        continue;
+                    }
+                    let of = match self.ty() {
+                        Ty::Path { segments, .. } => segments.last().cloned().unwrap_or_default(),
+                        _ => String::new(),
+                    };
+                    let functions = self.block_functions();
+                    if in_contract {
+                        out.impls.push(Impl {
+                            name,
+                            attrs,
+                            of,
+                            module: module.join("::"),
+                            functions,
+                        });
+                    }
+                }
+                "fn" => {
+                    let func = self.function(attrs);
+                    if in_contract {
+                        out.functions.push(func);
+                    }
+                }
+                "struct" | "enum" => {
+                    let is_enum = keyword == "enum";
+                    let name = self.next().unwrap_or_default();
+                    let members = self.members(is_enum);
+                    out.types.push(TypeDef {
+                        path: qualify(module, &name),
+                        attrs,
+                        is_enum,
+                        members,
+                        in_contract,
+                    });
+                }
+                "(" | "[" | "{" => {
+                    self.pos -= 1;
+                    self.skip_group();
+                }
+                _ => self.skip_item(),
+            }
+        }
+    }
+
+    // Functions in the `{ ... }` block of a trait or impl.
+    fn block_functions(&mut self) -> Vec<Func> {
+        let mut functions = Vec::new();
+        if !self.eat("{") {
+            self.skip_item();
+            return functions;
+        }
+        while !self.eat("}") && self.peek().is_some() {
+            let attrs = self.attributes();
+            self.visibility();
+            if self.eat("fn") {
+                functions.push(self.function(attrs));
+            } else {
+                self.skip_item();
+            }
+        }
+        functions
+    }
+}
+
+fn qualify(module: &[String], name: &str) -> String {
+    let mut path = module.to_vec();
+    path.push(name.to_string());
+    path.join("::")
+}
+
+struct Resolver<'a> {
+    /// Short name => fully qualified name of types defined in the source
+    defined: HashMap<String, String>,
+    imports: &'a HashMap<String, String>,
+}
+
+impl Resolver<'_> {
+    fn path(&self, segments: &[String]) -> String {
+        let Some(name) = segments.last() else {
+            return String::new();
+        };
+        let first = segments[0].as_str();
+        if segments.len() == 1 {
+            if let Some(path) = self.defined.get(name) {
+                return path.clone();
+            }
+            if let Some(path) = core_type(name) {
+                return path.to_string();
+            }
+        }
+        match first {
+            "core" => segments.join("::"),
+            "starknet" => core_type(name).map_or_else(|| segments.join("::"), str::to_string),
+            "super" | "self" | "crate" => self
+                .defined
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| segments.join("::")),
+            _ => match self.imports.get(first) {
+                Some(path) if !path.starts_with("super::") && !path.starts_with("self::") => {
+                    let mut full = vec![path.clone()];
+                    full.extend(segments[1..].iter().cloned());
+                    full.join("::")
+                }
+                _ => segments.join("::"),
+            },
+        }
+    }
+
+    fn render(&self, ty: &Ty) -> String {
+        match ty {
+            Ty::Path { segments, args } if args.is_empty() => self.path(segments),
+            Ty::Path { segments, args } => {
+                let args: Vec<String> = args.iter().map(|a| self.render(a)).collect();
+                format!("{}::<{}>", self.path(segments), args.join(", "))
+            }
+            Ty::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|i| self.render(i)).collect();
+                format!("({})", items.join(", "))
+            }
+            Ty::Snapshot(inner) => format!("@{}", self.render(inner)),
+            Ty::Other(s) => s.clone(),
+        }
+    }
+
+    // Types defined in the source that `ty` mentions.
+    fn defined_in(&self, ty: &Ty, out: &mut Vec<String>) {
+        match ty {
+            Ty::Path { segments, args } => {
+                let path = self.path(segments);
+                if self.defined.values().any(|p| *p == path) && !out.contains(&path) {
+                    out.push(path);
+                }
+                for arg in args {
+                    self.defined_in(arg, out);
+                }
+            }
+            Ty::Tuple(items) => {
+                for item in items {
+                    self.defined_in(item, out);
+                }
+            }
+            Ty::Snapshot(inner) => self.defined_in(inner, out),
+            Ty::Other(_) => {}
+        }
+    }
+
+    fn function(&self, kind: &str, f: &Func) -> Value {
+        let inputs: Vec<Value> = f
+            .inputs
+            .iter()
+            .map(|p| json!({"name": p.name, "type": self.render(&p.ty)}))
+            .collect();
+        let outputs: Vec<Value> = match &f.output {
+            Some(Ty::Tuple(items)) if items.is_empty() => Vec::new(),
+            Some(ty) => vec![json!({"type": self.render(ty)})],
+            None => Vec::new(),
+        };
+        let mutability = if f.view { "view" } else { "external" };
+        if kind == "constructor" {
+            return json!({"type": kind, "name": f.name, "inputs": inputs});
+        }
+        json!({
+            "type": kind,
+            "name": f.name,
+            "inputs": inputs,
+            "outputs": outputs,
+            "state_mutability": mutability,
+        })
+    }
+}
+
+/// Extract the ABI of the `#[starknet::contract]` module in Cairo `source`,
+/// with type paths rooted at `package` like the compiler's.
+///
+/// This reads the source without compiling it: impls embedded from
+/// components (`impl X = Component::Y<ContractState>;`) are not resolved,
+/// and names imported from other crates keep the path they were imported
+/// by. The Sierra ABI of a build is exact.
+pub fn extract(package: &str, source: &str) -> SourceAbi {
+    let mut parser = Parser {
+        tokens: tokenize(source),
+        pos: 0,
+    };
+    let mut src = Source::default();
+    parser.items(&[package.to_string()], false, &mut src);
+
+    let resolver = Resolver {
+        defined: src
+            .types
+            .iter()
+            .map(|t| {
+                let name = t.path.rsplit("::").next().unwrap_or_default();
+                (name.to_string(), t.path.clone())
+            })
+            .collect(),
+        imports: &src.imports,
+    };
+
+    let mut abi = Vec::new();
+    // Types used by entry points, in order of first use
+    let mut used = Vec::new();
+    let note_types = |f: &Func, used: &mut Vec<String>| {
+        for p in &f.inputs {
+            resolver.defined_in(&p.ty, used);
+        }
+        if let Some(ty) = &f.output {
+            resolver.defined_in(ty, used);
+        }
+    };
+
+    for imp in src
+        .impls
+        .iter()
+        .filter(|i| has_attr(&i.attrs, "abi(embed_v0)"))
+    {
+        let interface = src
+            .interfaces
+            .iter()
+            .find(|i| i.path.rsplit("::").next() == Some(imp.of.as_str()))
+            .cloned()
+            // `#[generate_trait]` impls define their trait themselves
+            .unwrap_or_else(|| Interface {
+                path: format!("{}::{}", imp.module, imp.of),
+                functions: imp.functions.clone(),
+            });
+        abi.push(json!({"type": "impl", "name": imp.name, "interface_name": interface.path}));
+        let items: Vec<Value> = interface
+            .functions
+            .iter()
+            .map(|f| {
+                note_types(f, &mut used);
+                resolver.function("function", f)
+            })
+            .collect();
+        abi.push(json!({"type": "interface", "name": interface.path, "items": items}));
+    }
+
+    let per_item = src
+        .impls
+        .iter()
+        .filter(|i| has_attr(&i.attrs, "abi(per_item)"))
+        .flat_map(|i| i.functions.iter());
+    let entry_points: Vec<&Func> = src.functions.iter().chain(per_item).collect();
+    for (attr, kind) in [
+        ("external(v0)", "function"),
+        ("constructor", "constructor"),
+        ("l1_handler", "l1_handler"),
+    ] {
+        for f in entry_points.iter().filter(|f| has_attr(&f.attrs, attr)) {
+            note_types(f, &mut used);
+            abi.push(resolver.function(kind, f));
+        }
+    }
+
+    // Structs and enums of the entry points, with the ones they contain
+    let mut i = 0;
+    while i < used.len() {
+        if let Some(def) = src.types.iter().find(|t| t.path == used[i]) {
+            for ty in def.members.iter().filter_map(|m| m.ty.as_ref()) {
+                resolver.defined_in(ty, &mut used);
+            }
+        }
+        i += 1;
+    }
+    for def in used
+        .iter()
+        .filter_map(|path| src.types.iter().find(|t| t.path == *path))
+    {
+        let members: Vec<Value> = def
+            .members
+            .iter()
+            .map(|m| {
+                let ty =
+                    m.ty.as_ref()
+                        .map_or("()".to_string(), |t| resolver.render(t));
+                json!({"name": m.name, "type": ty})
+            })
+            .collect();
+        abi.push(if def.is_enum {
+            json!({"type": "enum", "name": def.path, "variants": members})
+        } else {
+            json!({"type": "struct", "name": def.path, "members": members})
+        });
+    }
+
+    // Events, the contract's `#[event]` enum last
+    let mut events: Vec<&TypeDef> = src
+        .types
+        .iter()
+        .filter(|t| t.in_contract && derives(&t.attrs, "starknet::Event"))
+        .collect();
+    events.sort_by_key(|t| has_attr(&t.attrs, "event"));
+    let mut seen = HashSet::new();
+    for def in events.into_iter().filter(|t| seen.insert(t.path.clone())) {
+        let members: Vec<Value> = def
+            .members
+            .iter()
+            .map(|m| {
+                let ty =
+                    m.ty.as_ref()
+                        .map_or("()".to_string(), |t| resolver.render(t));
+                let kind = if has_attr(&m.attrs, "flat") {
+                    "flat"
+                } else if def.is_enum {
+                    "nested"
+                } else if has_attr(&m.attrs, "key") {
+                    "key"
+                } else {
+                    "data"
+                };
+                json!({"name": m.name, "type": ty, "kind": kind})
+            })
+            .collect();
+        abi.push(if def.is_enum {
+            json!({"type": "event", "name": def.path, "kind": "enum", "variants": members})
+        } else {
+            json!({"type": "event", "name": def.path, "kind": "struct", "members": members})
+        });
+    }
+
+    let storage = src
+        .types
+        .iter()
+        .find(|t| t.in_contract && has_attr(&t.attrs, "storage"))
+        .map(|t| {
+            t.members
+                .iter()
+                .filter_map(|m| {
+                    Some(StorageVar {
+                        name: m.name.clone(),
+                        ty: resolver.render(m.ty.as_ref()?),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    SourceAbi { abi, storage }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry<'a>(abi: &'a SourceAbi, kind: &str, name: &str) -> &'a Value {
+        abi.abi
+            .iter()
+            .find(|e| e["type"] == kind && e["name"] == name)
+            .unwrap_or_else(|| panic!("no {kind} {name} in {:#?}", abi.abi))
+    }
+
+    #[test]
+    fn interface_contract() {
+        let source = r#"use starknet::ContractAddress;
+
+#[derive(Drop, Serde, starknet::Store)]
+pub struct Listing {
+    pub seller: ContractAddress,
+    pub price: u256,
+    pub state: ListingState,
+}
+
+#[derive(Drop, Serde, starknet::Store)]
+pub enum ListingState {
+    #[default]
+    Open,
+    Sold: ContractAddress,
+}
+
+#[starknet::interface]
+pub trait IMarket<TContractState> {
+    fn list(ref self: TContractState, price: u256) -> u64;
+    fn listing(self: @TContractState, id: u64) -> Listing;
+    fn listings(self: @TContractState, ids: Span<u64>) -> Array<(u64, Listing)>;
+}
+
+#[starknet::contract]
+mod Market {
+    use starknet::storage::Map;
+    use starknet::{ContractAddress, get_caller_address};
+    use super::{IMarket, Listing};
+
+    #[storage]
+    struct Storage {
+        next_id: u64,
+        listings: Map<u64, Listing>,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        Listed: Listed,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Listed {
+        #[key]
+        id: u64,
+        price: u256,
+    }
+
+    #[constructor]
+    fn constructor(ref self: ContractState, first_id: u64) {
+        self.next_id.write(first_id);
+    }
+
+    #[abi(embed_v0)]
+    impl MarketImpl of super::IMarket<ContractState> {
+        fn list(ref self: ContractState, price: u256) -> u64 {
+            let id = self.next_id.read();
+            self.emit(Listed { id, price });
+            id
+        }
+
+        fn listing(self: @ContractState, id: u64) -> Listing {
+            self.listings.read(id)
+        }
+
+        fn listings(self: @ContractState, ids: Span<u64>) -> Array<(u64, Listing)> {
+            array![]
+        }
+    }
+
+    #[l1_handler]
+    fn on_deposit(ref self: ContractState, from_address: felt252, amount: u256) {}
+}
+"#;
+        let abi = extract("market", source);
+
+        assert_eq!(
+            entry(&abi, "impl", "MarketImpl")["interface_name"],
+            "market::IMarket"
+        );
+        let interface = entry(&abi, "interface", "market::IMarket");
+        assert_eq!(
+            interface["items"][0],
+            json!({
+                "type": "function",
+                "name": "list",
+                "inputs": [{"name": "price", "type": "core::integer::u256"}],
+                "outputs": [{"type": "core::integer::u64"}],
+                "state_mutability": "external",
+            })
+        );
+        assert_eq!(interface["items"][1]["state_mutability"], "view");
+        assert_eq!(
+            interface["items"][2]["outputs"][0]["type"],
+            "core::array::Array::<(core::integer::u64, market::Listing)>"
+        );
+        assert_eq!(
+            entry(&abi, "constructor", "constructor")["inputs"],
+            json!([{"name": "first_id", "type": "core::integer::u64"}])
+        );
+        assert_eq!(
+            entry(&abi, "l1_handler", "on_deposit")["inputs"][0]["type"],
+            "core::felt252"
+        );
+        assert_eq!(
+            entry(&abi, "struct", "market::Listing")["members"][0],
+            json!({"name": "seller", "type": "core::starknet::contract_address::ContractAddress"})
+        );
+        assert_eq!(
+            entry(&abi, "enum", "market::ListingState")["variants"],
+            json!([
+                {"name": "Open", "type": "()"},
+                {"name": "Sold", "type": "core::starknet::contract_address::ContractAddress"},
+            ])
+        );
+        assert_eq!(
+            entry(&abi, "event", "market::Market::Listed")["members"],
+            json!([
+                {"name": "id", "type": "core::integer::u64", "kind": "key"},
+                {"name": "price", "type": "core::integer::u256", "kind": "data"},
+            ])
+        );
+        let last = abi.abi.last().unwrap();
+        assert_eq!(last["name"], "market::Market::Event");
+        assert_eq!(last["variants"][0]["kind"], "nested");
+        assert_eq!(
+            abi.storage,
+            vec![
+                StorageVar {
+                    name: "next_id".into(),
+                    ty: "core::integer::u64".into(),
+                },
+                StorageVar {
+                    name: "listings".into(),
+                    ty: "core::starknet::storage::map::Map::<core::integer::u64, market::Listing>"
+                        .into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn per_item_template() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/templates");
+        let source = std::fs::read_to_string(format!("{dir}/ownable_counter.cairo")).unwrap();
+        let abi = extract("golden", &source);
+
+        let functions: Vec<(&str, &str)> = abi
+            .abi
+            .iter()
+            .filter(|e| e["type"] == "function")
+            .map(|e| {
+                (
+                    e["name"].as_str().unwrap(),
+                    e["state_mutability"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                ("get_count", "view"),
+                ("increment", "external"),
+                ("decrement", "external"),
+                ("reset", "external"),
+            ]
+        );
+        assert_eq!(
+            entry(&abi, "event", "golden::Golden::Event")["variants"][1],
+            json!({
+                "name": "OwnableEvent",
+                "type": "openzeppelin::access::ownable::OwnableComponent::Event",
+                "kind": "flat",
+            })
+        );
+        assert_eq!(abi.storage[0].ty, "core::integer::u64");
+    }
+
+    #[test]
+    fn templates_have_entry_points() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/templates");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            let abi = extract("golden", &source);
+            assert!(
+                abi.abi.iter().any(|e| e["type"] == "constructor"),
+                "{}",
+                path.display()
+            );
+            assert!(!abi.storage.is_empty(), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn sierra_abi_as_string_or_array() {
+        let entries = json!([{"type": "function", "name": "f"}]);
+        assert_eq!(
+            sierra_abi(&json!({"abi": entries})),
+            Some(vec![entries[0].clone()])
+        );
+        assert_eq!(
+            sierra_abi(&json!({"abi": entries.to_string()})),
+            Some(vec![entries[0].clone()])
+        );
+        assert_eq!(sierra_abi(&json!({})), None);
+    }
+
+    #[test]
+    fn malformed_types_terminate() {
+        for source in [
+            "#[starknet::contract]\nmod C {\n    fn f(a: (u8; u16)) {}\n}\n",
+            "#[starknet::contract]\nmod C {\n    #[storage]\n    struct Storage { a: Array<u8 }\n}\n",
+            "fn f(a: Map<, (>) -> (u8 u16",
+        ] {
+            extract("pkg", source);
+        }
+    }
+}
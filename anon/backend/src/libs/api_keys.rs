@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::libs::{
+    error::{ApiError, map_sqlx_error},
+    session::{generate_refresh_token, hash_token},
+};
+
+// Keys look like `sfk_<prefix>_<secret>`; the prefix is stored in clear so a
+// key can be found and shown in listings without keeping the secret around.
+pub const KEY_PREFIX: &str = "sfk_";
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Scope {
+    #[serde(rename = "contracts:read")]
+    ContractsRead,
+    #[serde(rename = "contracts:write")]
+    ContractsWrite,
+    #[serde(rename = "reviews:write")]
+    ReviewsWrite,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [
+        Scope::ContractsRead,
+        Scope::ContractsWrite,
+        Scope::ReviewsWrite,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::ContractsRead => "contracts:read",
+            Scope::ContractsWrite => "contracts:write",
+            Scope::ReviewsWrite => "reviews:write",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|scope| scope.as_str() == s)
+    }
+}
+
+// The key owner and what the key is allowed to do.
+#[derive(Debug, Clone)]
+pub struct ApiKeyIdentity {
+    pub key_id: i64,
+    pub wallet: String,
+    pub scopes: Vec<Scope>,
+}
+
+// id, wallet, key_hash, scopes, expires_at, revoked_at, user disabled_at
+type ApiKeyRow = (
+    i64,
+    String,
+    String,
+    Vec<String>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+);
+
+// Returns `(prefix, full key)`. Only the full key's hash is persisted.
+pub fn generate() -> (String, String) {
+    let prefix = format!("{:012x}", rand::random::<u64>() & 0xffff_ffff_ffff);
+    let key = format!("{}{}_{}", KEY_PREFIX, prefix, generate_refresh_token());
+    (prefix, key)
+}
+
+pub fn prefix_of(key: &str) -> Option<&str> {
+    let (prefix, secret) = key.strip_prefix(KEY_PREFIX)?.split_once('_')?;
+    (!prefix.is_empty() && !secret.is_empty()).then_some(prefix)
+}
+
+pub fn scopes_from_db(scopes: &[String]) -> Vec<Scope> {
+    scopes.iter().filter_map(|s| Scope::parse(s)).collect()
+}
+
+pub async fn authenticate(pool: &PgPool, key: &str) -> Result<ApiKeyIdentity, ApiError> {
+    let prefix = prefix_of(key).ok_or(ApiError::Unauthorized("invalid api key"))?;
+
+    let rec: Option<ApiKeyRow> = sqlx::query_as(
+        r#"SELECT k.id, u.wallet, k.key_hash, k.scopes, k.expires_at, k.revoked_at, u.disabled_at
+           FROM api_keys k
+           JOIN users u ON u.id = k.user_id
+           WHERE k.prefix = $1"#,
+    )
+    .bind(prefix)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    let (key_id, wallet, key_hash, scopes, expires_at, revoked_at, disabled_at) =
+        rec.ok_or(ApiError::Unauthorized("invalid api key"))?;
+
+    if key_hash != hash_token(key) {
+        return Err(ApiError::Unauthorized("invalid api key"));
+    }
+    if revoked_at.is_some() {
+        return Err(ApiError::Unauthorized("api key revoked"));
+    }
+    if expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(ApiError::Unauthorized("api key expired"));
+    }
+    if disabled_at.is_some() {
+        return Err(ApiError::Forbidden("account disabled"));
+    }
+
+    // Keep write traffic down for keys hammered by CI
+    sqlx::query(
+        r#"UPDATE api_keys SET last_used_at = NOW()
+           WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')"#,
+    )
+    .bind(key_id)
+    .execute(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    Ok(ApiKeyIdentity {
+        key_id,
+        wallet,
+        scopes: scopes_from_db(&scopes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_keys_expose_their_prefix() {
+        let (prefix, key) = generate();
+        assert!(key.starts_with(KEY_PREFIX));
+        assert_eq!(prefix_of(&key), Some(prefix.as_str()));
+        assert_eq!(prefix.len(), 12);
+    }
+
+    #[test]
+    fn malformed_keys_have_no_prefix() {
+        assert_eq!(prefix_of("sfk_abc"), None);
+        assert_eq!(prefix_of("sfk__secret"), None);
+        assert_eq!(prefix_of("abc_def"), None);
+    }
+
+    #[test]
+    fn scopes_round_trip() {
+        for scope in Scope::ALL {
+            assert_eq!(Scope::parse(scope.as_str()), Some(scope));
+        }
+        assert_eq!(Scope::parse("admin"), None);
+    }
+}
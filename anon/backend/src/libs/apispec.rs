@@ -1,4 +1,4 @@
-use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa::{Modify, OpenApi};
 
 struct SecurityAddon;
@@ -16,6 +16,29 @@ impl Modify for SecurityAddon {
                     .build(),
             ),
         );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-API-Key",
+                "Scoped API key (`sfk_...`) created through /api_keys",
+            ))),
+        );
+        openapi.components = Some(components);
+    }
+}
+
+// Publishes each template's parameters schema (e.g. `Erc20Parameters`) so
+// clients can build forms for `/generate`.
+struct TemplateSchemasAddon;
+
+impl Modify for TemplateSchemasAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let mut components = openapi.components.take().unwrap_or_default();
+        for template in crate::libs::templates::TEMPLATES {
+            let schema = serde_json::from_value(template.parameters_schema())
+                .expect("template parameters schema is valid OpenAPI");
+            components.schemas.insert(template.schema_name(), schema);
+        }
         openapi.components = Some(components);
     }
 }
@@ -23,35 +46,126 @@ impl Modify for SecurityAddon {
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        crate::routes::auth::nonce,
+        crate::routes::auth::verify,
+        crate::routes::auth::refresh,
+        crate::routes::auth::logout,
+        crate::routes::auth::jwks,
         crate::routes::register::register,
         crate::routes::user::me,
+        crate::routes::api_keys::create_api_key,
+        crate::routes::api_keys::list_api_keys,
+        crate::routes::api_keys::revoke_api_key,
+        crate::routes::admin::list_users,
+        crate::routes::admin::disable_user,
+        crate::routes::admin::enable_user,
+        crate::routes::admin::delete_review,
+        crate::routes::admin::delete_generated_contract,
         crate::routes::health::healthz,
+        crate::routes::templates::list_templates,
+        crate::routes::templates::get_template,
         crate::routes::generate::generate_contract,
         crate::routes::generate::list_generated_contracts,
+        crate::routes::generate::get_generated_contract,
+        crate::routes::generate::update_generated_contract,
+        crate::routes::generate::delete_contract,
+        crate::routes::generate::download_archive,
+        crate::routes::compile::compile_contract,
+        crate::routes::compile::get_compilation,
+        crate::routes::compile::get_artifact,
+        crate::routes::audit::audit_contract,
+        crate::routes::abi::get_abi,
+        crate::routes::abi::encode_calldata,
+        crate::routes::deployments::record_deployment,
+        crate::routes::deployments::deploy_contract,
+        crate::routes::deployments::list_deployments,
+        crate::routes::deployments::get_deployment,
+        crate::routes::revisions::update_code,
+        crate::routes::revisions::regenerate_contract,
+        crate::routes::revisions::list_revisions,
+        crate::routes::revisions::get_revision,
+        crate::routes::revisions::diff_revisions,
+        crate::routes::jobs::get_job,
+        crate::routes::jobs::job_events,
         crate::routes::reviews::list_reviews
     ),
     components(
         schemas(
+            crate::routes::auth::NonceReq,
+            crate::routes::auth::NonceRes,
+            crate::routes::auth::VerifyReq,
+            crate::routes::auth::VerifyRes,
+            crate::routes::auth::RefreshReq,
+            crate::routes::auth::LogoutReq,
             crate::routes::register::RegisterReq,
             crate::routes::register::RegisterRes,
             crate::routes::user::UserMeRes,
             crate::routes::user::ProfilePublic,
+            // API keys
+            crate::libs::api_keys::Scope,
+            crate::routes::api_keys::CreateApiKeyReq,
+            crate::routes::api_keys::CreateApiKeyRes,
+            crate::routes::api_keys::ApiKeyItem,
+            crate::routes::api_keys::ApiKeysListRes,
+            // Admin
+            crate::libs::roles::Role,
+            crate::routes::admin::AdminUserItem,
+            crate::routes::admin::AdminUsersListRes,
             crate::libs::error::ErrorBody,
+            crate::libs::error::FieldError,
             crate::routes::health::HealthzResponse,
+            // Templates
+            crate::routes::templates::TemplateSummary,
+            crate::routes::templates::TemplatesListRes,
+            crate::routes::templates::TemplateExample,
+            crate::routes::templates::TemplateDetail,
             // Contracts
+            crate::libs::generator::GeneratorKind,
             crate::routes::generate::GenerateContractReq,
             crate::routes::generate::GenerateContractRes,
             crate::routes::generate::GeneratedContractItem,
             crate::routes::generate::GeneratedContractsListRes,
+            crate::routes::generate::ContractSort,
+            crate::routes::generate::SortOrder,
+            crate::routes::generate::UpdateContractReq,
+            crate::libs::archive::ArchiveFormat,
+            crate::libs::compiler::Diagnostic,
+            crate::libs::compiler::CompileReport,
+            crate::routes::compile::Artifact,
+            crate::libs::audit::Severity,
+            crate::libs::audit::Finding,
+            crate::libs::audit::AuditReport,
+            crate::libs::abi::StorageVar,
+            crate::routes::abi::AbiRes,
+            crate::routes::abi::CalldataReq,
+            crate::routes::abi::CalldataRes,
+            crate::libs::deployments::Network,
+            crate::libs::deployments::Deployment,
+            crate::routes::deployments::RecordDeploymentReq,
+            crate::routes::deployments::DeployReq,
+            crate::routes::deployments::DeployRes,
+            crate::routes::deployments::DeploymentItem,
+            crate::routes::deployments::DeploymentsListRes,
+            crate::libs::revisions::Revision,
+            crate::routes::revisions::RevisionItem,
+            crate::routes::revisions::RevisionsListRes,
+            crate::routes::revisions::UpdateCodeReq,
+            crate::routes::revisions::RegenerateReq,
+            crate::routes::jobs::JobRes,
+            crate::routes::jobs::JobStatusEvent,
+            crate::routes::jobs::JobOutputEvent,
             // Reviews
             crate::routes::reviews::ReviewItem,
             crate::routes::reviews::ReviewsListRes
         )
     ),
-    modifiers(&SecurityAddon),
+    modifiers(&SecurityAddon, &TemplateSchemasAddon),
     tags(
         (name = "health", description = "Health check endpoints"),
         (name = "auth", description = "Authentication & registration endpoints"),
+        (name = "api_keys", description = "API keys for programmatic access"),
+        (name = "admin", description = "Moderation and user management (admin role)"),
+        (name = "templates", description = "Contract template catalog"),
         (name = "contracts", description = "Generated contracts endpoints"),
         (name = "reviews", description = "Reviews listing endpoints")
     )
@@ -0,0 +1,290 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use flate2::{Compression, write::GzEncoder};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use utoipa::ToSchema;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+/// Size of the chunks an archive is streamed in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+pub enum ArchiveFormat {
+    #[default]
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "application/zip",
+            ArchiveFormat::TarGz => "application/gzip",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProjectFile {
+    /// Path inside the project directory
+    pub path: &'static str,
+    pub contents: String,
+}
+
+/// What goes into a generated project besides its Scarb.toml
+pub struct Project<'a> {
+    pub package: &'a str,
+    pub module: &'a str,
+    pub description: &'a str,
+    pub contract_type: &'a str,
+    pub template_id: Option<&'a str>,
+    pub code: &'a str,
+    pub created_at: DateTime<Utc>,
+}
+
+// Smoke test that the contract class declares; constructors differ per
+// template, so deploying is left to the user.
+pub fn test_file(module: &str) -> String {
+    format!(
+        r#"use snforge_std::{{ContractClass, DeclareResultTrait, declare}};
+
+#[test]
+fn test_{snake}_declares() {{
+    let declared = declare("{module}").unwrap();
+    let _class: @ContractClass = declared.contract_class();
+}}
+"#,
+        snake = crate::libs::scarb::package_name(module),
+        module = module,
+    )
+}
+
+fn readme(project: &Project, cairo: &str) -> String {
+    let origin = match project.template_id {
+        Some(id) => format!("the `{}` template", id),
+        None => format!("contract type `{}`", project.contract_type),
+    };
+    format!(
+        r#"# {package}
+
+{description}
+
+Generated by StarkFinder on {date} from {origin}.
+
+## Layout
+
+- `Scarb.toml` - package manifest
+- `src/lib.cairo` - the `{module}` contract
+- `tests/test_contract.cairo` - Starknet Foundry smoke test
+
+## Build and test
+
+Requires Scarb with Cairo {cairo} and Starknet Foundry (`snforge`).
+
+```sh
+scarb build
+scarb test
+```
+"#,
+        package = project.package,
+        description = project.description,
+        date = project.created_at.format("%Y-%m-%d"),
+        origin = origin,
+        module = project.module,
+        cairo = cairo,
+    )
+}
+
+// Every file of the project, `scarb_config` being its Scarb.toml.
+pub fn project_files(project: &Project, scarb_config: String, cairo: &str) -> Vec<ProjectFile> {
+    vec![
+        ProjectFile {
+            path: "Scarb.toml",
+            contents: scarb_config,
+        },
+        ProjectFile {
+            path: "src/lib.cairo",
+            contents: project.code.to_string(),
+        },
+        ProjectFile {
+            path: "tests/test_contract.cairo",
+            contents: test_file(project.module),
+        },
+        ProjectFile {
+            path: "README.md",
+            contents: readme(project, cairo),
+        },
+    ]
+}
+
+// Pack `files` under a top-level `root/` directory, written to `out`.
+pub fn build<W: Write + Seek>(
+    format: ArchiveFormat,
+    root: &str,
+    files: &[ProjectFile],
+    modified: DateTime<Utc>,
+    out: W,
+) -> io::Result<W> {
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = ZipWriter::new(out);
+            let options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .unix_permissions(0o644);
+            for file in files {
+                zip.start_file(format!("{}/{}", root, file.path), options)?;
+                zip.write_all(file.contents.as_bytes())?;
+            }
+            Ok(zip.finish()?)
+        }
+        ArchiveFormat::TarGz => {
+            let mut tar = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+            for file in files {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(file.contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(modified.timestamp().max(0) as u64);
+                header.set_cksum();
+                tar.append_data(
+                    &mut header,
+                    format!("{}/{}", root, file.path),
+                    file.contents.as_bytes(),
+                )?;
+            }
+            tar.into_inner()?.finish()
+        }
+    }
+}
+
+// Build the archive into an already unlinked temporary file, rewound. Zip
+// seeks back to patch entry headers, so it cannot be written to the socket
+// directly.
+fn spool(
+    format: ArchiveFormat,
+    root: &str,
+    files: &[ProjectFile],
+    modified: DateTime<Utc>,
+) -> io::Result<File> {
+    let path = std::env::temp_dir().join(format!("archive-{:016x}", rand::random::<u64>()));
+    let file = File::options()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    std::fs::remove_file(&path)?;
+
+    let mut file = build(format, root, files, modified, file)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+// Stream the archive of `files` in chunks instead of holding it in memory.
+// Errors past the response head can only abort the body.
+pub fn stream(
+    format: ArchiveFormat,
+    root: String,
+    files: Vec<ProjectFile>,
+    modified: DateTime<Utc>,
+) -> ReceiverStream<io::Result<Bytes>> {
+    let (tx, rx) = mpsc::channel(4);
+    tokio::task::spawn_blocking(move || {
+        let sent = spool(format, &root, &files, modified).and_then(|mut file| {
+            let mut buf = vec![0; CHUNK_SIZE];
+            loop {
+                let n = file.read(&mut buf)?;
+                // Done, or the client went away
+                if n == 0
+                    || tx
+                        .blocking_send(Ok(Bytes::copy_from_slice(&buf[..n])))
+                        .is_err()
+                {
+                    return Ok(());
+                }
+            }
+        });
+        if let Err(e) = sent {
+            tracing::error!("Failed to build {} archive: {:?}", root, e);
+            let _ = tx.blocking_send(Err(e));
+        }
+    });
+    ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<ProjectFile> {
+        let project = Project {
+            package: "gold",
+            module: "Gold",
+            description: "Gold token",
+            contract_type: "erc20",
+            template_id: Some("erc20"),
+            code: "mod Gold {}\n",
+            created_at: Utc::now(),
+        };
+        project_files(&project, "[package]\n".into(), "2.11.4")
+    }
+
+    #[test]
+    fn zip_contains_project_files() {
+        let out = io::Cursor::new(Vec::new());
+        let out = build(ArchiveFormat::Zip, "gold", &files(), Utc::now(), out).unwrap();
+        let mut zip = zip::ZipArchive::new(out).unwrap();
+        let mut names: Vec<&str> = zip.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "gold/README.md",
+                "gold/Scarb.toml",
+                "gold/src/lib.cairo",
+                "gold/tests/test_contract.cairo"
+            ]
+        );
+
+        let mut code = String::new();
+        zip.by_name("gold/src/lib.cairo")
+            .unwrap()
+            .read_to_string(&mut code)
+            .unwrap();
+        assert_eq!(code, "mod Gold {}\n");
+    }
+
+    #[test]
+    fn tar_gz_contains_project_files() {
+        let out = io::Cursor::new(Vec::new());
+        let bytes = build(ArchiveFormat::TarGz, "gold", &files(), Utc::now(), out)
+            .unwrap()
+            .into_inner();
+        let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(&bytes[..]));
+        let mut entries = Vec::new();
+        for entry in tar.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().display().to_string();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            entries.push((path, contents));
+        }
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0], ("gold/Scarb.toml".into(), "[package]\n".into()));
+        assert!(entries[2].1.contains("declare(\"Gold\")"));
+    }
+}
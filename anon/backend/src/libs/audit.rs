@@ -0,0 +1,603 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use utoipa::ToSchema;
+
+use crate::libs::cairo::{is_ident, strip_line};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Finding {
+    /// Rule id, e.g. `missing-caller-check`
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Function the finding is in, if any
+    pub function: Option<String>,
+    /// First line of the span, 1-based
+    pub line_start: u32,
+    /// Last line of the span, inclusive
+    pub line_end: u32,
+}
+
+/// `(id, severity, what it flags)` for every rule
+pub const RULES: &[(&str, Severity, &str)] = &[
+    (
+        "unprotected-upgrade",
+        Severity::Critical,
+        "external upgrade without a caller check",
+    ),
+    (
+        "missing-caller-check",
+        Severity::High,
+        "privileged external (mint, set_*, pause, ...) without a caller check",
+    ),
+    (
+        "view-storage-write",
+        Severity::High,
+        "storage write in a function taking `self: @ContractState`",
+    ),
+    (
+        "felt-arithmetic",
+        Severity::Medium,
+        "arithmetic on felt252 values, which wraps instead of failing",
+    ),
+    (
+        "missing-event",
+        Severity::Low,
+        "external that changes storage without emitting an event",
+    ),
+    ("todo", Severity::Info, "leftover TODO/FIXME comment"),
+];
+
+fn severity_of(rule: &str) -> Severity {
+    RULES
+        .iter()
+        .find(|(id, _, _)| *id == rule)
+        .map(|(_, severity, _)| *severity)
+        .expect("known audit rule")
+}
+
+// Externals whose name suggests only an owner or admin should call them.
+// `upgrade` has its own rule.
+const PRIVILEGED_PREFIXES: &[&str] = &[
+    "mint",
+    "set_",
+    "pause",
+    "unpause",
+    "grant_",
+    "revoke_role",
+    "transfer_ownership",
+    "renounce_ownership",
+    "add_admin",
+    "remove_admin",
+    "add_owner",
+    "remove_owner",
+    "emergency",
+    "sweep",
+    "rescue",
+];
+// Standard token entry points any holder may call
+const UNPRIVILEGED: &[&str] = &["set_approval_for_all"];
+
+// Code that restricts who can call a function; an `assert` on the caller
+// counts too
+const CALLER_CHECKS: &[&str] = &[
+    "assert_only_",
+    "only_owner",
+    "only_role",
+    "only_admin",
+    "assert_owner",
+    "assert_admin",
+    "assert_caller",
+];
+
+const TODO_MARKERS: &[&str] = &["TODO", "FIXME", "XXX"];
+
+// Byte offsets of `word` in `s` where it is not part of a longer identifier.
+fn word_positions<'a>(s: &'a str, word: &'a str) -> impl Iterator<Item = usize> + 'a {
+    s.match_indices(word).map(|(at, _)| at).filter(move |&at| {
+        let before = s[..at].chars().next_back();
+        let after = s[at + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+fn has_word(s: &str, word: &str) -> bool {
+    word_positions(s, word).next().is_some()
+}
+
+// Whether `s` calls `name(...)`.
+fn calls(s: &str, name: &str) -> bool {
+    word_positions(s, name).any(|at| s[at + name.len()..].trim_start().starts_with('('))
+}
+
+// The comment part of `line`, if any.
+fn comment(line: &str) -> Option<&str> {
+    let code = strip_line(line);
+    let rest = &line[code.len()..];
+    rest.strip_prefix("//")
+}
+
+struct Function {
+    name: String,
+    /// Line of `fn`, 0-based
+    start: usize,
+    /// Line of the closing brace
+    end: usize,
+    /// Parameters and return type
+    signature: String,
+    /// `#[external(v0)]`, or in an `#[abi(embed_v0)]` impl
+    external: bool,
+    constructor: bool,
+}
+
+impl Function {
+    fn is_view(&self) -> bool {
+        self.signature.contains("@ContractState")
+    }
+
+    fn mutates(&self) -> bool {
+        self.signature.contains("ref self")
+    }
+
+    fn body<'a>(&self, code: &'a [String]) -> &'a [String] {
+        &code[self.start..=self.end]
+    }
+}
+
+// Attributes on the lines right above `line`.
+fn attributes(code: &[String], line: usize) -> String {
+    let mut attrs = String::new();
+    for l in code[..line].iter().rev() {
+        let l = l.trim();
+        if !l.starts_with("#[") {
+            break;
+        }
+        attrs.push_str(l);
+    }
+    attrs
+}
+
+// Line of the brace closing the block opened at `(line, col)`.
+fn block_end(code: &[String], line: usize, col: usize) -> usize {
+    let mut depth = 0i32;
+    for (i, l) in code.iter().enumerate().skip(line) {
+        let from = if i == line { col } else { 0 };
+        for c in l[from..].chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    code.len() - 1
+}
+
+// Opening brace of the item starting at `line`, unless the item ends with
+// `;` first (trait declarations, impl aliases).
+fn block_start(code: &[String], line: usize, col: usize) -> Option<(usize, usize)> {
+    for (i, l) in code.iter().enumerate().skip(line) {
+        let from = if i == line { col } else { 0 };
+        for (at, c) in l[from..].char_indices() {
+            match c {
+                '{' => return Some((i, from + at)),
+                ';' => return None,
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+fn functions(code: &[String]) -> Vec<Function> {
+    // (first line, last line) of impls whose functions are all external
+    let mut embedded = Vec::new();
+    let mut functions = Vec::new();
+    for (line, l) in code.iter().enumerate() {
+        let open = word_positions(l, "impl")
+            .next()
+            .filter(|_| attributes(code, line).contains("abi(embed_v0)"))
+            .and_then(|at| block_start(code, line, at));
+        if let Some((open_line, open_col)) = open {
+            embedded.push((line, block_end(code, open_line, open_col)));
+        }
+
+        let Some(at) = word_positions(l, "fn").next() else {
+            continue;
+        };
+        let name: String = l[at + 2..]
+            .trim_start()
+            .chars()
+            .take_while(|&c| is_ident(c))
+            .collect();
+        let Some((open_line, open_col)) = block_start(code, line, at) else {
+            continue;
+        };
+        let mut signature: Vec<&str> = code[line..=open_line].iter().map(String::as_str).collect();
+        let last = signature.len() - 1;
+        let tail = &code[open_line][..open_col];
+        signature[last] = tail;
+        let attrs = attributes(code, line);
+        functions.push(Function {
+            external: attrs.contains("external(v0)")
+                || embedded
+                    .iter()
+                    .any(|&(first, last)| first < line && line <= last),
+            constructor: attrs.contains("constructor"),
+            name,
+            start: line,
+            end: block_end(code, open_line, open_col),
+            signature: signature.join(" "),
+        });
+    }
+    functions
+}
+
+// Functions whose body matches `direct`, or calls one that does.
+fn closure(
+    functions: &[Function],
+    code: &[String],
+    direct: impl Fn(&str) -> bool,
+) -> HashSet<String> {
+    let mut set: HashSet<String> = functions
+        .iter()
+        .filter(|f| f.body(code).iter().any(|l| direct(l)))
+        .map(|f| f.name.clone())
+        .collect();
+    loop {
+        let more: Vec<String> = functions
+            .iter()
+            .filter(|f| !set.contains(&f.name))
+            .filter(|f| {
+                f.body(code)[1..]
+                    .iter()
+                    .any(|l| set.iter().any(|name| calls(l, name)))
+            })
+            .map(|f| f.name.clone())
+            .collect();
+        if more.is_empty() {
+            return set;
+        }
+        set.extend(more);
+    }
+}
+
+// Names declared as `name: felt252`: storage fields, parameters and lets.
+fn felt_names(code: &[String]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for l in code {
+        for (at, _) in l.match_indices("felt252") {
+            let Some(before) = l[..at].trim_end().strip_suffix(':') else {
+                continue;
+            };
+            let name: String = before
+                .trim_end()
+                .chars()
+                .rev()
+                .take_while(|&c| is_ident(c))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .collect();
+            if !name.is_empty() && !l[at + 7..].starts_with(|c: char| is_ident(c)) {
+                names.insert(name);
+            }
+        }
+    }
+    names
+}
+
+fn is_arithmetic(c: char) -> bool {
+    matches!(c, '+' | '-' | '*' | '/')
+}
+
+// Whether `name` is an operand of `+ - * /` in `l`, possibly through
+// `self.name.read()`.
+fn arithmetic_on(l: &str, name: &str) -> bool {
+    let l = l.replace("->", "  ");
+    word_positions(&l, name).any(|at| {
+        let before = l[..at].trim_end_matches("self.").trim_end();
+        let mut after = l[at + name.len()..].trim_start();
+        if let Some(rest) = after.strip_prefix(".read(") {
+            after = rest
+                .split_once(')')
+                .map_or("", |(_, rest)| rest)
+                .trim_start();
+        }
+        before.ends_with(is_arithmetic)
+            || before.ends_with("+=")
+            || before.ends_with("-=")
+            || after.starts_with(is_arithmetic)
+    })
+}
+
+/// Run every rule over Cairo `source`, most severe findings first
+pub fn analyze(source: &str) -> Vec<Finding> {
+    let code: Vec<String> = source.lines().map(strip_line).collect();
+    let functions = functions(&code);
+    let checked = closure(&functions, &code, |l| {
+        CALLER_CHECKS.iter().any(|check| l.contains(check))
+            || (has_word(l, "assert") && l.contains("caller"))
+    });
+    let emits = closure(&functions, &code, |l| l.contains(".emit("));
+    let writes = closure(&functions, &code, |l| l.contains(".write("));
+    let felts = felt_names(&code);
+
+    let mut findings = Vec::new();
+    let mut add =
+        |rule: &str, function: Option<&Function>, start: usize, end: usize, message: String| {
+            findings.push(Finding {
+                rule: rule.to_string(),
+                severity: severity_of(rule),
+                message,
+                function: function.map(|f| f.name.clone()),
+                line_start: start as u32 + 1,
+                line_end: end as u32 + 1,
+            });
+        };
+
+    for f in &functions {
+        let unchecked = f.external && f.mutates() && !f.constructor && !checked.contains(&f.name);
+        let body = f.body(&code);
+        if unchecked
+            && (f.name == "upgrade" || body.iter().any(|l| l.contains("replace_class_syscall")))
+        {
+            add(
+                "unprotected-upgrade",
+                Some(f),
+                f.start,
+                f.end,
+                format!(
+                    "`{}` replaces the contract class but anyone can call it; restrict it to the owner",
+                    f.name
+                ),
+            );
+        } else if unchecked
+            && PRIVILEGED_PREFIXES.iter().any(|p| f.name.starts_with(p))
+            && !UNPRIVILEGED.contains(&f.name.as_str())
+        {
+            add(
+                "missing-caller-check",
+                Some(f),
+                f.start,
+                f.end,
+                format!("`{}` looks privileged but never checks the caller", f.name),
+            );
+        }
+
+        if f.is_view() {
+            for (i, l) in body.iter().enumerate() {
+                if l.contains(".write(") {
+                    add(
+                        "view-storage-write",
+                        Some(f),
+                        f.start + i,
+                        f.start + i,
+                        format!(
+                            "`{}` takes `self: @ContractState` but writes storage",
+                            f.name
+                        ),
+                    );
+                }
+            }
+        }
+
+        for (i, l) in body.iter().enumerate().skip(1) {
+            if let Some(name) = felts.iter().filter(|n| arithmetic_on(l, n)).min() {
+                add(
+                    "felt-arithmetic",
+                    Some(f),
+                    f.start + i,
+                    f.start + i,
+                    format!(
+                        "arithmetic on felt252 `{}` wraps around the field prime instead of failing on overflow; use u256 or a sized integer",
+                        name
+                    ),
+                );
+            }
+        }
+
+        if f.external
+            && f.mutates()
+            && !f.constructor
+            && writes.contains(&f.name)
+            && !emits.contains(&f.name)
+        {
+            add(
+                "missing-event",
+                Some(f),
+                f.start,
+                f.end,
+                format!("`{}` changes storage without emitting an event", f.name),
+            );
+        }
+    }
+
+    for (i, line) in source.lines().enumerate() {
+        let todo = comment(line).filter(|text| TODO_MARKERS.iter().any(|m| has_word(text, m)));
+        if let Some(text) = todo {
+            add(
+                "todo",
+                functions.iter().find(|f| f.start <= i && i <= f.end),
+                i,
+                i,
+                format!("leftover comment: {}", text.trim()),
+            );
+        }
+    }
+
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(a.line_start.cmp(&b.line_start))
+    });
+    findings
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditReport {
+    pub contract_id: i64,
+    pub findings: Vec<Finding>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Replace the stored findings of contract `contract_id` with `findings`
+pub async fn record(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    findings: Vec<Finding>,
+) -> Result<AuditReport, sqlx::Error> {
+    let created_at = Utc::now();
+    sqlx::query("DELETE FROM audit_findings WHERE contract_id = $1")
+        .bind(contract_id)
+        .execute(&mut *conn)
+        .await?;
+    for finding in &findings {
+        sqlx::query(
+            r#"INSERT INTO audit_findings
+                   (contract_id, rule, severity, message, function_name, line_start, line_end, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+        )
+        .bind(contract_id)
+        .bind(&finding.rule)
+        .bind(finding.severity.as_str())
+        .bind(&finding.message)
+        .bind(&finding.function)
+        .bind(finding.line_start as i32)
+        .bind(finding.line_end as i32)
+        .bind(created_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(AuditReport {
+        contract_id,
+        findings,
+        created_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(source: &str) -> Vec<(String, u32)> {
+        analyze(source)
+            .into_iter()
+            .map(|f| (f.rule, f.line_start))
+            .collect()
+    }
+
+    #[test]
+    fn templates_are_clean() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/templates");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(analyze(&source), vec![], "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn flags_each_rule() {
+        let source = r#"#[starknet::contract]
+mod Bad {
+    #[storage]
+    struct Storage {
+        supply: felt252,
+        owner: ContractAddress,
+    }
+
+    #[abi(embed_v0)]
+    impl UpgradeableImpl of IUpgradeable<ContractState> {
+        fn upgrade(ref self: ContractState, new_class_hash: ClassHash) {
+            replace_class_syscall(new_class_hash).unwrap();
+        }
+    }
+
+    #[external(v0)]
+    fn mint(ref self: ContractState, amount: felt252) {
+        self.supply.write(self.supply.read() + amount);
+    }
+
+    #[external(v0)]
+    fn get_supply(self: @ContractState) -> felt252 {
+        // TODO: cache this
+        self.supply.write(0);
+        self.supply.read()
+    }
+}
+"#;
+        assert_eq!(
+            rules(source),
+            vec![
+                ("unprotected-upgrade".to_string(), 11),
+                ("missing-caller-check".to_string(), 17),
+                ("view-storage-write".to_string(), 24),
+                ("felt-arithmetic".to_string(), 18),
+                ("missing-event".to_string(), 17),
+                ("todo".to_string(), 23),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_in_helpers_count() {
+        let source = r#"
+    #[external(v0)]
+    fn set_fee(ref self: ContractState, fee: u256) {
+        self._assert_admin();
+        self._set_fee(fee);
+    }
+
+    fn _assert_admin(self: @ContractState) {
+        assert(get_caller_address() == self.admin.read(), 'not admin');
+    }
+
+    fn _set_fee(ref self: ContractState, fee: u256) {
+        self.fee.write(fee);
+        self.emit(FeeChanged { fee });
+    }
+"#;
+        assert_eq!(rules(source), vec![]);
+    }
+
+    #[test]
+    fn strings_and_comments_are_not_code() {
+        assert_eq!(strip_line("let s = 'a{b'; // }"), "let s = '   '; ");
+        assert_eq!(comment("x(); // TODO later"), Some(" TODO later"));
+        assert!(!arithmetic_on("fn f(x: felt252) -> felt252 {", "x"));
+        assert!(arithmetic_on("let y = 2 * x;", "x"));
+        assert!(arithmetic_on("self.x.read() - 1", "x"));
+    }
+}
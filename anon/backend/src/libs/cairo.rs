@@ -0,0 +1,55 @@
+// Helpers for scanning Cairo source without a full parser, shared by the
+// audit and ABI extraction.
+
+/// Whether `c` can be part of an identifier
+pub fn is_ident(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// `line` with comments removed and string literal contents blanked, so
+/// braces and keywords inside them are not mistaken for code.
+pub fn strip_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => {
+                // Blanks keep byte offsets the same as in `line`
+                if c == '\\' {
+                    let escaped = chars.next().map_or(0, char::len_utf8);
+                    out.push_str(&" ".repeat(1 + escaped));
+                    continue;
+                }
+                if c == q {
+                    quote = None;
+                    out.push(c);
+                } else {
+                    out.push_str(&" ".repeat(c.len_utf8()));
+                }
+            }
+            None => {
+                if c == '/' && chars.peek() == Some(&'/') {
+                    break;
+                }
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// Name of the `#[starknet::contract]` module declared in `source`
+pub fn contract_module(source: &str) -> Option<String> {
+    let code: Vec<String> = source.lines().map(strip_line).collect();
+    let code = code.join("\n");
+    let after = &code[code.find("#[starknet::contract]")? + "#[starknet::contract]".len()..];
+    let mut words = after
+        .split(|c: char| !is_ident(c))
+        .filter(|w| !w.is_empty());
+    words.find(|w| *w == "mod")?;
+    words.next().map(str::to_string)
+}
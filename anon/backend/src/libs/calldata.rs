@@ -0,0 +1,509 @@
+use serde_json::{Map, Value};
+use starknet::core::{
+    codec::Encode,
+    types::{ByteArray, Felt},
+    utils::cairo_short_string_to_felt,
+};
+
+use crate::libs::error::FieldError;
+
+// High 128 bits of the field prime 2^251 + 17 * 2^192 + 1 (its low half is 1)
+const PRIME_HIGH: u128 = (1 << 123) + (17 << 64);
+
+/// Inputs of the constructor in `abi`, as (name, type)
+pub fn constructor_inputs(abi: &[Value]) -> Vec<(String, String)> {
+    abi.iter()
+        .find(|e| e["type"] == "constructor")
+        .and_then(|e| e["inputs"].as_array())
+        .map(|inputs| {
+            inputs
+                .iter()
+                .map(|i| {
+                    let field = |key: &str| i[key].as_str().unwrap_or_default().to_string();
+                    (field("name"), field("type"))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Constructor calldata for a contract with `abi`, from its arguments by
+/// name. Values are JSON: numbers or decimal / `0x` strings for integers
+/// and addresses, strings for `ByteArray` (and short strings for felt252),
+/// arrays for `Array`, `Span` and tuples, objects for structs, and
+/// `"Variant"` or `{"Variant": value}` for enums. Every offending value is
+/// reported, under `prefix` (`args.owners[1]`).
+pub fn encode_constructor(
+    abi: &[Value],
+    args: &Map<String, Value>,
+    prefix: &str,
+) -> Result<Vec<Felt>, Vec<FieldError>> {
+    let inputs = constructor_inputs(abi);
+    let mut errors: Vec<FieldError> = args
+        .keys()
+        .filter(|k| !inputs.iter().any(|(name, _)| name == *k))
+        .map(|k| FieldError {
+            field: format!("{}.{}", prefix, k),
+            message: "is not a constructor argument".into(),
+        })
+        .collect();
+
+    let encoder = Encoder { abi };
+    let mut calldata = Vec::new();
+    for (name, ty) in &inputs {
+        let value = args.get(name).unwrap_or(&Value::Null);
+        let path = format!("{}.{}", prefix, name);
+        if let Err(e) = encoder.encode(ty, value, &path, &mut calldata) {
+            errors.push(e);
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(calldata)
+}
+
+struct Encoder<'a> {
+    abi: &'a [Value],
+}
+
+impl Encoder<'_> {
+    fn encode(
+        &self,
+        ty: &str,
+        value: &Value,
+        path: &str,
+        out: &mut Vec<Felt>,
+    ) -> Result<(), FieldError> {
+        let error = |message: String| FieldError {
+            field: path.to_string(),
+            message,
+        };
+        if let Some(inner) = generic(ty, "core::option::Option") {
+            return match value {
+                Value::Null => {
+                    out.push(Felt::ONE);
+                    Ok(())
+                }
+                value => {
+                    out.push(Felt::ZERO);
+                    self.encode(inner[0], value, path, out)
+                }
+            };
+        }
+        if value.is_null() && ty != "()" {
+            return Err(error("is required".into()));
+        }
+        if let Some(felts) = scalar(ty, value) {
+            out.extend(felts.map_err(error)?);
+            return Ok(());
+        }
+
+        let elements =
+            generic(ty, "core::array::Array").or_else(|| generic(ty, "core::array::Span"));
+        if let Some(element) = elements.map(|e| e[0]) {
+            let items = value
+                .as_array()
+                .ok_or_else(|| error("must be an array".into()))?;
+            out.push(items.len().into());
+            for (i, item) in items.iter().enumerate() {
+                self.encode(element, item, &format!("{}[{}]", path, i), out)?;
+            }
+            return Ok(());
+        }
+        if let Some(types) = ty.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            let types = split_top(types);
+            let items = value
+                .as_array()
+                .filter(|items| items.len() == types.len())
+                .ok_or_else(|| error(format!("must be an array of {} values", types.len())))?;
+            for (i, (ty, item)) in types.iter().zip(items).enumerate() {
+                self.encode(ty, item, &format!("{}[{}]", path, i), out)?;
+            }
+            return Ok(());
+        }
+
+        let def = self
+            .abi
+            .iter()
+            .find(|e| e["name"] == ty && (e["type"] == "struct" || e["type"] == "enum"))
+            .ok_or_else(|| error(format!("has unsupported type `{}`", ty)))?;
+        let members = |key: &str| -> Vec<(&str, &str)> {
+            def[key]
+                .as_array()
+                .map(|ms| {
+                    ms.iter()
+                        .map(|m| {
+                            (
+                                m["name"].as_str().unwrap_or_default(),
+                                m["type"].as_str().unwrap_or_default(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        if def["type"] == "struct" {
+            let members = members("members");
+            let fields = value
+                .as_object()
+                .ok_or_else(|| error("must be an object".into()))?;
+            if let Some(extra) = fields.keys().find(|k| !members.iter().any(|(n, _)| n == k)) {
+                return Err(error(format!("has no field `{}`", extra)));
+            }
+            for (name, ty) in members {
+                let value = fields.get(name).unwrap_or(&Value::Null);
+                self.encode(ty, value, &format!("{}.{}", path, name), out)?;
+            }
+            return Ok(());
+        }
+
+        let variants = members("variants");
+        let (variant, payload) = match value {
+            Value::String(name) => (name.as_str(), &Value::Null),
+            Value::Object(map) if map.len() == 1 => {
+                let (name, payload) = map.iter().next().expect("one entry");
+                (name.as_str(), payload)
+            }
+            _ => {
+                return Err(error(
+                    "must be a variant name or an object with a single variant".into(),
+                ));
+            }
+        };
+        let (index, (_, ty)) = variants
+            .iter()
+            .enumerate()
+            .find(|(_, (name, _))| *name == variant)
+            .ok_or_else(|| error(format!("has no variant `{}`", variant)))?;
+        out.push(index.into());
+        self.encode(ty, payload, &format!("{}.{}", path, variant), out)
+    }
+}
+
+// Felts of a corelib value, or `None` if `ty` is not a scalar type.
+fn scalar(ty: &str, value: &Value) -> Option<Result<Vec<Felt>, String>> {
+    let felts = match ty {
+        "()" => Ok(Vec::new()),
+        "core::felt252" => felt252(value).map(|f| vec![f]),
+        "core::bool" => value
+            .as_bool()
+            .map(|b| vec![if b { Felt::ONE } else { Felt::ZERO }])
+            .ok_or_else(|| "must be a boolean".into()),
+        "core::integer::u256" => uint(value).map(|(low, high)| vec![low.into(), high.into()]),
+        "core::starknet::contract_address::ContractAddress" => {
+            bounded(value, 251, "a contract address").map(|f| vec![f])
+        }
+        "core::starknet::class_hash::ClassHash" => {
+            bounded(value, 251, "a class hash").map(|f| vec![f])
+        }
+        "core::starknet::eth_address::EthAddress" => {
+            bounded(value, 160, "an Ethereum address").map(|f| vec![f])
+        }
+        "core::byte_array::ByteArray" => value
+            .as_str()
+            .map(|s| {
+                let mut felts = Vec::new();
+                ByteArray::from(s)
+                    .encode(&mut felts)
+                    .expect("ByteArray encodes");
+                felts
+            })
+            .ok_or_else(|| "must be a string".into()),
+        "core::bytes_31::bytes31" => value
+            .as_str()
+            .ok_or_else(|| "must be a string".to_string())
+            .and_then(|s| {
+                cairo_short_string_to_felt(s)
+                    .map_err(|_| "must be at most 31 ASCII characters".into())
+            })
+            .map(|f| vec![f]),
+        _ => {
+            let int = ty.strip_prefix("core::integer::")?;
+            let bits: u32 = int.get(1..)?.parse().ok()?;
+            match int.as_bytes()[0] {
+                b'u' => bounded(value, bits, &format!("a u{}", bits)).map(|f| vec![f]),
+                b'i' => signed(value, bits).map(|f| vec![f]),
+                _ => return None,
+            }
+        }
+    };
+    Some(felts)
+}
+
+// Digits of a JSON number or string, without a leading `-`, and whether
+// it had one.
+fn digits(value: &Value) -> Option<(String, bool)> {
+    let s = match value {
+        Value::Number(n) if n.is_u64() || n.is_i64() => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        _ => return None,
+    };
+    match s.strip_prefix('-') {
+        Some(rest) => Some((rest.to_string(), true)),
+        None => Some((s, false)),
+    }
+}
+
+// Decimal or `0x` hex digits as the (low, high) halves of a u256.
+fn parse_u256(s: &str) -> Option<(u128, u128)> {
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let (mut low, mut high) = (0u128, 0u128);
+    for c in digits.chars() {
+        let d = u128::from(c.to_digit(radix)?);
+        let r = u128::from(radix);
+        // (high, low) * radix + d, in 64-bit halves of `low`
+        let bottom = (low & u128::from(u64::MAX)) * r + d;
+        let middle = (low >> 64) * r + (bottom >> 64);
+        low = (middle << 64) | (bottom & u128::from(u64::MAX));
+        high = high.checked_mul(r)?.checked_add(middle >> 64)?;
+    }
+    Some((low, high))
+}
+
+fn uint(value: &Value) -> Result<(u128, u128), String> {
+    const MESSAGE: &str = "must be an unsigned integer (number, decimal or 0x string)";
+    match digits(value) {
+        Some((digits, false)) => parse_u256(&digits).ok_or_else(|| MESSAGE.into()),
+        _ => Err(MESSAGE.into()),
+    }
+}
+
+fn to_felt((low, high): (u128, u128)) -> Felt {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&high.to_be_bytes());
+    bytes[16..].copy_from_slice(&low.to_be_bytes());
+    Felt::from_bytes_be(&bytes)
+}
+
+// An unsigned value below 2^bits, for bits <= 251.
+fn bounded(value: &Value, bits: u32, what: &str) -> Result<Felt, String> {
+    let (low, high) = uint(value).map_err(|_| format!("must be {}", what))?;
+    let fits = if bits > 128 {
+        high >> (bits - 128) == 0
+    } else {
+        high == 0 && (bits == 128 || low >> bits == 0)
+    };
+    if !fits {
+        return Err(format!("must be {} (below 2^{})", what, bits));
+    }
+    Ok(to_felt((low, high)))
+}
+
+fn felt252(value: &Value) -> Result<Felt, String> {
+    // Text that is not a number is a short string, like Cairo's 'abc'
+    if let Some(s) = value.as_str() {
+        if !s.trim().starts_with(|c: char| c.is_ascii_digit()) {
+            return cairo_short_string_to_felt(s).map_err(|_| {
+                "must be a number or a short string of at most 31 ASCII characters".into()
+            });
+        }
+    }
+    let (low, high) = uint(value)?;
+    if high > PRIME_HIGH || (high == PRIME_HIGH && low > 0) {
+        return Err("must be below the field prime".into());
+    }
+    Ok(to_felt((low, high)))
+}
+
+fn signed(value: &Value, bits: u32) -> Result<Felt, String> {
+    let message = || format!("must be an i{}", bits);
+    let (digits, negative) = digits(value).ok_or_else(message)?;
+    let (magnitude, high) = parse_u256(&digits).ok_or_else(message)?;
+    // |i| <= 2^(bits - 1), one less when positive
+    let limit = 1u128 << (bits - 1);
+    if high != 0 || magnitude > limit || (!negative && magnitude == limit) {
+        return Err(format!("must be an i{} (out of range)", bits));
+    }
+    let magnitude = Felt::from(magnitude);
+    Ok(if negative {
+        Felt::ZERO - magnitude
+    } else {
+        magnitude
+    })
+}
+
+// Arguments of `ty` if it is `base::<...>`
+fn generic<'a>(ty: &'a str, base: &str) -> Option<Vec<&'a str>> {
+    let args = ty
+        .strip_prefix(base)?
+        .strip_prefix("::<")?
+        .strip_suffix('>')?;
+    Some(split_top(args))
+}
+
+// Comma-separated types, leaving commas inside `<>` and `()` alone.
+fn split_top(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = list[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn abi() -> Vec<Value> {
+        serde_json::from_value(json!([
+            {
+                "type": "struct",
+                "name": "pkg::Fee",
+                "members": [
+                    {"name": "recipient", "type": "core::starknet::contract_address::ContractAddress"},
+                    {"name": "bps", "type": "core::integer::u16"}
+                ]
+            },
+            {
+                "type": "enum",
+                "name": "pkg::Mode",
+                "variants": [
+                    {"name": "Open", "type": "()"},
+                    {"name": "Capped", "type": "core::integer::u256"}
+                ]
+            },
+            {
+                "type": "constructor",
+                "name": "constructor",
+                "inputs": [
+                    {"name": "name", "type": "core::byte_array::ByteArray"},
+                    {"name": "initial_supply", "type": "core::integer::u256"},
+                    {"name": "owners", "type": "core::array::Span::<core::starknet::contract_address::ContractAddress>"},
+                    {"name": "paused", "type": "core::bool"},
+                    {"name": "fee", "type": "pkg::Fee"},
+                    {"name": "mode", "type": "pkg::Mode"},
+                    {"name": "symbol", "type": "core::felt252"},
+                    {"name": "offset", "type": "core::integer::i32"},
+                    {"name": "cap", "type": "core::option::Option::<core::integer::u64>"}
+                ]
+            }
+        ]))
+        .unwrap()
+    }
+
+    fn hex(felts: &[Felt]) -> Vec<String> {
+        felts.iter().map(|f| format!("{:#x}", f)).collect()
+    }
+
+    #[test]
+    fn encodes_constructor() {
+        let args = json!({
+            "name": "TestToken",
+            "initial_supply": "1000000000000000000",
+            "owners": ["0x1", "0x2"],
+            "paused": false,
+            "fee": {"recipient": "0xabc", "bps": 30},
+            "mode": {"Capped": "0x100000000000000000000000000000001"},
+            "symbol": "TT",
+            "offset": -1,
+            "cap": null
+        });
+        let calldata = encode_constructor(&abi(), args.as_object().unwrap(), "args").unwrap();
+        assert_eq!(
+            hex(&calldata),
+            vec![
+                // "TestToken": no full word, pending word, its length
+                "0x0",
+                "0x54657374546f6b656e",
+                "0x9",
+                // 10^18 as (low, high)
+                "0xde0b6b3a7640000",
+                "0x0",
+                "0x2",
+                "0x1",
+                "0x2",
+                "0x0",
+                "0xabc",
+                "0x1e",
+                // Capped, then 2^128 + 1 as (low, high)
+                "0x1",
+                "0x1",
+                "0x1",
+                "0x5454",
+                "0x800000000000011000000000000000000000000000000000000000000000000",
+                "0x1",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_each_field() {
+        let args = json!({
+            "name": 5,
+            "initial_supply": "-1",
+            "owners": ["0x1", "zz"],
+            "fee": {"recipient": "0x1", "bps": 70000},
+            "mode": "Closed",
+            "symbol": "this short string is far too long to fit",
+            "offset": "2147483648",
+            "extra": true
+        });
+        let errors = encode_constructor(&abi(), args.as_object().unwrap(), "args").unwrap_err();
+        let fields: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|e| (e.field.as_str(), e.message.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("args.extra", "is not a constructor argument"),
+                ("args.name", "must be a string"),
+                (
+                    "args.initial_supply",
+                    "must be an unsigned integer (number, decimal or 0x string)"
+                ),
+                ("args.owners[1]", "must be a contract address"),
+                ("args.paused", "is required"),
+                ("args.fee.bps", "must be a u16 (below 2^16)"),
+                ("args.mode", "has no variant `Closed`"),
+                (
+                    "args.symbol",
+                    "must be a number or a short string of at most 31 ASCII characters"
+                ),
+                ("args.offset", "must be an i32 (out of range)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_u256() {
+        assert_eq!(parse_u256("0"), Some((0, 0)));
+        assert_eq!(
+            parse_u256("340282366920938463463374607431768211456"),
+            Some((0, 1))
+        );
+        assert_eq!(parse_u256("0xff"), Some((255, 0)));
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(parse_u256(max), Some((u128::MAX, u128::MAX)));
+        assert_eq!(
+            parse_u256(
+                "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+            ),
+            None
+        );
+        assert_eq!(parse_u256("12a"), None);
+        assert_eq!(parse_u256(""), None);
+    }
+}
@@ -0,0 +1,479 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgConnection;
+use tokio::sync::Semaphore;
+use utoipa::ToSchema;
+
+use crate::libs::error::ApiError;
+
+// Only these variables reach the compiler; secrets such as DATABASE_URL or
+// LLM_API_KEY stay out of the sandbox.
+const PASSED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "SCARB_CACHE",
+    "SCARB_CONFIG",
+    "XDG_CACHE_HOME",
+    "XDG_CONFIG_HOME",
+];
+
+/// Keep the end of the compiler output, where the errors are
+const MAX_LOG_BYTES: usize = 16 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Diagnostic {
+    /// `error` or `warning`
+    pub severity: String,
+    /// Path inside the project, e.g. `src/lib.cairo`
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct CompileOutput {
+    pub success: bool,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Sierra contract class (`*.contract_class.json`)
+    pub sierra: Option<Value>,
+    /// CASM (`*.compiled_contract_class.json`)
+    pub casm: Option<Value>,
+    pub log: String,
+    pub duration: Duration,
+}
+
+// Runs a local `scarb build` on generated projects. Each build gets a fresh
+// directory, a cleared environment, rlimits on CPU time and address space,
+// and is killed after `timeout`. At most `slots` permits' worth of builds run
+// at once; the others wait up to `timeout` for a turn.
+pub struct Compiler {
+    pub scarb_path: PathBuf,
+    pub timeout: Duration,
+    pub cpu_limit_secs: u64,
+    pub memory_limit_mb: u64,
+    /// Pass `--offline`: dependencies must already be in Scarb's cache
+    pub offline: bool,
+    /// Where build directories are created
+    pub work_dir: PathBuf,
+    /// One permit per build allowed to run at the same time
+    pub slots: Semaphore,
+}
+
+impl Compiler {
+    pub fn new(scarb_path: impl Into<PathBuf>) -> Self {
+        Self {
+            scarb_path: scarb_path.into(),
+            timeout: Duration::from_secs(120),
+            cpu_limit_secs: 120,
+            memory_limit_mb: 4096,
+            offline: true,
+            work_dir: std::env::temp_dir(),
+            slots: Semaphore::new(2),
+        }
+    }
+
+    /// Build a package made of `scarb_config` and `code` as `src/lib.cairo`
+    pub async fn compile(&self, scarb_config: &str, code: &str) -> Result<CompileOutput, ApiError> {
+        let _slot = tokio::time::timeout(self.timeout, self.slots.acquire())
+            .await
+            .map_err(|_| ApiError::ServiceUnavailable("compiler busy"))?
+            .expect("compiler slots are never closed");
+
+        let dir = BuildDir::create(&self.work_dir).map_err(|e| {
+            tracing::error!("Failed to create build directory: {:?}", e);
+            ApiError::Internal("failed to prepare build")
+        })?;
+        let write = async {
+            tokio::fs::create_dir_all(dir.path().join("src")).await?;
+            tokio::fs::write(dir.path().join("Scarb.toml"), scarb_config).await?;
+            tokio::fs::write(dir.path().join("src/lib.cairo"), code).await
+        };
+        write.await.map_err(|e| {
+            tracing::error!("Failed to write build directory: {:?}", e);
+            ApiError::Internal("failed to prepare build")
+        })?;
+
+        // The shell applies the limits, then becomes scarb
+        let script = format!(
+            "ulimit -t {} && ulimit -v {} && exec \"$0\" \"$@\"",
+            self.cpu_limit_secs,
+            self.memory_limit_mb * 1024
+        );
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(script).arg(&self.scarb_path);
+        if self.offline {
+            cmd.arg("--offline");
+        }
+        cmd.arg("build")
+            .current_dir(dir.path())
+            .env_clear()
+            .envs(
+                PASSED_ENV
+                    .iter()
+                    .filter_map(|k| Some((k, std::env::var_os(k)?))),
+            )
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let started = Instant::now();
+        let child = cmd.spawn().map_err(|e| {
+            tracing::error!("Failed to start scarb: {:?}", e);
+            ApiError::ServiceUnavailable("compiler unavailable")
+        })?;
+        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+            Ok(output) => output.map_err(|e| {
+                tracing::error!("scarb build failed to run: {:?}", e);
+                ApiError::ServiceUnavailable("compiler unavailable")
+            })?,
+            // Dropping the child kills it
+            Err(_) => {
+                return Ok(CompileOutput {
+                    success: false,
+                    diagnostics: vec![Diagnostic {
+                        severity: "error".to_string(),
+                        file: None,
+                        line: None,
+                        column: None,
+                        message: format!("compilation timed out after {}s", self.timeout.as_secs()),
+                    }],
+                    sierra: None,
+                    casm: None,
+                    log: String::new(),
+                    duration: started.elapsed(),
+                });
+            }
+        };
+        let duration = started.elapsed();
+
+        let mut log = String::from_utf8_lossy(&output.stdout).into_owned();
+        log.push_str(&String::from_utf8_lossy(&output.stderr));
+        let log = log.replace(&format!("{}/", dir.path().display()), "");
+        let diagnostics = parse_diagnostics(&log);
+
+        let (sierra, casm) = if output.status.success() {
+            read_artifacts(&dir.path().join("target/dev")).await
+        } else {
+            (None, None)
+        };
+
+        Ok(CompileOutput {
+            success: output.status.success() && sierra.is_some(),
+            diagnostics,
+            sierra,
+            casm,
+            log: tail(&log, MAX_LOG_BYTES).to_string(),
+            duration,
+        })
+    }
+}
+
+// SCARB_PATH enables compilation; COMPILE_TIMEOUT_SECS,
+// COMPILE_CPU_LIMIT_SECS, COMPILE_MEMORY_LIMIT_MB, COMPILE_OFFLINE and
+// COMPILE_WORK_DIR tune it.
+pub fn compiler_from_env() -> Option<Arc<Compiler>> {
+    fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+        std::env::var(name).ok().and_then(|v| v.parse().ok())
+    }
+    let path = std::env::var("SCARB_PATH").ok().filter(|v| !v.is_empty())?;
+    let defaults = Compiler::new(path);
+    Some(Arc::new(Compiler {
+        timeout: var("COMPILE_TIMEOUT_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.timeout),
+        cpu_limit_secs: var("COMPILE_CPU_LIMIT_SECS").unwrap_or(defaults.cpu_limit_secs),
+        memory_limit_mb: var("COMPILE_MEMORY_LIMIT_MB").unwrap_or(defaults.memory_limit_mb),
+        offline: var("COMPILE_OFFLINE").unwrap_or(defaults.offline),
+        work_dir: var("COMPILE_WORK_DIR").unwrap_or_else(|| defaults.work_dir.clone()),
+        slots: Semaphore::new(var("COMPILE_MAX_CONCURRENT").unwrap_or(2)),
+        ..defaults
+    }))
+}
+
+// Removed, with everything in it, when dropped.
+struct BuildDir(PathBuf);
+
+impl BuildDir {
+    fn create(parent: &Path) -> std::io::Result<Self> {
+        let path = parent.join(format!("starkfinder-build-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for BuildDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// Sierra and CASM classes Scarb wrote for the package's contract.
+async fn read_artifacts(target: &Path) -> (Option<Value>, Option<Value>) {
+    let (mut sierra, mut casm) = (None, None);
+    let Ok(mut entries) = tokio::fs::read_dir(target).await else {
+        return (None, None);
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let slot = if name.ends_with(".compiled_contract_class.json") {
+            &mut casm
+        } else if name.ends_with(".contract_class.json") {
+            &mut sierra
+        } else {
+            continue;
+        };
+        if slot.is_none() {
+            *slot = tokio::fs::read(entry.path())
+                .await
+                .ok()
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+        }
+    }
+    (sierra, casm)
+}
+
+// Compiler messages in Scarb's text output:
+//
+//     error[E0006]: Identifier not found.
+//      --> src/lib.cairo:12:9
+//
+// The closing "could not compile" summary is dropped.
+pub fn parse_diagnostics(log: &str) -> Vec<Diagnostic> {
+    let mut out: Vec<Diagnostic> = Vec::new();
+    for line in log.lines() {
+        if let Some(location) = line.trim_start().strip_prefix("--> ") {
+            if let Some(last) = out.last_mut().filter(|d| d.file.is_none()) {
+                let mut parts = location.trim().rsplitn(3, ':');
+                let column = parts.next().and_then(|c| c.parse().ok());
+                let line = parts.next().and_then(|l| l.parse().ok());
+                if let (Some(file), Some(_), Some(_)) = (parts.next(), line, column) {
+                    last.file = Some(file.to_string());
+                    last.line = line;
+                    last.column = column;
+                }
+            }
+            continue;
+        }
+
+        let Some((head, message)) = line.split_once(": ") else {
+            continue;
+        };
+        let severity = match head.split('[').next() {
+            Some("error") => "error",
+            Some("warning") => "warning",
+            _ => continue,
+        };
+        if message.starts_with("could not compile") {
+            continue;
+        }
+        out.push(Diagnostic {
+            severity: severity.to_string(),
+            file: None,
+            line: None,
+            column: None,
+            message: message.trim().to_string(),
+        });
+    }
+    out
+}
+
+fn tail(s: &str, max: usize) -> &str {
+    let mut start = s.len().saturating_sub(max);
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    &s[start..]
+}
+
+/// Outcome of compiling a generated contract
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CompileReport {
+    pub id: i64,
+    pub contract_id: i64,
+    /// `compiled` or `compile_failed`
+    pub status: String,
+    /// Revision of the code that was built
+    pub revision: Option<i32>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Tail of the compiler output
+    pub log: String,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+// Store a build of revision `revision` of contract `contract_id`. While that
+// is still the latest revision the contract moves to `compiled` or
+// `compile_failed`, unless it has moved on to `deployed` or `archived`.
+pub async fn record(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    revision: Option<i32>,
+    output: &CompileOutput,
+) -> Result<CompileReport, sqlx::Error> {
+    // The row lock keeps a new revision from landing between the check and
+    // the status update
+    let latest: Option<i32> = sqlx::query_scalar(
+        r#"SELECT (SELECT MAX(r.revision) FROM contract_revisions r WHERE r.contract_id = g.id)
+           FROM generated_contracts g
+           WHERE g.id = $1
+           FOR UPDATE"#,
+    )
+    .bind(contract_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let diagnostics = serde_json::to_value(&output.diagnostics).expect("diagnostics serialize");
+    let duration_ms = output.duration.as_millis() as i64;
+    let (id, created_at): (i64, DateTime<Utc>) = sqlx::query_as(
+        r#"INSERT INTO contract_compilations
+               (contract_id, revision, success, diagnostics, sierra, casm, log, duration_ms)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           RETURNING id, created_at"#,
+    )
+    .bind(contract_id)
+    .bind(revision)
+    .bind(output.success)
+    .bind(&diagnostics)
+    .bind(&output.sierra)
+    .bind(&output.casm)
+    .bind(&output.log)
+    .bind(duration_ms)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let status = if output.success {
+        "compiled"
+    } else {
+        "compile_failed"
+    };
+    if revision == latest {
+        sqlx::query(
+            r#"UPDATE generated_contracts SET status = $2, updated_at = NOW()
+               WHERE id = $1 AND status IN ('generated', 'compiled', 'compile_failed')"#,
+        )
+        .bind(contract_id)
+        .bind(status)
+        .execute(&mut *conn)
+        .await?;
+    } else {
+        tracing::info!(
+            "Build of contract {} revision {:?} is stale, revision {:?} is current",
+            contract_id,
+            revision,
+            latest
+        );
+    }
+
+    Ok(CompileReport {
+        id,
+        contract_id,
+        status: status.to_string(),
+        revision,
+        diagnostics: output.diagnostics.clone(),
+        log: output.log.clone(),
+        duration_ms,
+        created_at,
+    })
+}
+
+// id, revision, success, diagnostics, log, duration_ms, created_at
+type CompilationRow = (i64, Option<i32>, bool, Value, String, i64, DateTime<Utc>);
+
+// Latest build of contract `contract_id`.
+pub async fn latest(
+    conn: &mut PgConnection,
+    contract_id: i64,
+) -> Result<Option<CompileReport>, sqlx::Error> {
+    let row: Option<CompilationRow> = sqlx::query_as(
+        r#"SELECT id, revision, success, diagnostics, log, duration_ms, created_at
+           FROM contract_compilations
+           WHERE contract_id = $1
+           ORDER BY id DESC
+           LIMIT 1"#,
+    )
+    .bind(contract_id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row.map(
+        |(id, revision, success, diagnostics, log, duration_ms, created_at)| CompileReport {
+            id,
+            contract_id,
+            revision,
+            status: if success {
+                "compiled"
+            } else {
+                "compile_failed"
+            }
+            .to_string(),
+            diagnostics: serde_json::from_value(diagnostics).unwrap_or_default(),
+            log,
+            duration_ms,
+            created_at,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_located_diagnostics() {
+        let log = "   Compiling gold v0.1.0 (Scarb.toml)\n\
+error[E0006]: Identifier not found.\n --> src/lib.cairo:12:9\n        foo();\n        ^^^\n\n\
+warning: Unused variable. Consider ignoring by prefixing with `_`.\n --> src/lib.cairo:5:13\n\
+error: could not compile `gold` due to previous error\n";
+        assert_eq!(
+            parse_diagnostics(log),
+            [
+                Diagnostic {
+                    severity: "error".into(),
+                    file: Some("src/lib.cairo".into()),
+                    line: Some(12),
+                    column: Some(9),
+                    message: "Identifier not found.".into(),
+                },
+                Diagnostic {
+                    severity: "warning".into(),
+                    file: Some("src/lib.cairo".into()),
+                    line: Some(5),
+                    column: Some(13),
+                    message: "Unused variable. Consider ignoring by prefixing with `_`.".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_unlocated_errors() {
+        let log = "error: cannot find package `openzeppelin 1.0.0`\n";
+        let diagnostics = parse_diagnostics(log);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, None);
+        assert_eq!(
+            diagnostics[0].message,
+            "cannot find package `openzeppelin 1.0.0`"
+        );
+    }
+
+    #[test]
+    fn tail_respects_char_boundaries() {
+        assert_eq!(tail("héllo", 4), "llo");
+        assert_eq!(tail("abc", 10), "abc");
+    }
+}
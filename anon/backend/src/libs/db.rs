@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use sqlx::{
     PgPool,
@@ -7,6 +7,16 @@ use sqlx::{
 use thiserror::Error;
 use tokio::time::sleep;
 
+use crate::libs::{
+    compiler::{self, Compiler},
+    deployer::{self, Deployer, DeployerConfigError},
+    generator::{self, Generators},
+    idempotency,
+    jobs::{self, JobQueue},
+    jwt::{KeyRing, KeyRingError},
+    verifier::{self, SignatureVerifier, VerifierConfigError},
+};
+
 #[derive(Debug, Error)]
 pub enum DbInitError {
     #[error("DATABASE_URL not set")]
@@ -15,9 +25,44 @@ pub enum DbInitError {
     Sqlx(#[from] sqlx::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum StateInitError {
+    #[error("invalid JWT key configuration: {0}")]
+    Keys(#[from] KeyRingError),
+    #[error("invalid signature verifier configuration: {0}")]
+    Verifier(#[from] VerifierConfigError),
+    #[error("invalid deployer configuration: {0}")]
+    Deployer(#[from] DeployerConfigError),
+}
+
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
+    pub verifier: Arc<dyn SignatureVerifier>,
+    pub keys: Arc<KeyRing>,
+    pub generators: Arc<Generators>,
+    pub jobs: Arc<JobQueue>,
+    /// `None` when SCARB_PATH is not set
+    pub compiler: Option<Arc<Compiler>>,
+    /// `None` when DEPLOYER_RPC_URL is not set
+    pub deployer: Option<Arc<Deployer>>,
+    /// How long `Idempotency-Key`s are remembered
+    pub idempotency_ttl: Duration,
+}
+
+impl AppState {
+    pub fn new(pool: PgPool) -> Result<Self, StateInitError> {
+        Ok(Self {
+            pool,
+            verifier: verifier::verifier_from_env()?,
+            keys: Arc::new(KeyRing::from_env()?),
+            generators: Arc::new(generator::generators_from_env()),
+            jobs: Arc::new(jobs::queue_from_env()),
+            compiler: compiler::compiler_from_env(),
+            deployer: deployer::deployer_from_env()?,
+            idempotency_ttl: idempotency::ttl_from_env(),
+        })
+    }
 }
 
 pub async fn new_pool_from_env() -> Result<PgPool, DbInitError> {
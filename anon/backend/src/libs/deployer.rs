@@ -0,0 +1,609 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+use starknet::{
+    accounts::{Account, AccountError, ConnectedAccount, ExecutionEncoding, SingleOwnerAccount},
+    contract::ContractFactory,
+    core::chain_id,
+    core::types::{
+        BlockId, BlockTag, ExecutionResult, FeeEstimate, Felt, FlattenedSierraClass, StarknetError,
+        contract::{CompiledClass, SierraClass},
+    },
+    providers::{JsonRpcClient, Provider, ProviderError, Url, jsonrpc::HttpTransport},
+    signers::{LocalWallet, SigningKey},
+};
+use thiserror::Error;
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::libs::{deployments::Network, error::ApiError};
+
+type DeployerAccount = SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>;
+
+/// Headroom on estimated gas amounts and prices, as starknet-rs applies by
+/// default
+const FEE_MARGIN: f64 = 1.5;
+
+/// Longest `receipt_timeout`: with `lock_timeout` a deploy request still
+/// ends well before the `Idempotency-Key` held for it is taken as abandoned
+/// (`idempotency::STALE_AFTER`), so a retry cannot send it again
+const MAX_RECEIPT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Classes of a build, ready to declare
+pub struct Classes {
+    pub class_hash: Felt,
+    pub compiled_class_hash: Felt,
+    sierra: FlattenedSierraClass,
+}
+
+impl Classes {
+    /// From the Sierra and CASM classes Scarb wrote
+    pub fn from_artifacts(sierra: &Value, casm: &Value) -> Result<Self, ApiError> {
+        const INVALID: ApiError = ApiError::Unprocessable("build has no valid contract class");
+        let sierra = serde_json::from_value::<SierraClass>(sierra.clone())
+            .ok()
+            .and_then(|class| class.flatten().ok())
+            .ok_or(INVALID)?;
+        let compiled_class_hash = serde_json::from_value::<CompiledClass>(casm.clone())
+            .ok()
+            .and_then(|class| class.class_hash().ok())
+            .ok_or(INVALID)?;
+        Ok(Self {
+            class_hash: sierra.class_hash(),
+            compiled_class_hash,
+            sierra,
+        })
+    }
+}
+
+/// A UDC deployment to send
+pub struct DeployRequest {
+    pub constructor_calldata: Vec<Felt>,
+    pub salt: Felt,
+    /// Mix the deployer account into the address, so others cannot take it
+    pub unique: bool,
+}
+
+/// What became of a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    /// No receipt yet
+    Pending,
+    Succeeded,
+    Reverted,
+}
+
+#[derive(Debug)]
+pub struct DeployOutcome {
+    pub contract_address: Felt,
+    pub transaction_hash: Felt,
+    pub deploy_fee: FeeEstimate,
+    /// Set when the class was declared for this deployment
+    pub declare_transaction_hash: Option<Felt>,
+    pub declare_fee: Option<FeeEstimate>,
+}
+
+// Declares and deploys classes from one configured account. Every
+// transaction's fee is estimated before it is sent, and each is followed
+// until its receipt shows up or `receipt_timeout` after the deployment
+// started. Deployments run one at a time so the account's nonces do not
+// collide; one waits at most `lock_timeout` for its turn.
+pub struct Deployer {
+    pub network: Network,
+    pub rpc_url: Url,
+    pub account_address: Felt,
+    pub signing_key: SigningKey,
+    /// Universal Deployer Contract; starknet-rs's default when `None`
+    pub udc_address: Option<Felt>,
+    /// Largest fee, in fri, a transaction's resource bounds may allow
+    pub max_fee: u128,
+    pub poll_interval: Duration,
+    pub receipt_timeout: Duration,
+    pub lock_timeout: Duration,
+    lock: Mutex<()>,
+}
+
+/// A deployment whose transaction was sent; holds the deployer's turn until
+/// `Deployer::confirm` is done with it
+pub struct Sent<'a> {
+    pub outcome: DeployOutcome,
+    deadline: Instant,
+    _turn: MutexGuard<'a, ()>,
+}
+
+impl Deployer {
+    pub fn new(
+        network: Network,
+        rpc_url: Url,
+        account_address: Felt,
+        signing_key: SigningKey,
+        max_fee: u128,
+    ) -> Self {
+        Self {
+            network,
+            rpc_url,
+            account_address,
+            signing_key,
+            udc_address: None,
+            max_fee,
+            poll_interval: Duration::from_secs(2),
+            receipt_timeout: Duration::from_secs(300),
+            lock_timeout: Duration::from_secs(30),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn provider(&self) -> JsonRpcClient<HttpTransport> {
+        JsonRpcClient::new(HttpTransport::new(self.rpc_url.clone()))
+    }
+
+    /// Declare `classes` unless the network already knows them, then send
+    /// the deployment of an instance through the UDC. Errors mean nothing
+    /// was deployed.
+    pub async fn send(
+        &self,
+        classes: Classes,
+        request: DeployRequest,
+    ) -> Result<Sent<'_>, ApiError> {
+        let turn = tokio::time::timeout(self.lock_timeout, self.lock.lock())
+            .await
+            .map_err(|_| ApiError::ServiceUnavailable("deployer busy"))?;
+        let deadline = Instant::now() + self.receipt_timeout;
+
+        let provider = self.provider();
+        let chain_id = provider
+            .chain_id()
+            .await
+            .map_err(|e| provider_error("read chain id", e))?;
+        self.check_chain_id(chain_id)?;
+        let mut account = SingleOwnerAccount::new(
+            provider,
+            LocalWallet::from_signing_key(self.signing_key.clone()),
+            self.account_address,
+            chain_id,
+            ExecutionEncoding::New,
+        );
+        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+        let (declare_transaction_hash, declare_fee) =
+            if self.is_declared(&account, &classes).await? {
+                (None, None)
+            } else {
+                let (hash, fee) = self
+                    .declare(
+                        &account,
+                        classes.sierra,
+                        classes.compiled_class_hash,
+                        deadline,
+                    )
+                    .await?;
+                (Some(hash), Some(fee))
+            };
+
+        let factory = match self.udc_address {
+            Some(udc) => ContractFactory::new_with_udc(classes.class_hash, &account, udc),
+            None => ContractFactory::new(classes.class_hash, &account),
+        };
+        let deployment =
+            factory.deploy_v3(request.constructor_calldata, request.salt, request.unique);
+        let deploy_fee = deployment
+            .estimate_fee()
+            .await
+            .map_err(|e| account_error("estimate deployment fee", e))?;
+        let gas = Gas::from_estimate(&deploy_fee);
+        self.check_fee(&gas)?;
+        let sent = deployment
+            .l1_gas(gas.l1_gas)
+            .l1_gas_price(gas.l1_gas_price)
+            .l2_gas(gas.l2_gas)
+            .l2_gas_price(gas.l2_gas_price)
+            .l1_data_gas(gas.l1_data_gas)
+            .l1_data_gas_price(gas.l1_data_gas_price);
+        let contract_address = sent.deployed_address();
+        let transaction_hash = sent
+            .send()
+            .await
+            .map_err(|e| account_error("send deployment", e))?
+            .transaction_hash;
+
+        Ok(Sent {
+            outcome: DeployOutcome {
+                contract_address,
+                transaction_hash,
+                deploy_fee,
+                declare_transaction_hash,
+                declare_fee,
+            },
+            deadline,
+            _turn: turn,
+        })
+    }
+
+    /// Follow a sent deployment until its receipt shows up or the
+    /// deployment's time is up. A node failing meanwhile leaves it pending.
+    pub async fn confirm(&self, sent: Sent<'_>) -> TxStatus {
+        let hash = sent.outcome.transaction_hash;
+        self.wait_for(&self.provider(), hash, sent.deadline)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Lost track of transaction {:#x}: {:?}", hash, e);
+                TxStatus::Pending
+            })
+    }
+
+    /// Refuse a node on another chain than `network`, where the account
+    /// would sign for funds nobody meant to spend. Devnets pick their own id.
+    fn check_chain_id(&self, chain_id: Felt) -> Result<(), ApiError> {
+        let expected = match self.network {
+            Network::Mainnet => chain_id::MAINNET,
+            Network::Sepolia => chain_id::SEPOLIA,
+            Network::Devnet => return Ok(()),
+        };
+        if chain_id != expected {
+            tracing::error!(
+                "DEPLOYER_RPC_URL serves chain {:#x}, not {}",
+                chain_id,
+                self.network.as_str()
+            );
+            return Err(ApiError::ServiceUnavailable(
+                "deployer node is on another network",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Look up the receipt of `transaction_hash` once
+    pub async fn status(&self, transaction_hash: Felt) -> Result<TxStatus, ApiError> {
+        receipt_status(&self.provider(), transaction_hash).await
+    }
+
+    async fn is_declared(
+        &self,
+        account: &DeployerAccount,
+        classes: &Classes,
+    ) -> Result<bool, ApiError> {
+        match account
+            .provider()
+            .get_class(BlockId::Tag(BlockTag::Pending), classes.class_hash)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => Ok(false),
+            Err(e) => Err(provider_error("look up class", e)),
+        }
+    }
+
+    async fn declare(
+        &self,
+        account: &DeployerAccount,
+        sierra: FlattenedSierraClass,
+        compiled_class_hash: Felt,
+        deadline: Instant,
+    ) -> Result<(Felt, FeeEstimate), ApiError> {
+        let declaration = account.declare_v3(Arc::new(sierra), compiled_class_hash);
+        let fee = declaration
+            .estimate_fee()
+            .await
+            .map_err(|e| account_error("estimate declaration fee", e))?;
+        let gas = Gas::from_estimate(&fee);
+        self.check_fee(&gas)?;
+        let transaction_hash = declaration
+            .l1_gas(gas.l1_gas)
+            .l1_gas_price(gas.l1_gas_price)
+            .l2_gas(gas.l2_gas)
+            .l2_gas_price(gas.l2_gas_price)
+            .l1_data_gas(gas.l1_data_gas)
+            .l1_data_gas_price(gas.l1_data_gas_price)
+            .send()
+            .await
+            .map_err(|e| account_error("send declaration", e))?
+            .transaction_hash;
+        // Declaring twice is refused by the network, so a retry cannot pay
+        // for the class again
+        match self
+            .wait_for(account.provider(), transaction_hash, deadline)
+            .await?
+        {
+            TxStatus::Succeeded => Ok((transaction_hash, fee)),
+            TxStatus::Reverted => Err(ApiError::Unprocessable("transaction reverted")),
+            TxStatus::Pending => Err(ApiError::BadGateway(
+                "declaration was not confirmed in time",
+            )),
+        }
+    }
+
+    // The cap applies to what the bounds let the network charge, not to the
+    // estimate they were derived from.
+    fn check_fee(&self, gas: &Gas) -> Result<(), ApiError> {
+        if gas.max_fee() > self.max_fee {
+            return Err(ApiError::Unprocessable(
+                "fee bounds exceed the deployer's limit",
+            ));
+        }
+        Ok(())
+    }
+
+    // Poll until the receipt of `transaction_hash` shows up or `deadline`
+    // passes.
+    async fn wait_for(
+        &self,
+        provider: &JsonRpcClient<HttpTransport>,
+        transaction_hash: Felt,
+        deadline: Instant,
+    ) -> Result<TxStatus, ApiError> {
+        loop {
+            let status = receipt_status(provider, transaction_hash).await?;
+            if status != TxStatus::Pending {
+                return Ok(status);
+            }
+            if Instant::now() >= deadline {
+                tracing::warn!(
+                    "No receipt for transaction {:#x} after {:?}",
+                    transaction_hash,
+                    self.receipt_timeout
+                );
+                return Ok(TxStatus::Pending);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+async fn receipt_status(
+    provider: &JsonRpcClient<HttpTransport>,
+    transaction_hash: Felt,
+) -> Result<TxStatus, ApiError> {
+    match provider.get_transaction_receipt(transaction_hash).await {
+        Ok(receipt) => Ok(match receipt.receipt.execution_result() {
+            ExecutionResult::Succeeded => TxStatus::Succeeded,
+            ExecutionResult::Reverted { reason } => {
+                tracing::warn!("Transaction {:#x} reverted: {}", transaction_hash, reason);
+                TxStatus::Reverted
+            }
+        }),
+        Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {
+            Ok(TxStatus::Pending)
+        }
+        Err(e) => Err(provider_error("fetch receipt", e)),
+    }
+}
+
+// Resource bounds for a transaction, from its fee estimate plus margin.
+#[derive(Debug, PartialEq)]
+struct Gas {
+    l1_gas: u64,
+    l1_gas_price: u128,
+    l2_gas: u64,
+    l2_gas_price: u128,
+    l1_data_gas: u64,
+    l1_data_gas_price: u128,
+}
+
+impl Gas {
+    fn from_estimate(estimate: &FeeEstimate) -> Self {
+        let amount = |v: u64| (v as f64 * FEE_MARGIN) as u64;
+        let price = |v: u128| (v as f64 * FEE_MARGIN) as u128;
+        Self {
+            l1_gas: amount(estimate.l1_gas_consumed),
+            l1_gas_price: price(estimate.l1_gas_price),
+            l2_gas: amount(estimate.l2_gas_consumed),
+            l2_gas_price: price(estimate.l2_gas_price),
+            l1_data_gas: amount(estimate.l1_data_gas_consumed),
+            l1_data_gas_price: price(estimate.l1_data_gas_price),
+        }
+    }
+
+    /// Most the transaction can be charged, in fri
+    fn max_fee(&self) -> u128 {
+        let cost = |amount: u64, price: u128| (amount as u128).saturating_mul(price);
+        cost(self.l1_gas, self.l1_gas_price)
+            .saturating_add(cost(self.l2_gas, self.l2_gas_price))
+            .saturating_add(cost(self.l1_data_gas, self.l1_data_gas_price))
+    }
+}
+
+fn provider_error(action: &str, e: ProviderError) -> ApiError {
+    match e {
+        // The transaction would fail: a panicking constructor, an address
+        // already taken, an account short of STRK
+        ProviderError::StarknetError(
+            StarknetError::TransactionExecutionError(_)
+            | StarknetError::ContractError(_)
+            | StarknetError::ValidationFailure(_)
+            | StarknetError::InsufficientAccountBalance
+            | StarknetError::InsufficientResourcesForValidate
+            | StarknetError::CompilationFailed(_)
+            | StarknetError::ContractClassSizeIsTooLarge,
+        ) => {
+            tracing::warn!("Failed to {}: {:?}", action, e);
+            ApiError::Unprocessable("transaction rejected by the network")
+        }
+        e => {
+            tracing::error!("Failed to {}: {:?}", action, e);
+            ApiError::BadGateway("starknet node request failed")
+        }
+    }
+}
+
+fn account_error<S: std::fmt::Debug>(action: &str, e: AccountError<S>) -> ApiError {
+    match e {
+        AccountError::Provider(e) => provider_error(action, e),
+        e => {
+            tracing::error!("Failed to {}: {:?}", action, e);
+            ApiError::Internal("failed to prepare transaction")
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DeployerConfigError {
+    #[error("invalid DEPLOYER_RPC_URL")]
+    InvalidRpcUrl,
+    #[error(
+        "DEPLOYER_NETWORK, DEPLOYER_ACCOUNT_ADDRESS or DEPLOYER_PRIVATE_KEY is missing or invalid"
+    )]
+    InvalidAccount,
+    #[error("DEPLOYER_MAX_FEE must be set to a fee in fri")]
+    MissingMaxFee,
+    #[error("DEPLOYER_RECEIPT_TIMEOUT_SECS must be at most {}", MAX_RECEIPT_TIMEOUT.as_secs())]
+    ReceiptTimeout,
+}
+
+// Enabled by DEPLOYER_RPC_URL, DEPLOYER_ACCOUNT_ADDRESS and
+// DEPLOYER_PRIVATE_KEY; DEPLOYER_NETWORK names the network they point at.
+// The account spends real funds, so once enabled every setting including
+// DEPLOYER_MAX_FEE must be valid or the server does not start.
+pub fn deployer_from_env() -> Result<Option<Arc<Deployer>>, DeployerConfigError> {
+    fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+        std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+    }
+    let felt = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| Felt::from_hex(v.trim()).ok())
+    };
+
+    let Some(rpc_url) = std::env::var("DEPLOYER_RPC_URL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let rpc_url = Url::parse(rpc_url.trim()).map_err(|_| DeployerConfigError::InvalidRpcUrl)?;
+    let (Some(network), Some(address), Some(key)) = (
+        std::env::var("DEPLOYER_NETWORK")
+            .ok()
+            .and_then(|v| Network::parse(v.trim())),
+        felt("DEPLOYER_ACCOUNT_ADDRESS"),
+        felt("DEPLOYER_PRIVATE_KEY"),
+    ) else {
+        return Err(DeployerConfigError::InvalidAccount);
+    };
+    let max_fee = var("DEPLOYER_MAX_FEE").ok_or(DeployerConfigError::MissingMaxFee)?;
+
+    let defaults = Deployer::new(
+        network,
+        rpc_url,
+        address,
+        SigningKey::from_secret_scalar(key),
+        max_fee,
+    );
+    let receipt_timeout = var("DEPLOYER_RECEIPT_TIMEOUT_SECS")
+        .map(Duration::from_secs)
+        .unwrap_or(defaults.receipt_timeout);
+    if receipt_timeout > MAX_RECEIPT_TIMEOUT {
+        return Err(DeployerConfigError::ReceiptTimeout);
+    }
+    Ok(Some(Arc::new(Deployer {
+        udc_address: felt("DEPLOYER_UDC_ADDRESS"),
+        poll_interval: var("DEPLOYER_POLL_INTERVAL_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.poll_interval),
+        receipt_timeout,
+        ..defaults
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::core::types::PriceUnit;
+
+    #[test]
+    fn gas_bounds_add_margin_to_estimate() {
+        let estimate = FeeEstimate {
+            l1_gas_consumed: 0,
+            l1_gas_price: 1_000,
+            l2_gas_consumed: 800_000,
+            l2_gas_price: 10,
+            l1_data_gas_consumed: 128,
+            l1_data_gas_price: 3,
+            overall_fee: 8_000_384,
+            unit: PriceUnit::Fri,
+        };
+        assert_eq!(
+            Gas::from_estimate(&estimate),
+            Gas {
+                l1_gas: 0,
+                l1_gas_price: 1_500,
+                l2_gas: 1_200_000,
+                l2_gas_price: 15,
+                l1_data_gas: 192,
+                l1_data_gas_price: 4,
+            }
+        );
+        assert_eq!(Gas::from_estimate(&estimate).max_fee(), 18_000_768);
+    }
+
+    #[test]
+    fn fee_cap_applies_to_bounds() {
+        let estimate = FeeEstimate {
+            l1_gas_consumed: 0,
+            l1_gas_price: 1,
+            l2_gas_consumed: 1_000,
+            l2_gas_price: 10,
+            l1_data_gas_consumed: 0,
+            l1_data_gas_price: 1,
+            overall_fee: 10_000,
+            unit: PriceUnit::Fri,
+        };
+        let gas = Gas::from_estimate(&estimate);
+        let deployer = |max_fee| {
+            Deployer::new(
+                Network::Devnet,
+                Url::parse("http://127.0.0.1:9").unwrap(),
+                Felt::ONE,
+                SigningKey::from_secret_scalar(Felt::ONE),
+                max_fee,
+            )
+        };
+        assert!(matches!(
+            deployer(10_000).check_fee(&gas),
+            Err(ApiError::Unprocessable(_))
+        ));
+        assert!(deployer(22_500).check_fee(&gas).is_ok());
+    }
+
+    #[test]
+    fn chain_id_must_match_network() {
+        let deployer = |network| {
+            Deployer::new(
+                network,
+                Url::parse("http://127.0.0.1:9").unwrap(),
+                Felt::ONE,
+                SigningKey::from_secret_scalar(Felt::ONE),
+                1,
+            )
+        };
+        assert!(
+            deployer(Network::Mainnet)
+                .check_chain_id(chain_id::MAINNET)
+                .is_ok()
+        );
+        assert!(matches!(
+            deployer(Network::Mainnet).check_chain_id(chain_id::SEPOLIA),
+            Err(ApiError::ServiceUnavailable(_))
+        ));
+        assert!(
+            deployer(Network::Sepolia)
+                .check_chain_id(chain_id::MAINNET)
+                .is_err()
+        );
+        assert!(
+            deployer(Network::Devnet)
+                .check_chain_id(chain_id::SEPOLIA)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_artifacts() {
+        let stub = serde_json::json!({"sierra_program": ["0x1"], "abi": []});
+        assert!(matches!(
+            Classes::from_artifacts(&stub, &serde_json::json!({"bytecode": ["0x2"]})),
+            Err(ApiError::Unprocessable(_))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    Sepolia,
+    /// A local devnet (starknet-devnet, Katana)
+    Devnet,
+}
+
+impl Network {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Sepolia => "sepolia",
+            Network::Devnet => "devnet",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Network> {
+        match s {
+            "mainnet" => Some(Network::Mainnet),
+            "sepolia" => Some(Network::Sepolia),
+            "devnet" => Some(Network::Devnet),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Deployment {
+    pub id: i64,
+    pub contract_id: i64,
+    /// `mainnet`, `sepolia` or `devnet`
+    pub network: String,
+    pub contract_address: String,
+    pub class_hash: String,
+    pub transaction_hash: String,
+    /// Transaction that declared the class, when it was declared for this
+    /// deployment
+    pub declare_transaction_hash: Option<String>,
+    /// Revision of the contract's code that was deployed
+    pub revision: Option<i32>,
+    /// `pending` until the transaction is confirmed, `confirmed`, or `failed`
+    /// when it reverted
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A deployment to record; hashes and addresses already normalized
+#[derive(Debug, Clone)]
+pub struct NewDeployment {
+    pub network: Network,
+    pub contract_address: String,
+    pub class_hash: String,
+    pub transaction_hash: String,
+    pub declare_transaction_hash: Option<String>,
+    /// Revision of the code deployed
+    pub revision: Option<i32>,
+}
+
+/// Record a deployment of contract `contract_id` and mark the contract
+/// `deployed`.
+pub async fn record(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    new: &NewDeployment,
+) -> Result<Deployment, sqlx::Error> {
+    let deployment = insert(conn, contract_id, new, "confirmed").await?;
+    mark_deployed(conn, contract_id).await?;
+    Ok(deployment)
+}
+
+/// Record a deployment whose transaction was sent but not yet confirmed;
+/// `settle` finishes it.
+pub async fn record_pending(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    new: &NewDeployment,
+) -> Result<Deployment, sqlx::Error> {
+    insert(conn, contract_id, new, "pending").await
+}
+
+/// Settle pending deployment `id` as `confirmed`, marking its contract
+/// `deployed`, or as `failed`. Returns the status it has now, or `None`
+/// when there is no such deployment.
+pub async fn settle(
+    conn: &mut PgConnection,
+    id: i64,
+    confirmed: bool,
+) -> Result<Option<String>, sqlx::Error> {
+    let settled: Option<(i64, String)> = sqlx::query_as(
+        r#"UPDATE deployed_contracts SET status = $2
+           WHERE id = $1 AND status = 'pending'
+           RETURNING contract_id, status"#,
+    )
+    .bind(id)
+    .bind(if confirmed { "confirmed" } else { "failed" })
+    .fetch_optional(&mut *conn)
+    .await?;
+    match settled {
+        Some((contract_id, status)) => {
+            if confirmed {
+                mark_deployed(conn, contract_id).await?;
+            }
+            Ok(Some(status))
+        }
+        // Settled already
+        None => {
+            sqlx::query_scalar("SELECT status FROM deployed_contracts WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await
+        }
+    }
+}
+
+async fn insert(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    new: &NewDeployment,
+    status: &str,
+) -> Result<Deployment, sqlx::Error> {
+    let (id, created_at): (i64, DateTime<Utc>) = sqlx::query_as(
+        r#"INSERT INTO deployed_contracts
+               (contract_id, network, contract_address, class_hash, transaction_hash,
+                declare_transaction_hash, revision, status)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           RETURNING id, created_at"#,
+    )
+    .bind(contract_id)
+    .bind(new.network.as_str())
+    .bind(&new.contract_address)
+    .bind(&new.class_hash)
+    .bind(&new.transaction_hash)
+    .bind(&new.declare_transaction_hash)
+    .bind(new.revision)
+    .bind(status)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Deployment {
+        id,
+        contract_id,
+        network: new.network.as_str().to_string(),
+        contract_address: new.contract_address.clone(),
+        class_hash: new.class_hash.clone(),
+        transaction_hash: new.transaction_hash.clone(),
+        declare_transaction_hash: new.declare_transaction_hash.clone(),
+        revision: new.revision,
+        status: status.to_string(),
+        created_at,
+    })
+}
+
+async fn mark_deployed(conn: &mut PgConnection, contract_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE generated_contracts SET status = 'deployed' WHERE id = $1")
+        .bind(contract_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
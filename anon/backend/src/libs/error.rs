@@ -10,58 +10,86 @@ use utoipa::ToSchema;
 pub enum ApiError {
     BadRequest(&'static str),
     Unauthorized(&'static str),
+    Forbidden(&'static str),
     Conflict(&'static str),
     NotFound(&'static str),
     Internal(&'static str),
+    ServiceUnavailable(&'static str),
+    /// An upstream service answered with something unusable
+    BadGateway(&'static str),
+    /// 422: well-formed, but conflicts with an earlier request
+    Unprocessable(&'static str),
+    /// 400 listing every offending field at once
+    Validation(Vec<FieldError>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the offending value, e.g. `parameters.decimals`
+    pub field: String,
+    pub message: String,
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct ErrorBody {
     pub error: String,
+    /// Field-level errors, only present on validation failures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<FieldError>>,
 }
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
+impl ApiError {
+    /// The `error` field of the response body
+    pub fn message(&self) -> &'static str {
         match self {
-            ApiError::BadRequest(msg) => (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorBody {
-                    error: msg.to_string(),
-                }),
-            )
-                .into_response(),
-            ApiError::Unauthorized(msg) => (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorBody {
-                    error: msg.to_string(),
-                }),
-            )
-                .into_response(),
-            ApiError::Conflict(msg) => (
-                StatusCode::CONFLICT,
-                Json(ErrorBody {
-                    error: msg.to_string(),
-                }),
-            )
-                .into_response(),
-            ApiError::NotFound(msg) => (
-                StatusCode::NOT_FOUND,
-                Json(ErrorBody {
-                    error: msg.to_string(),
-                }),
-            )
-                .into_response(),
-            ApiError::Internal(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorBody {
-                    error: msg.to_string(),
-                }),
-            )
-                .into_response(),
+            ApiError::BadRequest(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::Forbidden(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::Internal(msg)
+            | ApiError::ServiceUnavailable(msg)
+            | ApiError::BadGateway(msg)
+            | ApiError::Unprocessable(msg) => msg,
+            ApiError::Validation(_) => "validation failed",
         }
     }
 }
 
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, msg) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            ApiError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            ApiError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg),
+            ApiError::Unprocessable(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
+            ApiError::Validation(details) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorBody {
+                        error: "validation failed".to_string(),
+                        details: Some(details),
+                    }),
+                )
+                    .into_response();
+            }
+        };
+        (
+            status,
+            Json(ErrorBody {
+                error: msg.to_string(),
+                details: None,
+            }),
+        )
+            .into_response()
+    }
+}
+
 pub fn map_sqlx_error(e: &sqlx::Error) -> ApiError {
     match e {
         sqlx::Error::Database(db) if db.code().as_deref() == Some("23505") => {
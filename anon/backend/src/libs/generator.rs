@@ -0,0 +1,494 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use utoipa::ToSchema;
+
+use semver::Version;
+
+use crate::libs::{
+    error::ApiError,
+    scarb,
+    templates::{self, Template},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratorKind {
+    Template,
+    Llm,
+}
+
+impl GeneratorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GeneratorKind::Template => "template",
+            GeneratorKind::Llm => "llm",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "template" => Some(GeneratorKind::Template),
+            "llm" => Some(GeneratorKind::Llm),
+            _ => None,
+        }
+    }
+}
+
+// Everything a generator gets to work from.
+#[derive(Debug)]
+pub struct GenerationRequest<'a> {
+    pub contract_type: &'a str,
+    /// `None` for free-form contract types, which only the LLM can handle
+    pub template: Option<&'static Template>,
+    pub contract_name: &'a str,
+    pub description: Option<&'a str>,
+    pub parameters: Option<&'a Value>,
+}
+
+#[async_trait]
+pub trait ContractGenerator: Send + Sync {
+    /// Complete Cairo source for the contract
+    async fn generate(&self, req: &GenerationRequest<'_>) -> Result<String, ApiError>;
+
+    /// Like `generate`, passing output to `on_output` as it is produced.
+    /// Generators that cannot stream report everything at once.
+    async fn generate_streaming(
+        &self,
+        req: &GenerationRequest<'_>,
+        on_output: &OutputSink<'_>,
+    ) -> Result<String, ApiError> {
+        let code = self.generate(req).await?;
+        on_output(&code);
+        Ok(code)
+    }
+}
+
+/// Receives generator output as it is produced
+pub type OutputSink<'a> = dyn Fn(&str) + Send + Sync + 'a;
+
+// Renders the built-in templates; deterministic and offline.
+pub struct TemplateGenerator;
+
+#[async_trait]
+impl ContractGenerator for TemplateGenerator {
+    async fn generate(&self, req: &GenerationRequest<'_>) -> Result<String, ApiError> {
+        let template = req
+            .template
+            .ok_or(ApiError::BadRequest("unknown contract_type"))?;
+        templates::render(template, req.contract_name, req.parameters)
+    }
+}
+
+// Asks an OpenAI-compatible chat completions endpoint to write the contract.
+pub struct LlmGenerator {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    /// Most bytes read from a reply, streamed or not, before giving up on it
+    pub max_reply_bytes: usize,
+}
+
+/// Default `LlmGenerator::max_reply_bytes`: far above any contract, well
+/// below what a misbehaving endpoint could make the server hold
+const MAX_REPLY_BYTES: usize = 2 * 1024 * 1024;
+
+const SYSTEM_PROMPT: &str = "You are an expert Cairo 2 and Starknet smart contract engineer. \
+Reply with exactly one complete, compilable Cairo 2 contract in a single ```cairo code block. \
+Use #[starknet::contract], the storage APIs of Cairo 2.9 or later, and OpenZeppelin Contracts \
+for Cairo 1.0.0 components where they fit. Do not leave TODOs or placeholders.";
+
+impl LlmGenerator {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`
+    pub fn new(base_url: &str, model: &str, api_key: Option<String>, timeout: Duration) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .expect("http client"),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key,
+            max_reply_bytes: MAX_REPLY_BYTES,
+        }
+    }
+
+    fn prompt(req: &GenerationRequest<'_>) -> Result<String, ApiError> {
+        // Template parameters are checked the same way as for rendering, so
+        // both generators accept exactly the same requests
+        let parameters = match req.template {
+            Some(t) => Value::Object(templates::resolve_params(t, req.parameters)?),
+            None => match req.parameters {
+                None | Some(Value::Null) => json!({}),
+                Some(Value::Object(map)) => Value::Object(map.clone()),
+                Some(_) => return Err(ApiError::BadRequest("parameters must be an object")),
+            },
+        };
+
+        let mut prompt = format!(
+            "Write a Starknet contract whose module is named `{}`.\n",
+            templates::module_name(req.contract_name)
+        );
+        match req.template {
+            Some(t) => {
+                prompt.push_str(&format!("Contract type: {} ({}).\n", t.name, t.description))
+            }
+            None => prompt.push_str(&format!("Contract type: {}.\n", req.contract_type.trim())),
+        }
+        if let Some(desc) = req.description.map(str::trim).filter(|d| !d.is_empty()) {
+            prompt.push_str(&format!("Requirements: {}\n", desc));
+        }
+        prompt.push_str(&format!("Parameters (JSON): {}\n", parameters));
+        Ok(prompt)
+    }
+
+    async fn send(
+        &self,
+        req: &GenerationRequest<'_>,
+        stream: bool,
+    ) -> Result<reqwest::Response, ApiError> {
+        let body = json!({
+            "model": self.model,
+            "temperature": 0.2,
+            "stream": stream,
+            "messages": [
+                {"role": "system", "content": SYSTEM_PROMPT},
+                {"role": "user", "content": Self::prompt(req)?},
+            ],
+        });
+
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            tracing::error!("LLM request failed: {:?}", e);
+            ApiError::ServiceUnavailable("llm generator unavailable")
+        })?;
+        if !response.status().is_success() {
+            tracing::error!("LLM endpoint answered {}", response.status());
+            return Err(ApiError::BadGateway("llm generator failed"));
+        }
+        Ok(response)
+    }
+
+    // Next chunk of the reply, counted against `max_reply_bytes`.
+    async fn chunk(
+        &self,
+        response: &mut reqwest::Response,
+        received: &mut usize,
+    ) -> Result<Option<axum::body::Bytes>, ApiError> {
+        let chunk = response.chunk().await.map_err(|e| {
+            tracing::error!("LLM response interrupted: {:?}", e);
+            ApiError::BadGateway("llm generator failed")
+        })?;
+        *received += chunk.as_ref().map_or(0, |c| c.len());
+        if *received > self.max_reply_bytes {
+            tracing::error!("LLM reply exceeded {} bytes", self.max_reply_bytes);
+            return Err(ApiError::BadGateway("llm reply too large"));
+        }
+        Ok(chunk)
+    }
+
+    // Message content of a non-streamed completion.
+    async fn content(&self, mut response: reqwest::Response) -> Result<String, ApiError> {
+        let mut body = Vec::new();
+        let mut received = 0;
+        while let Some(chunk) = self.chunk(&mut response, &mut received).await? {
+            body.extend_from_slice(&chunk);
+        }
+        let reply: Value = serde_json::from_slice(&body).map_err(|e| {
+            tracing::error!("Unreadable LLM response: {:?}", e);
+            ApiError::BadGateway("llm generator failed")
+        })?;
+        reply["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or(ApiError::BadGateway("llm generator failed"))
+    }
+
+    // Concatenated deltas of a streamed completion (`data: {...}` lines up to
+    // `data: [DONE]`), each passed to `on_output` as it arrives.
+    async fn stream_content(
+        &self,
+        mut response: reqwest::Response,
+        on_output: &OutputSink<'_>,
+    ) -> Result<String, ApiError> {
+        let mut pending = Vec::new();
+        let mut content = String::new();
+        let mut received = 0;
+        loop {
+            let Some(chunk) = self.chunk(&mut response, &mut received).await? else {
+                return Ok(content);
+            };
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    return Ok(content);
+                }
+                let delta: Value = serde_json::from_str(data).unwrap_or_default();
+                if let Some(text) = delta["choices"][0]["delta"]["content"].as_str() {
+                    on_output(text);
+                    content.push_str(text);
+                }
+            }
+        }
+    }
+}
+
+fn code_from_reply(reply: &str) -> Result<String, ApiError> {
+    extract_cairo(reply).ok_or(ApiError::BadGateway(
+        "llm reply contained no cairo contract",
+    ))
+}
+
+#[async_trait]
+impl ContractGenerator for LlmGenerator {
+    async fn generate(&self, req: &GenerationRequest<'_>) -> Result<String, ApiError> {
+        let response = self.send(req, false).await?;
+        code_from_reply(&self.content(response).await?)
+    }
+
+    async fn generate_streaming(
+        &self,
+        req: &GenerationRequest<'_>,
+        on_output: &OutputSink<'_>,
+    ) -> Result<String, ApiError> {
+        let response = self.send(req, true).await?;
+        let is_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        // Some OpenAI-compatible servers ignore `stream`
+        let reply = if is_stream {
+            self.stream_content(response, on_output).await?
+        } else {
+            let reply = self.content(response).await?;
+            on_output(&reply);
+            reply
+        };
+        code_from_reply(&reply)
+    }
+}
+
+// Cairo code from a model reply: the first ```cairo (or untagged/rust)
+// fenced block, or the whole reply if it is bare code. Anything without a
+// `#[starknet::contract]` module is rejected.
+pub fn extract_cairo(reply: &str) -> Option<String> {
+    let mut blocks = Vec::new();
+    let mut parts = reply.split("```");
+    parts.next();
+    while let (Some(block), _) = (parts.next(), parts.next()) {
+        let (info, code) = block.split_once('\n').unwrap_or((block, ""));
+        if matches!(info.trim(), "" | "cairo" | "rust") {
+            blocks.push(code);
+        }
+    }
+    let code = match blocks.into_iter().next() {
+        Some(code) => code,
+        None => reply,
+    }
+    .trim();
+
+    code.contains("#[starknet::contract]")
+        .then(|| format!("{}\n", code))
+}
+
+/// Code plus the Scarb.toml to build it with
+#[derive(Debug)]
+pub struct GeneratedContract {
+    pub code: String,
+    pub scarb_config: String,
+}
+
+// Available generators and how to pick one for a request.
+pub struct Generators {
+    pub template: Arc<dyn ContractGenerator>,
+    pub llm: Option<Arc<dyn ContractGenerator>>,
+    /// Used when neither the request nor `by_template` picks one
+    pub default: GeneratorKind,
+    /// Per `template_id` choice
+    pub by_template: HashMap<String, GeneratorKind>,
+}
+
+impl Generators {
+    // Request choice first, then the template's configured generator, then
+    // the default.
+    pub fn select(
+        &self,
+        requested: Option<GeneratorKind>,
+        template: Option<&Template>,
+    ) -> GeneratorKind {
+        requested
+            .or_else(|| template.and_then(|t| self.by_template.get(t.id).copied()))
+            .unwrap_or(self.default)
+    }
+
+    pub fn get(&self, kind: GeneratorKind) -> Result<&dyn ContractGenerator, ApiError> {
+        match kind {
+            GeneratorKind::Template => Ok(self.template.as_ref()),
+            GeneratorKind::Llm => self
+                .llm
+                .as_deref()
+                .ok_or(ApiError::ServiceUnavailable("llm generator not configured")),
+        }
+    }
+
+    // Run the `kind` generator and build a manifest targeting `cairo` for
+    // what it wrote. `on_output`, if any, sees the output as it streams.
+    pub async fn write(
+        &self,
+        kind: GeneratorKind,
+        req: &GenerationRequest<'_>,
+        cairo: &Version,
+        on_output: Option<&OutputSink<'_>>,
+    ) -> Result<GeneratedContract, ApiError> {
+        let generator = self.get(kind)?;
+        let code = match on_output {
+            Some(sink) => generator.generate_streaming(req, sink).await?,
+            None => generator.generate(req).await?,
+        };
+        let description = scarb::package_description(req.description, req.template.map(|t| t.name));
+        let scarb_config = scarb::generate(req.contract_name, &description, &[&code], cairo)?;
+        Ok(GeneratedContract { code, scarb_config })
+    }
+}
+
+impl Default for Generators {
+    fn default() -> Self {
+        Self {
+            template: Arc::new(TemplateGenerator),
+            llm: None,
+            default: GeneratorKind::Template,
+            by_template: HashMap::new(),
+        }
+    }
+}
+
+// The LLM generator is enabled by `LLM_API_URL` (with `LLM_MODEL`,
+// `LLM_API_KEY`, `LLM_TIMEOUT_SECS`). `CONTRACT_GENERATOR` sets the default
+// and `CONTRACT_GENERATOR_OVERRIDES` (`dao_governor=llm,...`) the per-template
+// choice.
+pub fn generators_from_env() -> Generators {
+    let mut generators = Generators::default();
+
+    if let Some(url) = std::env::var("LLM_API_URL").ok().filter(|v| !v.is_empty()) {
+        let model = std::env::var("LLM_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+        let api_key = std::env::var("LLM_API_KEY").ok().filter(|v| !v.is_empty());
+        let timeout = std::env::var("LLM_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(120);
+        let mut llm = LlmGenerator::new(&url, &model, api_key, Duration::from_secs(timeout));
+        if let Some(max) = std::env::var("LLM_MAX_REPLY_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+        {
+            llm.max_reply_bytes = max;
+        }
+        generators.llm = Some(Arc::new(llm));
+    }
+
+    if let Ok(v) = std::env::var("CONTRACT_GENERATOR") {
+        match GeneratorKind::parse(&v) {
+            Some(kind) => generators.default = kind,
+            None if v.is_empty() => {}
+            None => tracing::warn!("Ignoring unknown CONTRACT_GENERATOR {:?}", v),
+        }
+    }
+
+    let overrides = std::env::var("CONTRACT_GENERATOR_OVERRIDES").unwrap_or_default();
+    for entry in overrides.split(',').filter(|e| !e.trim().is_empty()) {
+        match entry.split_once('=') {
+            Some((id, kind)) if templates::find(id.trim()).is_some() => {
+                match GeneratorKind::parse(kind) {
+                    Some(kind) => {
+                        generators.by_template.insert(id.trim().to_string(), kind);
+                    }
+                    None => tracing::warn!("Ignoring generator override {:?}", entry),
+                }
+            }
+            _ => tracing::warn!("Ignoring generator override {:?}", entry),
+        }
+    }
+
+    generators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_fenced_cairo() {
+        let reply =
+            "Here you go:\n```json\n{}\n```\n```cairo\n#[starknet::contract]\nmod A {}\n```\nEnjoy";
+        assert_eq!(
+            extract_cairo(reply).as_deref(),
+            Some("#[starknet::contract]\nmod A {}\n")
+        );
+    }
+
+    #[test]
+    fn extracts_bare_code() {
+        let reply = "  #[starknet::contract]\nmod A {}  ";
+        assert_eq!(
+            extract_cairo(reply).as_deref(),
+            Some("#[starknet::contract]\nmod A {}\n")
+        );
+    }
+
+    #[test]
+    fn rejects_replies_without_a_contract() {
+        assert!(extract_cairo("I can't help with that.").is_none());
+        assert!(extract_cairo("```cairo\nfn main() {}\n```").is_none());
+    }
+
+    #[test]
+    fn selection_order() {
+        let mut generators = Generators::default();
+        generators
+            .by_template
+            .insert("dao_governor".into(), GeneratorKind::Llm);
+        let dao = templates::find("dao_governor");
+        let erc20 = templates::find("erc20");
+
+        assert_eq!(generators.select(None, erc20), GeneratorKind::Template);
+        assert_eq!(generators.select(None, dao), GeneratorKind::Llm);
+        assert_eq!(
+            generators.select(Some(GeneratorKind::Template), dao),
+            GeneratorKind::Template
+        );
+        assert!(generators.get(GeneratorKind::Llm).is_err());
+    }
+
+    #[test]
+    fn prompt_carries_description_and_parameters() {
+        let parameters = json!({"name": "Gold", "symbol": "GLD"});
+        let req = GenerationRequest {
+            contract_type: "erc20",
+            template: templates::find("erc20"),
+            contract_name: "gold token",
+            description: Some("Capped supply"),
+            parameters: Some(&parameters),
+        };
+        let prompt = LlmGenerator::prompt(&req).unwrap();
+        assert!(prompt.contains("`GoldToken`"));
+        assert!(prompt.contains("Requirements: Capped supply"));
+        assert!(prompt.contains("\"symbol\":\"GLD\""));
+        assert!(prompt.contains("\"decimals\":\"18\""));
+    }
+}
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Set on responses replayed from an earlier request
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted key
+pub const MAX_KEY_LEN: usize = 255;
+
+// A first request still unanswered after this long is assumed lost (the
+// process died) and its key may be claimed again.
+const STALE_AFTER: Duration = Duration::from_secs(600);
+
+/// How long keys are kept: IDEMPOTENCY_TTL_SECS, one day by default
+pub fn ttl_from_env() -> Duration {
+    std::env::var("IDEMPOTENCY_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(24 * 3600))
+}
+
+/// Whether `key` is 1 to 255 visible ASCII characters
+pub fn is_valid_key(key: &str) -> bool {
+    (1..=MAX_KEY_LEN).contains(&key.len()) && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// What a retry must match: method, path with query, and body
+pub fn request_hash(method: &str, uri: &str, body: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b" ");
+    hasher.update(uri.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher.finalize().to_vec()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Claim {
+    /// First use of the key: handle the request, then `complete` or `release`
+    /// it with this claim
+    New(Held),
+    /// Answer with the stored response
+    Replay(StoredResponse),
+    /// The first request has not been answered yet
+    InProgress,
+    /// The key was used for a different request
+    Mismatch,
+}
+
+/// Proof of holding a key: when it was claimed. A stale claim taken over by
+/// a retry no longer matches, so the first request cannot settle the key
+/// under the retry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Held(DateTime<Utc>);
+
+// response_status, response_headers, response_body, request_hash
+type KeyRow = (
+    Option<i16>,
+    Option<serde_json::Value>,
+    Option<Vec<u8>>,
+    Vec<u8>,
+);
+
+/// Reserve `key` of `wallet` for the request hashing to `hash`, or find
+/// what an earlier use of it left.
+pub async fn claim(
+    pool: &PgPool,
+    wallet: &str,
+    key: &str,
+    hash: &[u8],
+    ttl: Duration,
+) -> Result<Claim, sqlx::Error> {
+    sqlx::query(
+        r#"DELETE FROM idempotency_keys
+           WHERE expires_at <= NOW()
+              OR (wallet = $1 AND key = $2 AND response_status IS NULL
+                  AND created_at <= NOW() - make_interval(secs => $3))"#,
+    )
+    .bind(wallet)
+    .bind(key)
+    .bind(STALE_AFTER.as_secs_f64())
+    .execute(pool)
+    .await?;
+
+    let inserted: Option<(DateTime<Utc>,)> = sqlx::query_as(
+        r#"INSERT INTO idempotency_keys (wallet, key, request_hash, expires_at)
+           VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+           ON CONFLICT (wallet, key) DO NOTHING
+           RETURNING created_at"#,
+    )
+    .bind(wallet)
+    .bind(key)
+    .bind(hash)
+    .bind(ttl.as_secs_f64())
+    .fetch_optional(pool)
+    .await?;
+    if let Some((created_at,)) = inserted {
+        return Ok(Claim::New(Held(created_at)));
+    }
+
+    let row: Option<KeyRow> = sqlx::query_as(
+        r#"SELECT response_status, response_headers, response_body, request_hash
+           FROM idempotency_keys
+           WHERE wallet = $1 AND key = $2"#,
+    )
+    .bind(wallet)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match row {
+        // Released between the insert and the select
+        None => Claim::InProgress,
+        Some((_, _, _, stored)) if stored != hash => Claim::Mismatch,
+        Some((Some(status), headers, body, _)) => Claim::Replay(StoredResponse {
+            status: status as u16,
+            headers: headers
+                .and_then(|h| serde_json::from_value(h).ok())
+                .unwrap_or_default(),
+            body: body.unwrap_or_default(),
+        }),
+        Some((None, _, _, _)) => Claim::InProgress,
+    })
+}
+
+/// Store the response to the request that claimed `key`, if it still holds it
+pub async fn complete(
+    pool: &PgPool,
+    wallet: &str,
+    key: &str,
+    held: Held,
+    response: &StoredResponse,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE idempotency_keys
+           SET response_status = $4, response_headers = $5, response_body = $6
+           WHERE wallet = $1 AND key = $2 AND created_at = $3 AND response_status IS NULL"#,
+    )
+    .bind(wallet)
+    .bind(key)
+    .bind(held.0)
+    .bind(response.status as i16)
+    .bind(serde_json::json!(response.headers))
+    .bind(&response.body)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Forget `key`, if still held, so that a retry runs the request again
+pub async fn release(
+    pool: &PgPool,
+    wallet: &str,
+    key: &str,
+    held: Held,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"DELETE FROM idempotency_keys
+           WHERE wallet = $1 AND key = $2 AND created_at = $3 AND response_status IS NULL"#,
+    )
+    .bind(wallet)
+    .bind(key)
+    .bind(held.0)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_validated() {
+        assert!(is_valid_key("3f1c9a52-5d7e-4b7b-9a0e-1f2d3c4b5a69"));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("has space"));
+        assert!(!is_valid_key("clé"));
+        assert!(!is_valid_key(&"k".repeat(MAX_KEY_LEN + 1)));
+    }
+
+    #[test]
+    fn hash_covers_method_path_and_body() {
+        let hash = request_hash("POST", "/generate", b"{}");
+        assert_eq!(hash, request_hash("POST", "/generate", b"{}"));
+        assert_ne!(hash, request_hash("POST", "/generate", b"{ }"));
+        assert_ne!(hash, request_hash("POST", "/register", b"{}"));
+        assert_ne!(hash, request_hash("PUT", "/generate", b"{}"));
+    }
+}
@@ -0,0 +1,449 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+use tokio::sync::{Notify, broadcast};
+
+use crate::libs::{
+    audit::{self, AuditReport},
+    compiler::{self, CompileOutput, CompileReport},
+    db::AppState,
+    error::ApiError,
+    generator::{GeneratedContract, GenerationRequest, GeneratorKind},
+    revisions::{self, Source},
+    scarb, templates,
+};
+
+// Queue settings and the wake-up signal for idle workers.
+pub struct JobQueue {
+    pub workers: usize,
+    pub max_attempts: i32,
+    /// Delay before the first retry; doubles with every further attempt
+    pub retry_backoff: Duration,
+    /// How often idle workers look for due retries
+    pub poll_interval: Duration,
+    /// A running job whose worker has been silent this long is claimed again
+    pub lock_timeout: Duration,
+    pub notify: Notify,
+    pub events: JobEvents,
+}
+
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    /// The job row changed: claimed, retried or finished
+    Updated,
+    /// Generator output as it is produced
+    Output(String),
+    /// The generated code was built
+    Compiled(CompileReport),
+    /// The generated code was audited
+    Audited(AuditReport),
+}
+
+// Progress of the jobs run by this process's workers, for `/jobs/{id}/events`.
+// Only jobs someone listens to get a channel.
+#[derive(Default)]
+pub struct JobEvents {
+    channels: Mutex<HashMap<i64, broadcast::Sender<JobEvent>>>,
+}
+
+impl JobEvents {
+    pub fn subscribe(&self, job_id: i64) -> broadcast::Receiver<JobEvent> {
+        let mut channels = self.channels.lock().expect("job events lock");
+        channels.retain(|_, tx| tx.receiver_count() > 0);
+        channels
+            .entry(job_id)
+            .or_insert_with(|| broadcast::channel(256).0)
+            .subscribe()
+    }
+
+    pub fn publish(&self, job_id: i64, event: JobEvent) {
+        let mut channels = self.channels.lock().expect("job events lock");
+        if let Some(tx) = channels.get(&job_id) {
+            if tx.send(event).is_err() {
+                channels.remove(&job_id);
+            }
+        }
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            max_attempts: 3,
+            retry_backoff: Duration::from_secs(5),
+            poll_interval: Duration::from_secs(1),
+            lock_timeout: Duration::from_secs(600),
+            notify: Notify::new(),
+            events: JobEvents::default(),
+        }
+    }
+}
+
+impl JobQueue {
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let exp = attempts.saturating_sub(1).clamp(0, 16) as u32;
+        (self.retry_backoff * 2u32.pow(exp)).min(Duration::from_secs(3600))
+    }
+}
+
+// JOB_WORKERS, JOB_MAX_ATTEMPTS, JOB_RETRY_BACKOFF_SECS,
+// JOB_POLL_INTERVAL_MS and JOB_LOCK_TIMEOUT_SECS override the defaults.
+pub fn queue_from_env() -> JobQueue {
+    fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+        std::env::var(name).ok().and_then(|v| v.parse().ok())
+    }
+    let defaults = JobQueue::default();
+    JobQueue {
+        workers: var("JOB_WORKERS").unwrap_or(defaults.workers),
+        max_attempts: var::<i32>("JOB_MAX_ATTEMPTS")
+            .unwrap_or(defaults.max_attempts)
+            .max(1),
+        retry_backoff: var("JOB_RETRY_BACKOFF_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.retry_backoff),
+        poll_interval: var("JOB_POLL_INTERVAL_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.poll_interval),
+        lock_timeout: var("JOB_LOCK_TIMEOUT_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.lock_timeout),
+        ..defaults
+    }
+}
+
+// Queue a generation for an already inserted `queued` contract.
+pub async fn enqueue(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    cairo_version: &str,
+    max_attempts: i32,
+) -> Result<i64, sqlx::Error> {
+    let (id,): (i64,) = sqlx::query_as(
+        r#"INSERT INTO generation_jobs (contract_id, cairo_version, max_attempts)
+           VALUES ($1, $2, $3)
+           RETURNING id"#,
+    )
+    .bind(contract_id)
+    .bind(cairo_version)
+    .bind(max_attempts)
+    .fetch_one(conn)
+    .await?;
+    Ok(id)
+}
+
+#[derive(Debug)]
+pub struct ClaimedJob {
+    pub id: i64,
+    pub contract_id: i64,
+    pub cairo_version: String,
+    /// Including the current one
+    pub attempts: i32,
+    pub max_attempts: i32,
+}
+
+// Take the next due job, if any, and mark it and its contract as in progress.
+// SKIP LOCKED lets any number of workers, in any number of processes, poll
+// the same table without handing a job out twice.
+pub async fn claim(
+    pool: &PgPool,
+    lock_timeout: Duration,
+) -> Result<Option<ClaimedJob>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // A worker that died on its last attempt leaves nothing to retry
+    sqlx::query(
+        r#"WITH lost AS (
+               UPDATE generation_jobs
+               SET status = 'failed', locked_at = NULL, last_error = 'worker lost',
+                   updated_at = NOW()
+               WHERE status = 'running' AND attempts >= max_attempts
+                 AND locked_at < NOW() - make_interval(secs => $1)
+               RETURNING contract_id
+           )
+           UPDATE generated_contracts
+           SET status = 'failed', error = 'worker lost', updated_at = NOW()
+           WHERE id IN (SELECT contract_id FROM lost) AND status = 'generating'"#,
+    )
+    .bind(lock_timeout.as_secs_f64())
+    .execute(&mut *tx)
+    .await?;
+
+    let row: Option<(i64, i64, String, i32, i32)> = sqlx::query_as(
+        r#"UPDATE generation_jobs
+           SET status = 'running', attempts = attempts + 1, locked_at = NOW(), updated_at = NOW()
+           WHERE id = (
+               SELECT id FROM generation_jobs
+               WHERE (status = 'queued' AND run_at <= NOW())
+                  OR (status = 'running' AND attempts < max_attempts
+                      AND locked_at < NOW() - make_interval(secs => $1))
+               ORDER BY run_at, id
+               LIMIT 1
+               FOR UPDATE SKIP LOCKED
+           )
+           RETURNING id, contract_id, cairo_version, attempts, max_attempts"#,
+    )
+    .bind(lock_timeout.as_secs_f64())
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((id, contract_id, cairo_version, attempts, max_attempts)) = row else {
+        tx.commit().await?;
+        return Ok(None);
+    };
+    sqlx::query(
+        "UPDATE generated_contracts SET status = 'generating', updated_at = NOW() WHERE id = $1",
+    )
+    .bind(contract_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Some(ClaimedJob {
+        id,
+        contract_id,
+        cairo_version,
+        attempts,
+        max_attempts,
+    }))
+}
+
+// contract_type, contract_name, description, parameters, template_id, generator
+type JobContractRow = (
+    String,
+    String,
+    Option<String>,
+    Option<Value>,
+    Option<String>,
+    String,
+);
+
+async fn generate(state: &AppState, job: &ClaimedJob) -> Result<GeneratedContract, ApiError> {
+    let (contract_type, contract_name, description, parameters, template_id, generator): JobContractRow =
+        sqlx::query_as(
+            r#"SELECT contract_type, contract_name, description, parameters, template_id, generator
+               FROM generated_contracts WHERE id = $1"#,
+        )
+        .bind(job.contract_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    let kind = GeneratorKind::parse(&generator).unwrap_or(GeneratorKind::Template);
+    let cairo = scarb::parse_cairo_version(&job.cairo_version)?;
+    let req = GenerationRequest {
+        contract_type: &contract_type,
+        template: template_id.as_deref().and_then(templates::find),
+        contract_name: &contract_name,
+        description: description.as_deref(),
+        parameters: parameters.as_ref(),
+    };
+    let on_output = |text: &str| {
+        state
+            .jobs
+            .events
+            .publish(job.id, JobEvent::Output(text.to_string()))
+    };
+    state
+        .generators
+        .write(kind, &req, &cairo, Some(&on_output))
+        .await
+}
+
+// Upstream and server hiccups are worth another try; a rejected request is not.
+fn is_transient(e: &ApiError) -> bool {
+    matches!(
+        e,
+        ApiError::Internal(_) | ApiError::ServiceUnavailable(_) | ApiError::BadGateway(_)
+    )
+}
+
+async fn finish(
+    pool: &PgPool,
+    queue: &JobQueue,
+    job: &ClaimedJob,
+    result: Result<GeneratedContract, ApiError>,
+    build: Option<&CompileOutput>,
+) -> Result<Vec<JobEvent>, sqlx::Error> {
+    // Deleting a contract fails its queued job; a running one learns of it
+    // here. The contract is locked before the job, in the order
+    // `delete_contract` takes them.
+    let mut tx = pool.begin().await?;
+    let (deleted,): (bool,) = sqlx::query_as(
+        "SELECT deleted_at IS NOT NULL FROM generated_contracts WHERE id = $1 FOR UPDATE",
+    )
+    .bind(job.contract_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let result = if deleted {
+        Err(ApiError::NotFound("contract deleted"))
+    } else {
+        result
+    };
+
+    let retry = match &result {
+        Err(e) if is_transient(e) && job.attempts < job.max_attempts => {
+            Some(queue.backoff(job.attempts))
+        }
+        _ => None,
+    };
+    let status = match (&result, retry) {
+        (Ok(_), _) => "succeeded",
+        (Err(_), Some(_)) => "queued",
+        (Err(_), None) => "failed",
+    };
+
+    // Only the attempt that holds the job may settle it: a worker that
+    // outlived the lock timeout was superseded by another claim
+    let settled = sqlx::query(
+        r#"UPDATE generation_jobs
+           SET status = $3, locked_at = NULL, last_error = $4,
+               run_at = COALESCE(NOW() + make_interval(secs => $5), run_at), updated_at = NOW()
+           WHERE id = $1 AND status = 'running' AND attempts = $2"#,
+    )
+    .bind(job.id)
+    .bind(job.attempts)
+    .bind(status)
+    .bind(result.as_ref().err().map(|e| e.message()))
+    .bind(retry.map(|delay| delay.as_secs_f64()))
+    .execute(&mut *tx)
+    .await?;
+    if settled.rows_affected() == 0 {
+        tracing::warn!(
+            "Job {} attempt {} no longer holds the job, dropping its result",
+            job.id,
+            job.attempts
+        );
+        return Ok(Vec::new());
+    }
+
+    let mut events = Vec::new();
+    match result {
+        Ok(out) => {
+            let revision = revisions::add(
+                &mut tx,
+                job.contract_id,
+                Source::Generated,
+                &out.code,
+                Some(&out.scarb_config),
+                None,
+            )
+            .await?;
+            let findings = audit::analyze(&out.code);
+            let report = audit::record(&mut tx, job.contract_id, findings).await?;
+            events.push(JobEvent::Audited(report));
+            if let Some(build) = build {
+                let report =
+                    compiler::record(&mut tx, job.contract_id, Some(revision.revision), build)
+                        .await?;
+                events.push(JobEvent::Compiled(report));
+            }
+            tracing::info!("Job {} generated contract {}", job.id, job.contract_id);
+        }
+        Err(e) => match retry {
+            Some(delay) => {
+                sqlx::query(
+                    "UPDATE generated_contracts SET status = 'queued', updated_at = NOW() WHERE id = $1",
+                )
+                .bind(job.contract_id)
+                .execute(&mut *tx)
+                .await?;
+                tracing::warn!(
+                    "Job {} attempt {} failed ({}), retrying in {:?}",
+                    job.id,
+                    job.attempts,
+                    e.message(),
+                    delay
+                );
+            }
+            None => {
+                sqlx::query(
+                    r#"UPDATE generated_contracts
+                       SET status = 'failed', error = $2, updated_at = NOW()
+                       WHERE id = $1"#,
+                )
+                .bind(job.contract_id)
+                .bind(e.message())
+                .execute(&mut *tx)
+                .await?;
+                tracing::error!(
+                    "Job {} failed after {} attempt(s): {}",
+                    job.id,
+                    job.attempts,
+                    e.message()
+                );
+            }
+        },
+    }
+    tx.commit().await?;
+    Ok(events)
+}
+
+// Claim and run one job. Returns whether there was one.
+pub async fn run_next(state: &AppState) -> Result<bool, sqlx::Error> {
+    let Some(job) = claim(&state.pool, state.jobs.lock_timeout).await? else {
+        return Ok(false);
+    };
+    state.jobs.events.publish(job.id, JobEvent::Updated);
+    let result = generate(state, &job).await;
+
+    // With a compiler configured, queued generations are built right away
+    let build = match (&result, &state.compiler) {
+        (Ok(out), Some(compiler)) => match compiler.compile(&out.scarb_config, &out.code).await {
+            Ok(build) => Some(build),
+            Err(e) => {
+                tracing::warn!(
+                    "Skipping compile of contract {}: {}",
+                    job.contract_id,
+                    e.message()
+                );
+                None
+            }
+        },
+        _ => None,
+    };
+
+    let events = finish(&state.pool, &state.jobs, &job, result, build.as_ref()).await?;
+    for event in events {
+        state.jobs.events.publish(job.id, event);
+    }
+    state.jobs.events.publish(job.id, JobEvent::Updated);
+    Ok(true)
+}
+
+async fn worker(state: AppState, n: usize) {
+    tracing::info!("Generation worker {} started", n);
+    loop {
+        match run_next(&state).await {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => tracing::error!("Generation worker {} database error: {:?}", n, e),
+        }
+        tokio::select! {
+            _ = state.jobs.notify.notified() => {}
+            _ = tokio::time::sleep(state.jobs.poll_interval) => {}
+        }
+    }
+}
+
+// Start the in-process workers; they run until the process exits.
+pub fn spawn_workers(state: &AppState) {
+    for n in 0..state.jobs.workers {
+        tokio::spawn(worker(state.clone(), n));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles() {
+        let queue = JobQueue::default();
+        assert_eq!(queue.backoff(1), Duration::from_secs(5));
+        assert_eq!(queue.backoff(2), Duration::from_secs(10));
+        assert_eq!(queue.backoff(3), Duration::from_secs(20));
+        assert_eq!(queue.backoff(40), Duration::from_secs(3600));
+    }
+}
@@ -1,42 +1,497 @@
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use std::sync::OnceLock;
+
+use base64::{
+    Engine as _,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair, RsaPublicKeyComponents};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::libs::roles::Role;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String, // wallet address
     pub exp: usize,  // expiration as UTC timestamp (seconds)
+    pub iat: usize,  // issued at as UTC timestamp (seconds)
+    pub jti: String, // unique token id, used for revocation
+    #[serde(default)]
+    pub roles: Vec<Role>, // roles at issue time
 }
 
-pub fn secret_from_env() -> Vec<u8> {
-    std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "dev-secret-change-me".to_string())
-        .into_bytes()
+#[derive(Debug, Error)]
+pub enum KeyRingError {
+    #[error("invalid JWT_KEYS: {0}")]
+    Config(String),
+    #[error("key {0}: {1}")]
+    Key(String, String),
+    #[error("no active signing key")]
+    NoActiveKey,
+    #[error("key id {0} is used more than once")]
+    DuplicateKid(String),
+    #[error(
+        "no key configured: set JWT_KEYS, JWT_KEYS_FILE or JWT_SECRET (JWT_EPHEMERAL_KEY=true for development)"
+    )]
+    NoKeys,
 }
 
-#[allow(dead_code)] // This function is used in other parts of the codebase
-pub fn encode(wallet: &str, secret: &[u8]) -> Result<String, jsonwebtoken::errors::Error> {
-    let exp = std::time::SystemTime::now()
-        .checked_add(std::time::Duration::from_secs(60 * 60 * 24 * 7)) // 7 days
-        .unwrap()
+// One entry of `JWT_KEYS` / `JWT_KEYS_FILE`.
+#[derive(Debug, Deserialize)]
+pub struct KeyConfig {
+    pub kid: String,
+    pub alg: Algorithm,
+    /// HS256 shared secret
+    pub secret: Option<String>,
+    /// PKCS#8 private key for EdDSA / RS256
+    pub private_key_pem: Option<String>,
+    pub private_key_file: Option<String>,
+    /// Retired keys no longer sign, but still verify during the grace period
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+pub struct JwtKey {
+    pub kid: String,
+    pub alg: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    /// Public half, published through JWKS (`None` for symmetric keys)
+    jwk: Option<Jwk>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+pub struct KeyRing {
+    keys: Vec<JwtKey>,
+    active: usize,
+    grace: chrono::Duration,
+}
+
+pub fn access_ttl_from_env() -> u64 {
+    std::env::var("JWT_ACCESS_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60 * 15) // 15 minutes
+}
+
+pub fn new_claims(wallet: &str) -> Claims {
+    let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
-        .as_secs() as usize;
+        .as_secs();
 
-    let claims = Claims {
+    Claims {
         sub: wallet.to_string(),
-        exp,
-    };
-    jsonwebtoken::encode(
-        &Header::new(Algorithm::HS256),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
-}
-
-pub fn decode(token: &str, secret: &[u8]) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.validate_exp = true;
-    let data =
-        jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(secret), &validation)?;
-    Ok(data.claims)
+        exp: (now + access_ttl_from_env()) as usize,
+        iat: now as usize,
+        jti: format!("{:032x}", rand::random::<u128>()),
+        roles: Vec::new(),
+    }
+}
+
+impl JwtKey {
+    pub fn from_config(cfg: KeyConfig) -> Result<Self, KeyRingError> {
+        let err = |msg: &str| KeyRingError::Key(cfg.kid.clone(), msg.to_string());
+
+        let (encoding, decoding, jwk) = match cfg.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                let secret = cfg
+                    .secret
+                    .as_deref()
+                    .ok_or_else(|| err("secret required"))?;
+                (
+                    EncodingKey::from_secret(secret.as_bytes()),
+                    DecodingKey::from_secret(secret.as_bytes()),
+                    None,
+                )
+            }
+            Algorithm::EdDSA | Algorithm::RS256 => {
+                let pem = match (&cfg.private_key_pem, &cfg.private_key_file) {
+                    (Some(pem), _) => pem.clone(),
+                    (None, Some(path)) => {
+                        std::fs::read_to_string(path).map_err(|e| err(&e.to_string()))?
+                    }
+                    (None, None) => {
+                        return Err(err("private_key_pem or private_key_file required"));
+                    }
+                };
+                let der = pem_to_der(&pem).ok_or_else(|| err("invalid PEM"))?;
+                let params = public_params(cfg.alg, &der).ok_or_else(|| err("invalid key"))?;
+
+                let encoding = if cfg.alg == Algorithm::EdDSA {
+                    EncodingKey::from_ed_der(&der)
+                } else {
+                    EncodingKey::from_rsa_pem(pem.as_bytes()).map_err(|e| err(&e.to_string()))?
+                };
+                let jwk = Jwk {
+                    common: CommonParameters {
+                        public_key_use: Some(PublicKeyUse::Signature),
+                        key_algorithm: Some(if cfg.alg == Algorithm::EdDSA {
+                            KeyAlgorithm::EdDSA
+                        } else {
+                            KeyAlgorithm::RS256
+                        }),
+                        key_id: Some(cfg.kid.clone()),
+                        ..Default::default()
+                    },
+                    algorithm: params,
+                };
+                let decoding = DecodingKey::from_jwk(&jwk).map_err(|e| err(&e.to_string()))?;
+                (encoding, decoding, Some(jwk))
+            }
+            _ => return Err(err("unsupported algorithm")),
+        };
+
+        Ok(Self {
+            kid: cfg.kid,
+            alg: cfg.alg,
+            encoding,
+            decoding,
+            jwk,
+            retired_at: cfg.retired_at,
+        })
+    }
+}
+
+fn pem_to_der(pem: &str) -> Option<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .filter(|l| !l.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    STANDARD.decode(body).ok()
+}
+
+// Derive the public JWK parameters from a PKCS#8 private key.
+fn public_params(alg: Algorithm, der: &[u8]) -> Option<AlgorithmParameters> {
+    match alg {
+        Algorithm::EdDSA => {
+            let pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der).ok()?;
+            Some(AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(pair.public_key().as_ref()),
+            }))
+        }
+        Algorithm::RS256 => {
+            let pair = RsaKeyPair::from_pkcs8(der).ok()?;
+            let public: RsaPublicKeyComponents<Vec<u8>> = pair.public().into();
+            Some(AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(public.n),
+                e: URL_SAFE_NO_PAD.encode(public.e),
+            }))
+        }
+        _ => None,
+    }
+}
+
+// Without any configuration and with JWT_EPHEMERAL_KEY set for development,
+// every instance of the process shares one random key, so tokens are valid
+// until restart and no well-known secret exists.
+fn ephemeral_secret() -> &'static str {
+    static SECRET: OnceLock<String> = OnceLock::new();
+    SECRET.get_or_init(|| {
+        tracing::warn!("no JWT keys configured, using an ephemeral signing key");
+        let bytes: [u8; 32] = rand::random();
+        URL_SAFE_NO_PAD.encode(bytes)
+    })
+}
+
+impl KeyRing {
+    pub fn new(
+        keys: Vec<JwtKey>,
+        active_kid: Option<&str>,
+        grace: chrono::Duration,
+    ) -> Result<Self, KeyRingError> {
+        // A token's `kid` must name exactly one key
+        for (i, key) in keys.iter().enumerate() {
+            if keys[..i].iter().any(|k| k.kid == key.kid) {
+                return Err(KeyRingError::DuplicateKid(key.kid.clone()));
+            }
+        }
+        let active = match active_kid {
+            Some(kid) => keys.iter().position(|k| k.kid == kid),
+            None => keys.iter().position(|k| k.retired_at.is_none()),
+        }
+        .ok_or(KeyRingError::NoActiveKey)?;
+
+        if keys[active].retired_at.is_some() {
+            return Err(KeyRingError::NoActiveKey);
+        }
+
+        Ok(Self {
+            keys,
+            active,
+            grace,
+        })
+    }
+
+    // Single HS256 key, e.g. for tests or the legacy `JWT_SECRET`.
+    pub fn hs256(kid: &str, secret: &str) -> Self {
+        let key = JwtKey::from_config(KeyConfig {
+            kid: kid.to_string(),
+            alg: Algorithm::HS256,
+            secret: Some(secret.to_string()),
+            private_key_pem: None,
+            private_key_file: None,
+            retired_at: None,
+        })
+        .expect("hs256 key");
+        Self {
+            keys: vec![key],
+            active: 0,
+            grace: chrono::Duration::zero(),
+        }
+    }
+
+    // `JWT_KEYS` (inline JSON) or `JWT_KEYS_FILE`, then legacy `JWT_SECRET`.
+    pub fn from_env() -> Result<Self, KeyRingError> {
+        let grace = chrono::Duration::seconds(
+            std::env::var("JWT_KEY_GRACE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(access_ttl_from_env() as i64),
+        );
+
+        let raw = match (std::env::var("JWT_KEYS"), std::env::var("JWT_KEYS_FILE")) {
+            (Ok(json), _) if !json.trim().is_empty() => Some(json),
+            (_, Ok(path)) if !path.trim().is_empty() => Some(
+                std::fs::read_to_string(&path)
+                    .map_err(|e| KeyRingError::Config(format!("{}: {}", path, e)))?,
+            ),
+            _ => None,
+        };
+
+        let Some(raw) = raw else {
+            let secret = legacy_secret(
+                std::env::var("JWT_SECRET").ok(),
+                std::env::var("JWT_EPHEMERAL_KEY").is_ok_and(|v| v.trim() == "true"),
+            )?;
+            return Ok(Self::hs256("default", &secret));
+        };
+
+        let configs: Vec<KeyConfig> =
+            serde_json::from_str(&raw).map_err(|e| KeyRingError::Config(e.to_string()))?;
+        let keys = configs
+            .into_iter()
+            .map(JwtKey::from_config)
+            .collect::<Result<Vec<_>, _>>()?;
+        let active_kid = std::env::var("JWT_ACTIVE_KID").ok();
+
+        Self::new(keys, active_kid.as_deref(), grace)
+    }
+
+    pub fn active_kid(&self) -> &str {
+        &self.keys[self.active].kid
+    }
+
+    pub fn encode(&self, claims: &Claims) -> Result<String, jsonwebtoken::errors::Error> {
+        let key = &self.keys[self.active];
+        let mut header = Header::new(key.alg);
+        header.kid = Some(key.kid.clone());
+        jsonwebtoken::encode(&header, claims, &key.encoding)
+    }
+
+    pub fn decode(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        use jsonwebtoken::errors::ErrorKind;
+
+        let header = jsonwebtoken::decode_header(token)?;
+        // Tokens minted before key ids existed were signed with `JWT_SECRET`
+        let kid = header.kid.as_deref().unwrap_or("default");
+        let key = self
+            .find_usable(kid)
+            .ok_or_else(|| jsonwebtoken::errors::Error::from(ErrorKind::InvalidSignature))?;
+
+        let mut validation = Validation::new(key.alg);
+        validation.validate_exp = true;
+        let data = jsonwebtoken::decode::<Claims>(token, &key.decoding, &validation)?;
+        Ok(data.claims)
+    }
+
+    // Public keys of every key still accepted for verification.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .filter(|k| self.is_usable(k))
+                .filter_map(|k| k.jwk.clone())
+                .collect(),
+        }
+    }
+
+    fn find_usable(&self, kid: &str) -> Option<&JwtKey> {
+        self.keys
+            .iter()
+            .find(|k| k.kid == kid)
+            .filter(|k| self.is_usable(k))
+    }
+
+    fn is_usable(&self, key: &JwtKey) -> bool {
+        key.retired_at
+            .is_none_or(|retired_at| retired_at + self.grace > Utc::now())
+    }
+}
+
+// `JWT_SECRET`, else the ephemeral key when development allows it
+fn legacy_secret(jwt_secret: Option<String>, ephemeral: bool) -> Result<String, KeyRingError> {
+    match jwt_secret.filter(|s| !s.is_empty()) {
+        Some(secret) => Ok(secret),
+        None if ephemeral => Ok(ephemeral_secret().to_string()),
+        None => Err(KeyRingError::NoKeys),
+    }
+}
+
+pub fn encode(wallet: &str, keys: &KeyRing) -> Result<String, jsonwebtoken::errors::Error> {
+    keys.encode(&new_claims(wallet))
+}
+
+pub fn decode(token: &str, keys: &KeyRing) -> Result<Claims, jsonwebtoken::errors::Error> {
+    keys.decode(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ED25519_PEM: &str = include_str!("../../tests/fixtures/jwt/ed25519.pem");
+    const RSA_PEM: &str = include_str!("../../tests/fixtures/jwt/rsa.pem");
+
+    fn key(kid: &str, alg: Algorithm, retired_at: Option<DateTime<Utc>>) -> JwtKey {
+        let (secret, pem) = match alg {
+            Algorithm::EdDSA => (None, Some(ED25519_PEM.to_string())),
+            Algorithm::RS256 => (None, Some(RSA_PEM.to_string())),
+            _ => (Some(format!("secret-{}", kid)), None),
+        };
+        JwtKey::from_config(KeyConfig {
+            kid: kid.to_string(),
+            alg,
+            secret,
+            private_key_pem: pem,
+            private_key_file: None,
+            retired_at,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn roundtrip_each_algorithm() {
+        for alg in [Algorithm::HS256, Algorithm::EdDSA, Algorithm::RS256] {
+            let ring =
+                KeyRing::new(vec![key("k1", alg, None)], None, chrono::Duration::zero()).unwrap();
+            let token = encode("0x1", &ring).unwrap();
+
+            let header = jsonwebtoken::decode_header(&token).unwrap();
+            assert_eq!(header.kid.as_deref(), Some("k1"));
+            assert_eq!(header.alg, alg);
+            assert_eq!(decode(&token, &ring).unwrap().sub, "0x1");
+        }
+    }
+
+    #[test]
+    fn retired_key_verifies_only_during_grace() {
+        let old = KeyRing::new(
+            vec![key("old", Algorithm::HS256, None)],
+            None,
+            chrono::Duration::zero(),
+        )
+        .unwrap();
+        let token = encode("0x1", &old).unwrap();
+
+        let rotated = |retired_at: DateTime<Utc>| {
+            KeyRing::new(
+                vec![
+                    key("new", Algorithm::EdDSA, None),
+                    key("old", Algorithm::HS256, Some(retired_at)),
+                ],
+                None,
+                chrono::Duration::minutes(15),
+            )
+            .unwrap()
+        };
+
+        let in_grace = rotated(Utc::now() - chrono::Duration::minutes(5));
+        assert_eq!(in_grace.active_kid(), "new");
+        assert!(decode(&token, &in_grace).is_ok());
+
+        let past_grace = rotated(Utc::now() - chrono::Duration::hours(1));
+        assert!(decode(&token, &past_grace).is_err());
+    }
+
+    #[test]
+    fn duplicate_kid_rejected() {
+        let ring = KeyRing::new(
+            vec![
+                key("k1", Algorithm::HS256, None),
+                key("k1", Algorithm::EdDSA, None),
+            ],
+            None,
+            chrono::Duration::zero(),
+        );
+        assert!(matches!(ring, Err(KeyRingError::DuplicateKid(kid)) if kid == "k1"));
+    }
+
+    #[test]
+    fn ephemeral_key_only_when_asked_for() {
+        assert_eq!(legacy_secret(Some("s".into()), false).unwrap(), "s");
+        assert!(matches!(
+            legacy_secret(Some(String::new()), false),
+            Err(KeyRingError::NoKeys)
+        ));
+        let first = legacy_secret(None, true).unwrap();
+        assert_eq!(legacy_secret(None, true).unwrap(), first);
+    }
+
+    #[test]
+    fn unknown_kid_rejected() {
+        let a = KeyRing::hs256("a", "same-secret");
+        let b = KeyRing::hs256("b", "same-secret");
+        let token = encode("0x1", &a).unwrap();
+        assert!(decode(&token, &b).is_err());
+    }
+
+    #[test]
+    fn jwks_publishes_only_asymmetric_usable_keys() {
+        let ring = KeyRing::new(
+            vec![
+                key("ed", Algorithm::EdDSA, None),
+                key("rsa", Algorithm::RS256, None),
+                key("hs", Algorithm::HS256, None),
+                key(
+                    "gone",
+                    Algorithm::EdDSA,
+                    Some(Utc::now() - chrono::Duration::days(1)),
+                ),
+            ],
+            None,
+            chrono::Duration::minutes(15),
+        )
+        .unwrap();
+
+        let kids: Vec<_> = ring
+            .jwks()
+            .keys
+            .iter()
+            .map(|k| k.common.key_id.clone().unwrap())
+            .collect();
+        assert_eq!(kids, vec!["ed", "rsa"]);
+    }
+
+    #[test]
+    fn retired_key_cannot_be_active() {
+        let result = KeyRing::new(
+            vec![key("old", Algorithm::HS256, Some(Utc::now()))],
+            Some("old"),
+            chrono::Duration::zero(),
+        );
+        assert!(matches!(result, Err(KeyRingError::NoActiveKey)));
+    }
 }
@@ -17,3 +17,13 @@ pub fn decode_cursor(s: &str) -> Option<ReviewsCursor> {
     let bytes = URL_SAFE_NO_PAD.decode(s).ok()?;
     serde_json::from_slice(&bytes).ok()
 }
+
+/// Cursor for lists ordered by id alone
+pub fn encode_id_cursor(id: i64) -> String {
+    URL_SAFE_NO_PAD.encode(id.to_string())
+}
+
+pub fn decode_id_cursor(s: &str) -> Option<i64> {
+    let bytes = URL_SAFE_NO_PAD.decode(s).ok()?;
+    String::from_utf8(bytes).ok()?.parse().ok()
+}
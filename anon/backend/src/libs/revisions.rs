@@ -0,0 +1,194 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use similar::TextDiff;
+use sqlx::PgConnection;
+use utoipa::ToSchema;
+
+use crate::libs::abi;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// First generation, synchronous or queued
+    Generated,
+    /// `POST /generated_contracts/{id}/regenerate`
+    Regenerated,
+    /// `PUT /generated_contracts/{id}/code`
+    Edited,
+}
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Source::Generated => "generated",
+            Source::Regenerated => "regenerated",
+            Source::Edited => "edited",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Revision {
+    pub contract_id: i64,
+    /// 1 for the first generation, then one more per change
+    pub revision: i32,
+    /// `generated`, `regenerated` or `edited`
+    pub source: String,
+    /// Note given with a hand edit
+    pub message: Option<String>,
+    pub generated_code: String,
+    pub scarb_config: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Store `code` as the next revision of contract `contract_id` and make it
+/// the contract's current code. Its status goes back to `generated`, since
+/// builds of older code no longer apply; `deployed` and `archived` contracts
+/// keep theirs. The ABI extracted from `code` is stored along with it.
+/// Deleted contracts get no new code: that fails with `RowNotFound`.
+pub async fn add(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    source: Source,
+    code: &str,
+    scarb_config: Option<&str>,
+    message: Option<&str>,
+) -> Result<Revision, sqlx::Error> {
+    // The row lock serializes revision numbers per contract
+    let contract_name: String = sqlx::query_scalar(
+        r#"UPDATE generated_contracts
+           SET generated_code = $2, scarb_config = $3, error = NULL,
+               status = CASE WHEN status IN ('deployed', 'archived') THEN status
+                             ELSE 'generated' END
+           WHERE id = $1 AND deleted_at IS NULL
+           RETURNING contract_name"#,
+    )
+    .bind(contract_id)
+    .bind(code)
+    .bind(scarb_config)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let abi = abi::of_contract(&contract_name, scarb_config, code);
+    let abi = serde_json::to_value(&abi).expect("ABI serializes");
+    sqlx::query("UPDATE generated_contracts SET abi = $2 WHERE id = $1")
+        .bind(contract_id)
+        .bind(&abi)
+        .execute(&mut *conn)
+        .await?;
+
+    let (revision, created_at): (i32, DateTime<Utc>) = sqlx::query_as(
+        r#"INSERT INTO contract_revisions
+               (contract_id, revision, source, generated_code, scarb_config, message)
+           VALUES (
+               $1,
+               COALESCE((SELECT MAX(revision) FROM contract_revisions WHERE contract_id = $1), 0) + 1,
+               $2, $3, $4, $5
+           )
+           RETURNING revision, created_at"#,
+    )
+    .bind(contract_id)
+    .bind(source.as_str())
+    .bind(code)
+    .bind(scarb_config)
+    .bind(message)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Revision {
+        contract_id,
+        revision,
+        source: source.as_str().to_string(),
+        message: message.map(str::to_string),
+        generated_code: code.to_string(),
+        scarb_config: scarb_config.map(str::to_string),
+        created_at,
+    })
+}
+
+// revision, source, message, generated_code, scarb_config, created_at
+type RevisionRow = (
+    i32,
+    String,
+    Option<String>,
+    String,
+    Option<String>,
+    DateTime<Utc>,
+);
+
+/// Revision `revision` of contract `contract_id`; the latest one if `None`
+pub async fn find(
+    conn: &mut PgConnection,
+    contract_id: i64,
+    revision: Option<i32>,
+) -> Result<Option<Revision>, sqlx::Error> {
+    let row: Option<RevisionRow> = sqlx::query_as(
+        r#"SELECT revision, source, message, generated_code, scarb_config, created_at
+               FROM contract_revisions
+               WHERE contract_id = $1 AND ($2::INTEGER IS NULL OR revision = $2)
+               ORDER BY revision DESC
+               LIMIT 1"#,
+    )
+    .bind(contract_id)
+    .bind(revision)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row.map(
+        |(revision, source, message, generated_code, scarb_config, created_at)| Revision {
+            contract_id,
+            revision,
+            source,
+            message,
+            generated_code,
+            scarb_config,
+            created_at,
+        },
+    ))
+}
+
+/// Unified diff of the code of two revisions
+pub fn diff(from: &Revision, to: &Revision) -> String {
+    TextDiff::from_lines(&from.generated_code, &to.generated_code)
+        .unified_diff()
+        .context_radius(3)
+        .header(
+            &format!("src/lib.cairo (revision {})", from.revision),
+            &format!("src/lib.cairo (revision {})", to.revision),
+        )
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revision(revision: i32, code: &str) -> Revision {
+        Revision {
+            contract_id: 1,
+            revision,
+            source: "edited".to_string(),
+            message: None,
+            generated_code: code.to_string(),
+            scarb_config: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn diff_is_unified() {
+        let from = revision(1, "a\nb\nc\n");
+        let to = revision(2, "a\nB\nc\nd\n");
+        assert_eq!(
+            diff(&from, &to),
+            "--- src/lib.cairo (revision 1)\n\
+             +++ src/lib.cairo (revision 2)\n\
+             @@ -1,3 +1,4 @@\n \
+             a\n\
+             -b\n\
+             +B\n \
+             c\n\
+             +d\n"
+        );
+        assert_eq!(diff(&from, &from), "");
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use utoipa::ToSchema;
+
+use crate::libs::{
+    error::{ApiError, map_sqlx_error},
+    wallet,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Role> {
+        match s {
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+pub fn roles_from_db(roles: &[String]) -> Vec<Role> {
+    roles.iter().filter_map(|r| Role::parse(r)).collect()
+}
+
+// Comma-separated wallets that are always admins, used to bootstrap the
+// first admin before anyone can grant the role.
+pub fn admin_wallets_from_env() -> Vec<String> {
+    std::env::var("ADMIN_WALLETS")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|w| !w.trim().is_empty())
+        .filter_map(|w| wallet::normalize_and_validate(w).ok())
+        .collect()
+}
+
+// Roles to put in a fresh access token for `wallet`. Fails for disabled
+// users so that neither sign-in nor refresh hands them a token.
+pub async fn roles_for(conn: &mut PgConnection, wallet: &str) -> Result<Vec<Role>, ApiError> {
+    let rec: Option<(Vec<String>, Option<DateTime<Utc>>)> =
+        sqlx::query_as("SELECT roles, disabled_at FROM users WHERE wallet = $1")
+            .bind(wallet)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| map_sqlx_error(&e))?;
+
+    match rec {
+        Some((_, Some(_))) => Err(ApiError::Forbidden("account disabled")),
+        Some((roles, None)) => Ok(effective_roles(&roles, wallet)),
+        // Signed in but not registered yet
+        None => Ok(effective_roles(&[], wallet)),
+    }
+}
+
+// Roles stored for `wallet`, plus admin when it is listed in ADMIN_WALLETS.
+pub fn effective_roles(stored: &[String], wallet: &str) -> Vec<Role> {
+    let mut roles = roles_from_db(stored);
+    if !roles.contains(&Role::Admin) && admin_wallets_from_env().iter().any(|w| w == wallet) {
+        roles.push(Role::Admin);
+    }
+    roles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_roles_are_ignored() {
+        let roles = roles_from_db(&["admin".to_string(), "superuser".to_string()]);
+        assert_eq!(roles, vec![Role::Admin]);
+    }
+}
@@ -0,0 +1,449 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use semver::{Version, VersionReq};
+
+use crate::libs::error::ApiError;
+
+/// Cairo version targeted when a request does not name one
+pub const DEFAULT_CAIRO_VERSION: &str = "2.11.4";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Dependencies,
+    DevDependencies,
+}
+
+#[derive(Debug)]
+pub enum Source {
+    /// Published on the scarbs.xyz registry
+    Registry(&'static str),
+    Git {
+        url: &'static str,
+        tag: &'static str,
+    },
+    /// Ships with the compiler and takes its version (`starknet`)
+    Compiler,
+}
+
+#[derive(Debug)]
+pub struct Release {
+    pub source: Source,
+    /// Cairo versions this release builds with, as a semver requirement
+    pub cairo: &'static str,
+}
+
+#[derive(Debug)]
+pub struct Dependency {
+    /// Package name, which is also the first segment of its `use` paths
+    pub name: &'static str,
+    pub section: Section,
+    /// Newest first; the first release supporting the target Cairo wins
+    pub releases: &'static [Release],
+}
+
+const fn registry(version: &'static str, cairo: &'static str) -> Release {
+    Release {
+        source: Source::Registry(version),
+        cairo,
+    }
+}
+
+// The alexandria packages are released together
+static ALEXANDRIA_RELEASES: &[Release] = &[
+    registry("0.5.1", ">=2.11.2"),
+    registry("0.4.0", ">=2.10.0"),
+    Release {
+        source: Source::Git {
+            url: "https://github.com/keep-starknet-strange/alexandria.git",
+            tag: "v0.3.0",
+        },
+        cairo: ">=2.9.1",
+    },
+];
+
+const fn alexandria(name: &'static str) -> Dependency {
+    Dependency {
+        name,
+        section: Section::Dependencies,
+        releases: ALEXANDRIA_RELEASES,
+    }
+}
+
+pub static DEPENDENCIES: &[Dependency] = &[
+    Dependency {
+        name: "starknet",
+        section: Section::Dependencies,
+        releases: &[Release {
+            source: Source::Compiler,
+            cairo: "*",
+        }],
+    },
+    Dependency {
+        name: "openzeppelin",
+        section: Section::Dependencies,
+        releases: &[
+            registry("1.0.0", ">=2.9.4"),
+            registry("0.20.0", ">=2.9.1"),
+            registry("0.17.0", ">=2.8.2"),
+        ],
+    },
+    alexandria("alexandria_math"),
+    alexandria("alexandria_storage"),
+    alexandria("alexandria_data_structures"),
+    alexandria("alexandria_ascii"),
+    alexandria("alexandria_bytes"),
+    Dependency {
+        name: "snforge_std",
+        section: Section::DevDependencies,
+        releases: &[
+            registry("0.46.0", ">=2.11.0"),
+            registry("0.38.3", ">=2.9.4"),
+            registry("0.33.0", ">=2.8.5"),
+        ],
+    },
+];
+
+pub fn parse_cairo_version(v: &str) -> Result<Version, ApiError> {
+    Version::parse(v.trim()).map_err(|_| ApiError::BadRequest("invalid cairo_version"))
+}
+
+// First segments of every path in `source` (`openzeppelin` in
+// `use openzeppelin::token::erc20::ERC20Component;`). Comments are skipped;
+// roots that are not packages (`core`, `ERC20Component`, ...) are harmless
+// since only known dependencies are looked up.
+pub fn path_roots(source: &str) -> BTreeSet<&str> {
+    let mut roots = BTreeSet::new();
+    for line in source.lines() {
+        let code = line.split("//").next().unwrap_or_default();
+        let bytes = code.as_bytes();
+        let mut start = None;
+        for (i, &b) in bytes.iter().enumerate() {
+            let is_ident = b.is_ascii_alphanumeric() || b == b'_';
+            match (start, is_ident) {
+                (None, true) => {
+                    // Skip identifiers that continue a path (`a::b`)
+                    let continues_path = i >= 2 && &bytes[i - 2..i] == b"::";
+                    start = Some((i, continues_path));
+                }
+                (Some((s, continues_path)), false) => {
+                    if !continues_path && code[i..].starts_with("::") {
+                        roots.insert(&code[s..i]);
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    roots
+}
+
+// Dependencies `sources` need, each with the newest release that builds
+// with `cairo`.
+pub fn detect(
+    sources: &[&str],
+    cairo: &Version,
+) -> Result<Vec<(&'static Dependency, &'static Release)>, ApiError> {
+    let roots: BTreeSet<&str> = sources.iter().flat_map(|s| path_roots(s)).collect();
+    DEPENDENCIES
+        .iter()
+        .filter(|dep| roots.contains(dep.name))
+        .map(|dep| {
+            dep.releases
+                .iter()
+                .find(|r| {
+                    VersionReq::parse(r.cairo)
+                        .expect("dependency cairo requirement is valid")
+                        .matches(cairo)
+                })
+                .map(|r| (dep, r))
+                .ok_or(ApiError::BadRequest(
+                    "no dependency release supports the requested cairo_version",
+                ))
+        })
+        .collect()
+}
+
+// Scarb package name: `MyToken V2` -> `my_token_v2`.
+pub fn package_name(contract_name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in contract_name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    let out = out.trim_end_matches('_');
+    match out.chars().next() {
+        None => "contract".to_string(),
+        Some(c) if !c.is_ascii_lowercase() => format!("c_{}", out),
+        Some(_) => out.to_string(),
+    }
+}
+
+// `description` of the package: the contract's own description, or the
+// template it came from.
+pub fn package_description(description: Option<&str>, template_name: Option<&str>) -> String {
+    match description.map(str::trim) {
+        Some(d) if !d.is_empty() => d.to_string(),
+        _ => format!(
+            "{} generated by StarkFinder",
+            template_name.unwrap_or("Contract")
+        ),
+    }
+}
+
+// TOML basic string
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04X}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn dependency_line(dep: &Dependency, release: &Release, cairo: &Version) -> String {
+    let spec = match release.source {
+        Source::Registry(version) => quote(version),
+        Source::Git { url, tag } => format!("{{ git = {}, tag = {} }}", quote(url), quote(tag)),
+        Source::Compiler => quote(&cairo.to_string()),
+    };
+    format!("{} = {}\n", dep.name, spec)
+}
+
+/// Scarb.toml for a package built from `sources` (the contract, plus any
+/// test files).
+pub fn generate(
+    contract_name: &str,
+    description: &str,
+    sources: &[&str],
+    cairo: &Version,
+) -> Result<String, ApiError> {
+    let deps = detect(sources, cairo)?;
+
+    let mut out = String::new();
+    out.push_str("[package]\n");
+    let _ = writeln!(out, "name = {}", quote(&package_name(contract_name)));
+    out.push_str("version = \"0.1.0\"\n");
+    out.push_str("edition = \"2024_07\"\n");
+    let _ = writeln!(out, "cairo-version = {}", quote(&cairo.to_string()));
+    let _ = writeln!(out, "description = {}", quote(description));
+
+    for (section, header) in [
+        (Section::Dependencies, "[dependencies]"),
+        (Section::DevDependencies, "[dev-dependencies]"),
+    ] {
+        let lines: Vec<String> = deps
+            .iter()
+            .filter(|(dep, _)| dep.section == section)
+            .map(|(dep, release)| dependency_line(dep, release, cairo))
+            .collect();
+        if !lines.is_empty() {
+            let _ = write!(out, "\n{}\n{}", header, lines.concat());
+        }
+    }
+
+    out.push_str("\n[[target.starknet-contract]]\n");
+    out.push_str("sierra = true\n");
+    out.push_str("casm = true\n");
+
+    if deps.iter().any(|(dep, _)| dep.name == "snforge_std") {
+        out.push_str("\n[scripts]\n");
+        out.push_str("test = \"snforge test\"\n");
+    }
+    Ok(out)
+}
+
+// `config` plus the dev-dependencies `tests` need, for a manifest that was
+// generated for the contract alone.
+pub fn add_dev_dependencies(
+    config: &str,
+    tests: &[&str],
+    cairo: &Version,
+) -> Result<String, ApiError> {
+    let missing: Vec<(&Dependency, &Release)> = detect(tests, cairo)?
+        .into_iter()
+        .filter(|(dep, _)| dep.section == Section::DevDependencies)
+        .filter(|(dep, _)| {
+            let prefix = format!("{} = ", dep.name);
+            !config.lines().any(|l| l.starts_with(&prefix))
+        })
+        .collect();
+    if missing.is_empty() {
+        return Ok(config.to_string());
+    }
+
+    let lines: String = missing
+        .iter()
+        .map(|(dep, release)| dependency_line(dep, release, cairo))
+        .collect();
+    let mut out = config.to_string();
+    const HEADER: &str = "[dev-dependencies]\n";
+    match out.find(HEADER) {
+        Some(at) => out.insert_str(at + HEADER.len(), &lines),
+        None => {
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            let _ = write!(out, "\n{}{}", HEADER, lines);
+        }
+    }
+
+    if missing.iter().any(|(dep, _)| dep.name == "snforge_std") && !out.contains("[scripts]") {
+        out.push_str("\n[scripts]\n");
+        out.push_str("test = \"snforge test\"\n");
+    }
+    Ok(out)
+}
+
+// Cairo version a manifest from `generate` targets.
+pub fn cairo_version_of(config: &str) -> Option<Version> {
+    config
+        .lines()
+        .find_map(|l| l.strip_prefix("cairo-version = "))
+        .and_then(|v| Version::parse(v.trim_matches('"')).ok())
+}
+
+// Package name a manifest from `generate` declares.
+pub fn package_name_of(config: &str) -> Option<String> {
+    config
+        .lines()
+        .find_map(|l| l.strip_prefix("name = "))
+        .map(|v| v.trim_matches('"').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+#[starknet::contract]
+mod Token {
+    // use alexandria_math::pow;
+    use openzeppelin::token::erc20::{ERC20Component, ERC20HooksEmptyImpl};
+    use starknet::ContractAddress;
+
+    fn f() {
+        let x = core::num::traits::Zero::zero();
+        ERC20Component::InternalImpl::mint();
+    }
+}
+"#;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn finds_path_roots_outside_comments() {
+        let roots = path_roots(SOURCE);
+        assert!(roots.contains("openzeppelin"));
+        assert!(roots.contains("starknet"));
+        assert!(roots.contains("core"));
+        assert!(!roots.contains("alexandria_math"));
+        assert!(!roots.contains("token"));
+        assert!(!roots.contains("traits"));
+    }
+
+    #[test]
+    fn picks_newest_release_for_cairo_version() {
+        let pick = |src: &str, cairo: &str| {
+            detect(&[src], &v(cairo))
+                .unwrap()
+                .into_iter()
+                .map(|(d, r)| dependency_line(d, r, &v(cairo)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            pick(SOURCE, "2.11.4"),
+            ["starknet = \"2.11.4\"\n", "openzeppelin = \"1.0.0\"\n"]
+        );
+        assert_eq!(
+            pick("use openzeppelin::x;", "2.9.2")[0],
+            "openzeppelin = \"0.20.0\"\n"
+        );
+        assert_eq!(
+            pick("use alexandria_math::pow;", "2.9.2")[0],
+            "alexandria_math = { git = \"https://github.com/keep-starknet-strange/alexandria.git\", tag = \"v0.3.0\" }\n"
+        );
+        assert!(detect(&["use openzeppelin::x;"], &v("2.6.0")).is_err());
+    }
+
+    #[test]
+    fn package_names_are_snake_case() {
+        assert_eq!(package_name("MyToken"), "my_token");
+        assert_eq!(package_name("My Token V2"), "my_token_v2");
+        assert_eq!(package_name("ERC20 Gold"), "erc20_gold");
+        assert_eq!(package_name("1st drop"), "c_1st_drop");
+        assert_eq!(package_name("!!!"), "contract");
+    }
+
+    #[test]
+    fn generates_manifest() {
+        let toml = generate(
+            "MyToken",
+            "A \"gold\" token",
+            &["use openzeppelin::token;", "use snforge_std::declare;"],
+            &v("2.11.4"),
+        )
+        .unwrap();
+        assert_eq!(
+            toml,
+            r#"[package]
+name = "my_token"
+version = "0.1.0"
+edition = "2024_07"
+cairo-version = "2.11.4"
+description = "A \"gold\" token"
+
+[dependencies]
+openzeppelin = "1.0.0"
+
+[dev-dependencies]
+snforge_std = "0.46.0"
+
+[[target.starknet-contract]]
+sierra = true
+casm = true
+
+[scripts]
+test = "snforge test"
+"#
+        );
+        assert_eq!(cairo_version_of(&toml), Some(v("2.11.4")));
+        assert_eq!(package_name_of(&toml).as_deref(), Some("my_token"));
+    }
+
+    #[test]
+    fn adds_dev_dependencies_to_stored_manifest() {
+        let cairo = v("2.11.4");
+        let stored = generate("MyToken", "Gold", &["use openzeppelin::token;"], &cairo).unwrap();
+        let tests = ["use snforge_std::declare;"];
+
+        let toml = add_dev_dependencies(&stored, &tests, &cairo).unwrap();
+        assert!(toml.starts_with(&stored));
+        assert!(toml.contains("\n[dev-dependencies]\nsnforge_std = \"0.46.0\"\n"));
+        assert!(toml.ends_with("[scripts]\ntest = \"snforge test\"\n"));
+
+        // Already there: left alone
+        assert_eq!(add_dev_dependencies(&toml, &tests, &cairo).unwrap(), toml);
+    }
+}
@@ -0,0 +1,247 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+
+use crate::libs::{
+    error::{ApiError, map_sqlx_error},
+    jwt::{self, Claims, KeyRing},
+    roles::{self, Role},
+};
+
+// Access + refresh token pair handed out on sign-in and on every refresh.
+#[derive(Debug, Clone)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub expires_in: u64,
+    pub refresh_token: String,
+}
+
+// id, wallet, family_id, expires_at, revoked_at
+type RefreshTokenRow = (i64, String, String, DateTime<Utc>, Option<DateTime<Utc>>);
+
+pub fn refresh_ttl_from_env() -> i64 {
+    std::env::var("JWT_REFRESH_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60 * 60 * 24 * 30) // 30 days
+}
+
+pub fn generate_refresh_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// Refresh tokens are only ever stored as their SHA-256 digest.
+pub fn hash_token(token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
+
+// Mint a new access token and a refresh token in `family_id` (a new family
+// when `None`). Returns the pair and the id of the stored refresh token.
+pub async fn issue(
+    conn: &mut PgConnection,
+    keys: &KeyRing,
+    wallet: &str,
+    family_id: Option<&str>,
+) -> Result<(TokenPair, i64), ApiError> {
+    let mut claims = jwt::new_claims(wallet);
+    claims.roles = roles::roles_for(conn, wallet).await?;
+    let access_token = keys
+        .encode(&claims)
+        .map_err(|_| ApiError::Internal("failed to issue token"))?;
+
+    let refresh_token = generate_refresh_token();
+    let family_id = family_id
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:032x}", rand::random::<u128>()));
+    let expires_at = Utc::now() + chrono::Duration::seconds(refresh_ttl_from_env());
+
+    let (id,): (i64,) = sqlx::query_as(
+        r#"INSERT INTO refresh_tokens (wallet, token_hash, family_id, expires_at)
+           VALUES ($1, $2, $3, $4)
+           RETURNING id"#,
+    )
+    .bind(wallet)
+    .bind(hash_token(&refresh_token))
+    .bind(&family_id)
+    .bind(expires_at)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    Ok((
+        TokenPair {
+            access_token,
+            expires_in: jwt::access_ttl_from_env(),
+            refresh_token,
+        },
+        id,
+    ))
+}
+
+// Exchange a refresh token for a new pair. The presented token is retired;
+// presenting an already-retired token revokes its entire family.
+pub async fn rotate(
+    pool: &PgPool,
+    keys: &KeyRing,
+    refresh_token: &str,
+) -> Result<(String, TokenPair), ApiError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::Internal("failed to start transaction"))?;
+
+    let rec: Option<RefreshTokenRow> = sqlx::query_as(
+        r#"SELECT id, wallet, family_id, expires_at, revoked_at
+               FROM refresh_tokens
+               WHERE token_hash = $1
+               FOR UPDATE"#,
+    )
+    .bind(hash_token(refresh_token))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    let (id, wallet, family_id, expires_at, revoked_at) =
+        rec.ok_or(ApiError::Unauthorized("invalid refresh token"))?;
+
+    if revoked_at.is_some() {
+        tracing::warn!("refresh token reuse detected for wallet {}", wallet);
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(&family_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| map_sqlx_error(&e))?;
+        tx.commit()
+            .await
+            .map_err(|_| ApiError::Internal("failed to commit transaction"))?;
+        return Err(ApiError::Unauthorized("refresh token reused"));
+    }
+
+    if expires_at <= Utc::now() {
+        return Err(ApiError::Unauthorized("refresh token expired"));
+    }
+
+    let (pair, new_id) = issue(&mut tx, keys, &wallet, Some(&family_id)).await?;
+
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW(), replaced_by = $2 WHERE id = $1")
+        .bind(id)
+        .bind(new_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| map_sqlx_error(&e))?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::Internal("failed to commit transaction"))?;
+
+    Ok((wallet, pair))
+}
+
+// Kill an access token before its natural expiry.
+pub async fn revoke_access(pool: &PgPool, claims: &Claims) -> Result<(), ApiError> {
+    let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now);
+
+    // Entries past their expiry are dead weight: the token is rejected anyway
+    sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+        .execute(pool)
+        .await
+        .map_err(|e| map_sqlx_error(&e))?;
+
+    sqlx::query(
+        r#"INSERT INTO revoked_tokens (jti, wallet, expires_at) VALUES ($1, $2, $3)
+           ON CONFLICT (jti) DO NOTHING"#,
+    )
+    .bind(&claims.jti)
+    .bind(&claims.sub)
+    .bind(expires_at)
+    .execute(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+    Ok(())
+}
+
+// Revoke the family of `refresh_token`, provided it belongs to `wallet`.
+pub async fn revoke_family(
+    pool: &PgPool,
+    wallet: &str,
+    refresh_token: &str,
+) -> Result<(), ApiError> {
+    sqlx::query(
+        r#"UPDATE refresh_tokens SET revoked_at = NOW()
+           WHERE revoked_at IS NULL AND family_id = (
+               SELECT family_id FROM refresh_tokens WHERE token_hash = $1 AND wallet = $2
+           )"#,
+    )
+    .bind(hash_token(refresh_token))
+    .bind(wallet)
+    .execute(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+    Ok(())
+}
+
+// Revoke every outstanding refresh token of `wallet` (logout everywhere).
+pub async fn revoke_all(pool: &PgPool, wallet: &str) -> Result<(), ApiError> {
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE wallet = $1 AND revoked_at IS NULL",
+    )
+    .bind(wallet)
+    .execute(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+    Ok(())
+}
+
+// Reject every access token of `wallet` issued so far.
+pub async fn revoke_issued_access(pool: &PgPool, wallet: &str) -> Result<(), ApiError> {
+    sqlx::query(
+        r#"INSERT INTO session_cutoffs (wallet, revoked_before) VALUES ($1, NOW())
+           ON CONFLICT (wallet) DO UPDATE SET revoked_before = EXCLUDED.revoked_before"#,
+    )
+    .bind(wallet)
+    .execute(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+    Ok(())
+}
+
+// Whether an access token may still be used: it was not revoked, its wallet
+// did not log out everywhere after it was issued, and the wallet is not
+// disabled. `iat` has second precision, so a token issued within the same
+// second as a logout counts as older.
+//
+// Returns the wallet's current roles: the ones in the token may have been
+// revoked since it was issued.
+pub async fn check_access(pool: &PgPool, claims: &Claims) -> Result<Vec<Role>, ApiError> {
+    // revoked, disabled, roles
+    let (revoked, disabled, stored): (bool, bool, Option<Vec<String>>) = sqlx::query_as(
+        r#"SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)
+                  OR EXISTS (
+                      SELECT 1 FROM session_cutoffs
+                      WHERE wallet = $2 AND revoked_before >= to_timestamp($3)
+                  ),
+                  EXISTS (SELECT 1 FROM users WHERE wallet = $2 AND disabled_at IS NOT NULL),
+                  (SELECT roles FROM users WHERE wallet = $2)"#,
+    )
+    .bind(&claims.jti)
+    .bind(&claims.sub)
+    .bind(claims.iat as f64)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    if revoked {
+        return Err(ApiError::Unauthorized("token revoked"));
+    }
+    if disabled {
+        return Err(ApiError::Forbidden("account disabled"));
+    }
+    Ok(roles::effective_roles(
+        &stored.unwrap_or_default(),
+        &claims.sub,
+    ))
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use starknet::core::{
+    crypto::{Signature, ecdsa_verify},
+    types::{Felt, TypedData},
+};
+use std::str::FromStr;
+
+use crate::libs::error::ApiError;
+
+pub const DOMAIN_NAME: &str = "StarkFinder";
+pub const DOMAIN_VERSION: &str = "1";
+pub const STATEMENT: &str = "Sign in to StarkFinder";
+
+// The challenge a wallet signs to prove ownership. Timestamps are kept at
+// second precision so the message can be rebuilt byte-for-byte from the row.
+#[derive(Debug, Clone)]
+pub struct SignInMessage {
+    pub wallet: String,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub fn chain_id_from_env() -> String {
+    std::env::var("STARKNET_CHAIN_ID").unwrap_or_else(|_| "SN_MAIN".to_string())
+}
+
+pub fn nonce_ttl_from_env() -> i64 {
+    std::env::var("AUTH_NONCE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(300)
+}
+
+// 128 random bits rendered as a felt so it can be embedded in the typed data.
+pub fn generate_nonce() -> String {
+    format!("{:#034x}", rand::random::<u128>())
+}
+
+// SNIP-12 (revision 1) typed data the client passes to `account.signMessage`.
+pub fn typed_data_json(msg: &SignInMessage, chain_id: &str) -> serde_json::Value {
+    json!({
+        "types": {
+            "StarknetDomain": [
+                { "name": "name", "type": "shortstring" },
+                { "name": "version", "type": "shortstring" },
+                { "name": "chainId", "type": "shortstring" },
+                { "name": "revision", "type": "shortstring" }
+            ],
+            "SignIn": [
+                { "name": "statement", "type": "string" },
+                { "name": "wallet", "type": "ContractAddress" },
+                { "name": "nonce", "type": "felt" },
+                { "name": "issuedAt", "type": "timestamp" },
+                { "name": "expiresAt", "type": "timestamp" }
+            ]
+        },
+        "primaryType": "SignIn",
+        "domain": {
+            "name": DOMAIN_NAME,
+            "version": DOMAIN_VERSION,
+            "chainId": chain_id,
+            "revision": "1"
+        },
+        "message": {
+            "statement": STATEMENT,
+            "wallet": msg.wallet,
+            "nonce": msg.nonce,
+            "issuedAt": msg.issued_at.timestamp(),
+            "expiresAt": msg.expires_at.timestamp()
+        }
+    })
+}
+
+pub fn message_hash(msg: &SignInMessage, chain_id: &str) -> Result<Felt, ApiError> {
+    let typed: TypedData = serde_json::from_value(typed_data_json(msg, chain_id))
+        .map_err(|_| ApiError::Internal("failed to build typed data"))?;
+    let account = parse_felt(&msg.wallet)?;
+    typed
+        .message_hash(account)
+        .map_err(|_| ApiError::Internal("failed to hash typed data"))
+}
+
+pub fn parse_felt(s: &str) -> Result<Felt, ApiError> {
+    Felt::from_str(s.trim()).map_err(|_| ApiError::BadRequest("invalid felt"))
+}
+
+// Verify an `[r, s]` ECDSA signature over `hash` with a STARK public key.
+pub fn verify_ecdsa(public_key: &Felt, hash: &Felt, signature: &[Felt]) -> bool {
+    let [r, s] = signature else {
+        return false;
+    };
+    ecdsa_verify(public_key, hash, &Signature { r: *r, s: *s }).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::signers::SigningKey;
+
+    fn message() -> SignInMessage {
+        SignInMessage {
+            wallet: "0x1234".to_string(),
+            nonce: generate_nonce(),
+            issued_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            expires_at: DateTime::from_timestamp(1_700_000_300, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn signature_roundtrip() {
+        let key = SigningKey::from_secret_scalar(Felt::from(0xC0FFEE_u64));
+        let msg = message();
+        let hash = message_hash(&msg, "SN_MAIN").unwrap();
+        let sig = key.sign(&hash).unwrap();
+
+        assert!(verify_ecdsa(
+            &key.verifying_key().scalar(),
+            &hash,
+            &[sig.r, sig.s]
+        ));
+    }
+
+    #[test]
+    fn signature_rejected_for_other_message() {
+        let key = SigningKey::from_secret_scalar(Felt::from(0xC0FFEE_u64));
+        let msg = message();
+        let hash = message_hash(&msg, "SN_MAIN").unwrap();
+        let sig = key.sign(&hash).unwrap();
+
+        let other = message_hash(&msg, "SN_SEPOLIA").unwrap();
+        assert_ne!(hash, other);
+        assert!(!verify_ecdsa(
+            &key.verifying_key().scalar(),
+            &other,
+            &[sig.r, sig.s]
+        ));
+        assert!(!verify_ecdsa(
+            &key.verifying_key().scalar(),
+            &hash,
+            &[sig.r]
+        ));
+    }
+}
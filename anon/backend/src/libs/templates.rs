@@ -0,0 +1,753 @@
+use std::sync::OnceLock;
+
+use minijinja::{Environment, UndefinedBehavior, Value as TemplateValue};
+use serde_json::{Map, Value, json};
+
+use crate::libs::error::{ApiError, FieldError};
+
+#[derive(Debug, Clone, Copy)]
+pub enum ParamKind {
+    /// Cairo string literal of at most `max_len` characters
+    Text {
+        max_len: usize,
+    },
+    /// Unsigned integer fitting in `bits`, given as a JSON number or a
+    /// decimal string (for values beyond the JSON number range)
+    Uint {
+        bits: u32,
+        min: u64,
+    },
+    Bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ParamDefault {
+    /// Must be given; the JSON literal is an example value
+    Required(&'static str),
+    /// JSON literal
+    Value(&'static str),
+}
+
+#[derive(Debug)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: ParamDefault,
+    pub description: &'static str,
+}
+
+// Validation that spans several resolved parameters
+type ParamsCheck = fn(&Map<String, Value>) -> Result<(), FieldError>;
+
+/// Cairo compilers the templates are written for (OpenZeppelin Contracts for
+/// Cairo 1.0.0 needs 2.9.4 or later), as a semver requirement
+pub const CAIRO_VERSION_REQ: &str = ">=2.9.4";
+
+pub fn supports_cairo(version: &semver::Version) -> bool {
+    semver::VersionReq::parse(CAIRO_VERSION_REQ)
+        .expect("CAIRO_VERSION_REQ is a valid requirement")
+        .matches(version)
+}
+
+#[derive(Debug)]
+pub struct Template {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Bumped whenever the generated code changes
+    pub version: &'static str,
+    pub description: &'static str,
+    /// Other `contract_type` values that select this template
+    pub aliases: &'static [&'static str],
+    pub params: &'static [ParamSpec],
+    check: Option<ParamsCheck>,
+    source: &'static str,
+}
+
+const fn text(
+    name: &'static str,
+    max_len: usize,
+    default: ParamDefault,
+    description: &'static str,
+) -> ParamSpec {
+    ParamSpec {
+        name,
+        kind: ParamKind::Text { max_len },
+        default,
+        description,
+    }
+}
+
+const fn uint(
+    name: &'static str,
+    bits: u32,
+    min: u64,
+    default: &'static str,
+    description: &'static str,
+) -> ParamSpec {
+    ParamSpec {
+        name,
+        kind: ParamKind::Uint { bits, min },
+        default: ParamDefault::Value(default),
+        description,
+    }
+}
+
+const fn flag(name: &'static str, default: &'static str, description: &'static str) -> ParamSpec {
+    ParamSpec {
+        name,
+        kind: ParamKind::Bool,
+        default: ParamDefault::Value(default),
+        description,
+    }
+}
+
+pub static TEMPLATES: &[Template] = &[
+    Template {
+        id: "erc20",
+        name: "ERC20 Token",
+        version: "1.0.0",
+        description: "Fungible token built on the OpenZeppelin ERC20 component",
+        aliases: &["token", "erc_20", "fungible_token"],
+        params: &[
+            text("name", 64, ParamDefault::Required("\"Gold\""), "Token name"),
+            text(
+                "symbol",
+                16,
+                ParamDefault::Required("\"GLD\""),
+                "Token symbol",
+            ),
+            uint("decimals", 8, 0, "18", "Number of decimals"),
+            uint(
+                "initial_supply",
+                256,
+                0,
+                "\"0\"",
+                "Supply minted to the constructor's `recipient`, in base units",
+            ),
+            flag("mintable", "false", "Let the owner mint new tokens"),
+            flag("burnable", "false", "Let holders burn their tokens"),
+        ],
+        check: None,
+        source: include_str!("../../templates/cairo/erc20.cairo.j2"),
+    },
+    Template {
+        id: "erc721",
+        name: "ERC721 NFT",
+        version: "1.0.0",
+        description: "Non-fungible token collection with owner-only minting",
+        aliases: &["nft", "erc_721"],
+        params: &[
+            text(
+                "name",
+                64,
+                ParamDefault::Required("\"Art Pieces\""),
+                "Collection name",
+            ),
+            text(
+                "symbol",
+                16,
+                ParamDefault::Required("\"ART\""),
+                "Collection symbol",
+            ),
+            text(
+                "base_uri",
+                256,
+                ParamDefault::Value("\"\""),
+                "Prefix of token URIs",
+            ),
+            flag("burnable", "false", "Let holders burn their tokens"),
+        ],
+        check: None,
+        source: include_str!("../../templates/cairo/erc721.cairo.j2"),
+    },
+    Template {
+        id: "erc1155",
+        name: "ERC1155 Multi Token",
+        version: "1.0.0",
+        description: "Multi-token contract with owner-only minting",
+        aliases: &["multi_token", "erc_1155"],
+        params: &[
+            text(
+                "base_uri",
+                256,
+                ParamDefault::Value("\"\""),
+                "Token metadata URI",
+            ),
+            flag("burnable", "false", "Let holders burn their tokens"),
+        ],
+        check: None,
+        source: include_str!("../../templates/cairo/erc1155.cairo.j2"),
+    },
+    Template {
+        id: "ownable_counter",
+        name: "Ownable Counter",
+        version: "1.0.0",
+        description: "Counter that anyone can step and only the owner can reset",
+        aliases: &["counter"],
+        params: &[
+            uint(
+                "initial_value",
+                64,
+                0,
+                "0",
+                "Value set at deployment and on reset",
+            ),
+            uint("step", 64, 1, "1", "Amount added or removed per call"),
+            flag(
+                "owner_only_increment",
+                "false",
+                "Restrict increment and decrement to the owner",
+            ),
+        ],
+        check: None,
+        source: include_str!("../../templates/cairo/ownable_counter.cairo.j2"),
+    },
+    Template {
+        id: "vesting",
+        name: "Token Vesting",
+        version: "1.0.0",
+        description: "Linear vesting of any ERC20 balance to a beneficiary, with a cliff",
+        aliases: &["vesting_wallet", "token_vesting"],
+        params: &[
+            uint(
+                "cliff_seconds",
+                64,
+                0,
+                "0",
+                "Nothing vests before start + cliff",
+            ),
+            uint(
+                "duration_seconds",
+                64,
+                1,
+                "31536000",
+                "Everything has vested at start + duration",
+            ),
+        ],
+        check: Some(check_vesting),
+        source: include_str!("../../templates/cairo/vesting.cairo.j2"),
+    },
+    Template {
+        id: "staking",
+        name: "Staking Rewards",
+        version: "1.0.0",
+        description: "Stake one ERC20 and earn another at a fixed rate",
+        aliases: &["staking_pool", "staking_rewards"],
+        params: &[uint(
+            "reward_rate",
+            128,
+            1,
+            "\"1000000000000000\"",
+            "Reward tokens (base units) distributed per second across all stakers",
+        )],
+        check: None,
+        source: include_str!("../../templates/cairo/staking.cairo.j2"),
+    },
+    Template {
+        id: "multisig",
+        name: "Multisig Wallet",
+        version: "1.0.0",
+        description: "M-of-N wallet executing arbitrary calls once confirmed",
+        aliases: &["multisig_wallet", "multi_sig"],
+        params: &[uint(
+            "threshold",
+            32,
+            1,
+            "2",
+            "Confirmations required to execute a transaction",
+        )],
+        check: None,
+        source: include_str!("../../templates/cairo/multisig.cairo.j2"),
+    },
+    Template {
+        id: "dao_governor",
+        name: "DAO Governor",
+        version: "1.0.0",
+        description: "Token-weighted governance over single-call proposals",
+        aliases: &["dao", "governor"],
+        params: &[
+            uint(
+                "voting_delay_seconds",
+                64,
+                0,
+                "86400",
+                "Delay between proposal and the start of voting",
+            ),
+            uint(
+                "voting_period_seconds",
+                64,
+                1,
+                "604800",
+                "Length of the voting window",
+            ),
+            uint(
+                "quorum",
+                256,
+                0,
+                "\"1000000000000000000000\"",
+                "Minimum for + abstain votes for a proposal to pass",
+            ),
+            uint(
+                "proposal_threshold",
+                256,
+                0,
+                "\"0\"",
+                "Votes needed to create a proposal",
+            ),
+        ],
+        check: None,
+        source: include_str!("../../templates/cairo/dao_governor.cairo.j2"),
+    },
+];
+
+fn check_vesting(params: &Map<String, Value>) -> Result<(), FieldError> {
+    let get = |name: &str| {
+        params
+            .get(name)
+            .and_then(Value::as_str)
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or_default()
+    };
+    if get("cliff_seconds") > get("duration_seconds") {
+        return Err(field_error(
+            "cliff_seconds",
+            "must not exceed duration_seconds",
+        ));
+    }
+    Ok(())
+}
+
+pub fn find(id: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|t| t.id == id)
+}
+
+// `contract_type` is matched case-insensitively against template ids and
+// aliases, with `-` and spaces read as `_`.
+pub fn find_by_contract_type(contract_type: &str) -> Option<&'static Template> {
+    let key = contract_type.trim().to_lowercase().replace(['-', ' '], "_");
+    TEMPLATES
+        .iter()
+        .find(|t| t.id == key || t.aliases.contains(&key.as_str()))
+}
+
+// Pick the template for a request. An explicit `template_id` wins, but must
+// belong to the requested `contract_type`.
+pub fn resolve(
+    contract_type: &str,
+    template_id: Option<&str>,
+) -> Result<&'static Template, ApiError> {
+    let by_type = find_by_contract_type(contract_type);
+    match template_id.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => {
+            let template = find(id).ok_or(ApiError::BadRequest("unknown template_id"))?;
+            match by_type {
+                Some(t) if t.id == template.id => Ok(template),
+                _ => Err(ApiError::BadRequest(
+                    "template_id does not match contract_type",
+                )),
+            }
+        }
+        None => by_type.ok_or(ApiError::BadRequest("unknown contract_type")),
+    }
+}
+
+// Cairo module name derived from the contract name: `my token` -> `MyToken`.
+pub fn module_name(contract_name: &str) -> String {
+    let mut out: String = contract_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect();
+    if out.is_empty() {
+        out.push_str("Contract");
+    }
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, 'C');
+    }
+    out
+}
+
+const U256_MAX: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+// Decimal digits of an unsigned integer, without leading zeros.
+fn uint_digits(value: &Value) -> Option<String> {
+    let digits = match value {
+        Value::Number(n) => n.as_u64()?.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        _ => return None,
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(match digits.trim_start_matches('0') {
+        "" => "0".to_string(),
+        d => d.to_string(),
+    })
+}
+
+fn fits_bits(digits: &str, bits: u32) -> bool {
+    if bits <= 128 {
+        digits
+            .parse::<u128>()
+            .is_ok_and(|n| bits == 128 || n >> bits == 0)
+    } else {
+        digits.len() < U256_MAX.len() || (digits.len() == U256_MAX.len() && digits <= U256_MAX)
+    }
+}
+
+// Text ends up inside a Cairo string literal, so only plain printable ASCII
+// without quotes or backslashes is accepted.
+fn is_safe_text(s: &str) -> bool {
+    s.chars()
+        .all(|c| c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\')
+}
+
+fn field_error(name: &str, message: impl Into<String>) -> FieldError {
+    FieldError {
+        field: format!("parameters.{}", name),
+        message: message.into(),
+    }
+}
+
+// Check one value against its spec, returning it normalized: text trimmed,
+// integers as decimal strings.
+fn check_param(spec: &ParamSpec, value: &Value) -> Result<Value, String> {
+    match spec.kind {
+        ParamKind::Text { max_len } => {
+            let s = value.as_str().ok_or("must be a string")?.trim();
+            if s.chars().count() > max_len {
+                return Err(format!("must be at most {} characters", max_len));
+            }
+            if !is_safe_text(s) {
+                return Err("must be printable ASCII without quotes or backslashes".into());
+            }
+            Ok(Value::String(s.to_string()))
+        }
+        ParamKind::Uint { bits, min } => {
+            let digits = uint_digits(value)
+                .ok_or("must be an unsigned integer (number or decimal string)")?;
+            if !fits_bits(&digits, bits) {
+                return Err(format!("must fit in u{}", bits));
+            }
+            if digits.len() < 20 && digits.parse::<u64>().is_ok_and(|n| n < min) {
+                return Err(format!("must be at least {}", min));
+            }
+            Ok(Value::String(digits))
+        }
+        ParamKind::Bool => value
+            .as_bool()
+            .map(Value::Bool)
+            .ok_or_else(|| "must be a boolean".into()),
+    }
+}
+
+// Apply defaults and check every parameter against its spec, reporting all
+// offending fields together.
+pub fn resolve_params(
+    template: &Template,
+    parameters: Option<&Value>,
+) -> Result<Map<String, Value>, ApiError> {
+    let given = match parameters {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(map)) => map.clone(),
+        Some(_) => {
+            return Err(ApiError::Validation(vec![FieldError {
+                field: "parameters".into(),
+                message: "must be an object".into(),
+            }]));
+        }
+    };
+
+    let mut errors: Vec<FieldError> = given
+        .keys()
+        .filter(|k| !template.params.iter().any(|p| p.name == k.as_str()))
+        .map(|k| field_error(k, "unknown parameter"))
+        .collect();
+
+    let mut out = Map::new();
+    for spec in template.params {
+        let value = match (given.get(spec.name), spec.default) {
+            (Some(v), _) if !v.is_null() => v.clone(),
+            (_, ParamDefault::Value(json)) => {
+                serde_json::from_str(json).expect("template default is valid JSON")
+            }
+            (_, ParamDefault::Required(_)) => {
+                errors.push(field_error(spec.name, "is required"));
+                continue;
+            }
+        };
+        match check_param(spec, &value) {
+            Ok(value) => {
+                out.insert(spec.name.to_string(), value);
+            }
+            Err(message) => errors.push(field_error(spec.name, message)),
+        }
+    }
+
+    // Cross-field checks only make sense once every field is valid
+    if errors.is_empty() {
+        if let Some(check) = template.check {
+            check(&out).map_err(|e| ApiError::Validation(vec![e]))?;
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::Validation(errors));
+    }
+    Ok(out)
+}
+
+// Printable ASCII without `"` or `\`, as enforced by `is_safe_text`
+const SAFE_TEXT_PATTERN: &str = r"^[ !#-\[\]-~]*$";
+
+impl ParamSpec {
+    /// JSON Schema for the values this parameter accepts
+    pub fn schema(&self) -> Value {
+        let mut schema = match self.kind {
+            ParamKind::Text { max_len } => json!({
+                "type": "string",
+                "maxLength": max_len,
+                "pattern": SAFE_TEXT_PATTERN,
+            }),
+            ParamKind::Uint { bits, min } => {
+                let max = if bits >= 64 {
+                    u64::MAX
+                } else {
+                    (1u64 << bits) - 1
+                };
+                let format = format!("u{}", bits);
+                json!({
+                    "anyOf": [
+                        {"type": "integer", "format": format, "minimum": min, "maximum": max},
+                        {"type": "string", "format": format, "pattern": "^[0-9]+$"},
+                    ],
+                })
+            }
+            ParamKind::Bool => json!({"type": "boolean"}),
+        };
+        schema["description"] = self.description.into();
+        match self.default {
+            ParamDefault::Value(json) => {
+                schema["default"] =
+                    serde_json::from_str(json).expect("template default is valid JSON");
+            }
+            ParamDefault::Required(json) => {
+                schema["examples"] =
+                    json!([serde_json::from_str::<Value>(json).expect("example is valid JSON")]);
+            }
+        }
+        schema
+    }
+}
+
+impl Template {
+    /// Name of the parameters schema in the OpenAPI components, e.g. `Erc20Parameters`
+    pub fn schema_name(&self) -> String {
+        format!("{}Parameters", module_name(self.id))
+    }
+
+    /// The smallest valid `parameters`: an example for each required one
+    pub fn example_parameters(&self) -> Value {
+        let params: Map<String, Value> = self
+            .params
+            .iter()
+            .filter_map(|p| match p.default {
+                ParamDefault::Required(json) => Some((
+                    p.name.to_string(),
+                    serde_json::from_str(json).expect("example is valid JSON"),
+                )),
+                ParamDefault::Value(_) => None,
+            })
+            .collect();
+        Value::Object(params)
+    }
+
+    /// JSON Schema of the `parameters` object accepted by this template
+    pub fn parameters_schema(&self) -> Value {
+        let properties: Map<String, Value> = self
+            .params
+            .iter()
+            .map(|p| (p.name.to_string(), p.schema()))
+            .collect();
+        let required: Vec<&str> = self
+            .params
+            .iter()
+            .filter(|p| matches!(p.default, ParamDefault::Required(_)))
+            .map(|p| p.name)
+            .collect();
+
+        json!({
+            "type": "object",
+            "description": format!("Parameters of the `{}` template: {}", self.id, self.description),
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+}
+
+fn environment() -> &'static Environment<'static> {
+    static ENV: OnceLock<Environment<'static>> = OnceLock::new();
+    ENV.get_or_init(|| {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_keep_trailing_newline(true);
+        // A typo in a template must fail loudly rather than render blanks
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        for t in TEMPLATES {
+            env.add_template(t.id, t.source)
+                .expect("built-in template must parse");
+        }
+        env
+    })
+}
+
+// Render complete Cairo source for `template`.
+pub fn render(
+    template: &Template,
+    contract_name: &str,
+    parameters: Option<&Value>,
+) -> Result<String, ApiError> {
+    let mut ctx = resolve_params(template, parameters)?;
+    ctx.insert("module".into(), Value::String(module_name(contract_name)));
+    ctx.insert("template_id".into(), Value::String(template.id.into()));
+
+    environment()
+        .get_template(template.id)
+        .and_then(|t| t.render(TemplateValue::from_serialize(&ctx)))
+        .map_err(|e| {
+            tracing::error!("failed to render template {}: {}", template.id, e);
+            ApiError::Internal("failed to render template")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contract_types_resolve_through_aliases() {
+        assert_eq!(resolve("ERC20", None).unwrap().id, "erc20");
+        assert_eq!(resolve("token", None).unwrap().id, "erc20");
+        assert_eq!(resolve("erc-721", None).unwrap().id, "erc721");
+        assert_eq!(resolve("nft", Some("erc721")).unwrap().id, "erc721");
+        assert!(resolve("custom", None).is_err());
+        assert!(resolve("token", Some("erc721")).is_err());
+    }
+
+    #[test]
+    fn module_names_are_cairo_identifiers() {
+        assert_eq!(module_name("MyToken"), "MyToken");
+        assert_eq!(module_name("my token-v2"), "MyTokenV2");
+        assert_eq!(module_name("1st drop"), "C1stDrop");
+        assert_eq!(module_name("!!!"), "Contract");
+    }
+
+    #[test]
+    fn params_get_defaults_and_are_checked() {
+        let erc20 = find("erc20").unwrap();
+        let given = json!({"name": "Gold", "symbol": "GLD", "decimals": 6});
+        let params = resolve_params(erc20, Some(&given)).unwrap();
+        assert_eq!(params["name"], "Gold");
+        assert_eq!(params["decimals"], "6");
+        assert_eq!(params["mintable"], false);
+
+        let with = |key: &str, value: Value| {
+            let mut params = given.clone();
+            params[key] = value;
+            resolve_params(erc20, Some(&params))
+        };
+        assert!(with("decimals", json!(256)).is_err());
+        assert!(with("name", json!("a\"b")).is_err());
+        assert!(with("colour", json!("red")).is_err());
+    }
+
+    #[test]
+    fn required_params_must_be_given() {
+        let erc20 = find("erc20").unwrap();
+        let Err(ApiError::Validation(errors)) = resolve_params(erc20, None) else {
+            panic!("expected validation errors");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["parameters.name", "parameters.symbol"]);
+        assert!(errors.iter().all(|e| e.message == "is required"));
+
+        // The catalog example is enough for every template
+        for t in TEMPLATES {
+            assert!(
+                resolve_params(t, Some(&t.example_parameters())).is_ok(),
+                "{}",
+                t.id
+            );
+        }
+    }
+
+    #[test]
+    fn all_parameter_errors_are_reported() {
+        let erc20 = find("erc20").unwrap();
+        let params = json!({"decimals": 256, "symbol": 5, "colour": "red"});
+        let Err(ApiError::Validation(errors)) = resolve_params(erc20, Some(&params)) else {
+            panic!("expected validation errors");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "parameters.colour",
+                "parameters.name",
+                "parameters.symbol",
+                "parameters.decimals"
+            ]
+        );
+        assert_eq!(errors[1].message, "is required");
+        assert_eq!(errors[3].message, "must fit in u8");
+    }
+
+    #[test]
+    fn parameter_schemas_follow_specs() {
+        let schema = find("erc20").unwrap().parameters_schema();
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["properties"]["symbol"]["maxLength"], 16);
+        assert_eq!(schema["properties"]["decimals"]["default"], 18);
+        assert_eq!(schema["properties"]["decimals"]["anyOf"][0]["maximum"], 255);
+        assert_eq!(
+            schema["properties"]["initial_supply"]["anyOf"][1]["format"],
+            "u256"
+        );
+        assert_eq!(schema["properties"]["name"]["pattern"], SAFE_TEXT_PATTERN);
+        assert_eq!(schema["properties"]["name"]["examples"], json!(["Gold"]));
+        assert_eq!(schema["required"], json!(["name", "symbol"]));
+    }
+
+    #[test]
+    fn uint_bounds() {
+        let parse = |v: Value, bits| uint_digits(&v).filter(|d| fits_bits(d, bits));
+        assert_eq!(parse(json!("007"), 8).as_deref(), Some("7"));
+        assert_eq!(parse(json!(255), 8).as_deref(), Some("255"));
+        assert!(parse(json!(256), 8).is_none());
+        assert!(parse(json!(U256_MAX), 256).is_some());
+        assert!(parse(json!(format!("{}0", U256_MAX)), 256).is_none());
+        assert!(parse(json!("-1"), 64).is_none());
+    }
+
+    #[test]
+    fn vesting_cliff_cannot_exceed_duration() {
+        let vesting = find("vesting").unwrap();
+        let params = json!({"cliff_seconds": 100, "duration_seconds": 10});
+        assert!(resolve_params(vesting, Some(&params)).is_err());
+    }
+
+    #[test]
+    fn every_template_renders_with_its_example() {
+        for t in TEMPLATES {
+            let code = render(t, "Sample", Some(&t.example_parameters())).unwrap();
+            assert!(code.contains("#[starknet::contract]"), "{}", t.id);
+            assert!(code.contains("mod Sample {"), "{}", t.id);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, Felt, FunctionCall, StarknetError},
+        utils::cairo_short_string_to_felt,
+    },
+    macros::selector,
+    providers::{JsonRpcClient, Provider, ProviderError, Url, jsonrpc::HttpTransport},
+};
+
+use thiserror::Error;
+
+use crate::libs::{error::ApiError, siws};
+
+#[derive(Debug, Error)]
+pub enum VerifierConfigError {
+    #[error("invalid STARKNET_RPC_URL")]
+    InvalidRpcUrl,
+    #[error("unknown AUTH_SIGNATURE_VERIFIER {0:?} (expected `account` or `dev-ecdsa`)")]
+    UnknownMode(String),
+    #[error("invalid AUTH_DEV_PUBLIC_KEYS: {0}")]
+    DevKeys(String),
+}
+
+// What a verifier needs to decide whether `signature` was produced by `account`.
+#[derive(Debug, Clone)]
+pub struct SignatureCheck<'a> {
+    pub account: Felt,
+    pub message_hash: Felt,
+    pub signature: &'a [Felt],
+}
+
+#[async_trait]
+pub trait SignatureVerifier: Send + Sync {
+    async fn verify(&self, check: &SignatureCheck<'_>) -> Result<bool, ApiError>;
+}
+
+// Stands in when no node is configured: nothing can be verified, so every
+// sign-in is refused.
+pub struct UnavailableVerifier;
+
+#[async_trait]
+impl SignatureVerifier for UnavailableVerifier {
+    async fn verify(&self, _check: &SignatureCheck<'_>) -> Result<bool, ApiError> {
+        Err(ApiError::ServiceUnavailable(
+            "signature verification not configured",
+        ))
+    }
+}
+
+// Development only: plain STARK-curve ECDSA against public keys listed in the
+// server's own configuration, for working without a node. Nothing on chain
+// is consulted, so it must never face real users.
+pub struct DevEcdsaVerifier {
+    keys: HashMap<Felt, Felt>,
+}
+
+impl DevEcdsaVerifier {
+    /// `keys` maps account addresses to the public keys that sign for them
+    pub fn new(keys: HashMap<Felt, Felt>) -> Self {
+        Self { keys }
+    }
+}
+
+#[async_trait]
+impl SignatureVerifier for DevEcdsaVerifier {
+    async fn verify(&self, check: &SignatureCheck<'_>) -> Result<bool, ApiError> {
+        Ok(self.keys.get(&check.account).is_some_and(|public_key| {
+            siws::verify_ecdsa(public_key, &check.message_hash, check.signature)
+        }))
+    }
+}
+
+// Asks the account contract itself through `is_valid_signature`, which covers
+// Argent, Braavos, multisig and any other SNIP-6 account.
+pub struct AccountVerifier {
+    provider: JsonRpcClient<HttpTransport>,
+}
+
+impl AccountVerifier {
+    pub fn new(rpc_url: Url) -> Self {
+        Self {
+            provider: JsonRpcClient::new(HttpTransport::new(rpc_url)),
+        }
+    }
+
+    async fn call(
+        &self,
+        selector: Felt,
+        check: &SignatureCheck<'_>,
+    ) -> Result<Vec<Felt>, ProviderError> {
+        let mut calldata = vec![check.message_hash, Felt::from(check.signature.len())];
+        calldata.extend_from_slice(check.signature);
+
+        self.provider
+            .call(
+                FunctionCall {
+                    contract_address: check.account,
+                    entry_point_selector: selector,
+                    calldata,
+                },
+                BlockId::Tag(BlockTag::Latest),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl SignatureVerifier for AccountVerifier {
+    async fn verify(&self, check: &SignatureCheck<'_>) -> Result<bool, ApiError> {
+        // Older accounts only expose the camelCase entrypoint
+        let mut result = self.call(selector!("is_valid_signature"), check).await;
+        if let Err(ProviderError::StarknetError(StarknetError::ContractError(_))) = &result {
+            result = self.call(selector!("isValidSignature"), check).await;
+        }
+
+        match result {
+            Ok(ret) => Ok(is_valid_return(&ret)),
+            // Reverts (e.g. "argent/invalid-signature") and undeployed accounts
+            Err(ProviderError::StarknetError(
+                StarknetError::ContractError(_)
+                | StarknetError::ContractNotFound
+                | StarknetError::EntrypointNotFound,
+            )) => Ok(false),
+            Err(e) => {
+                tracing::error!("is_valid_signature call failed: {:?}", e);
+                Err(ApiError::ServiceUnavailable(
+                    "signature verification unavailable",
+                ))
+            }
+        }
+    }
+}
+
+// SNIP-6 accounts return 'VALID'; legacy Cairo 0 accounts return 1.
+fn is_valid_return(ret: &[Felt]) -> bool {
+    let valid = cairo_short_string_to_felt("VALID").expect("short string");
+    matches!(ret.first(), Some(v) if *v == valid || *v == Felt::ONE)
+}
+
+// `wallet=public_key` pairs, comma-separated.
+fn parse_dev_keys(raw: &str) -> Result<HashMap<Felt, Felt>, VerifierConfigError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (wallet, key) = pair.split_once('=').ok_or_else(|| {
+                VerifierConfigError::DevKeys(format!("{:?} is not wallet=key", pair))
+            })?;
+            match (siws::parse_felt(wallet), siws::parse_felt(key)) {
+                (Ok(wallet), Ok(key)) => Ok((wallet, key)),
+                _ => Err(VerifierConfigError::DevKeys(format!(
+                    "{:?} is not a felt pair",
+                    pair
+                ))),
+            }
+        })
+        .collect()
+}
+
+// Signatures are checked by the account contract through the node at
+// `STARKNET_RPC_URL`; without one sign-in is unavailable. A misconfiguration
+// is an error rather than a quiet downgrade. `AUTH_SIGNATURE_VERIFIER=dev-ecdsa`
+// opts into `DevEcdsaVerifier` with the keys in `AUTH_DEV_PUBLIC_KEYS`.
+pub fn verifier_from_env() -> Result<Arc<dyn SignatureVerifier>, VerifierConfigError> {
+    let mode = std::env::var("AUTH_SIGNATURE_VERIFIER").unwrap_or_default();
+    match mode.trim() {
+        "" | "account" => {
+            let rpc_url = std::env::var("STARKNET_RPC_URL").unwrap_or_default();
+            if rpc_url.trim().is_empty() {
+                tracing::warn!("STARKNET_RPC_URL not set, sign-in is unavailable");
+                return Ok(Arc::new(UnavailableVerifier));
+            }
+            let url = Url::parse(rpc_url.trim()).map_err(|_| VerifierConfigError::InvalidRpcUrl)?;
+            Ok(Arc::new(AccountVerifier::new(url)))
+        }
+        "dev-ecdsa" => {
+            let keys = parse_dev_keys(&std::env::var("AUTH_DEV_PUBLIC_KEYS").unwrap_or_default())?;
+            if keys.is_empty() {
+                return Err(VerifierConfigError::DevKeys("no keys listed".to_string()));
+            }
+            tracing::warn!(
+                "Sign-in uses development ECDSA verification for {} wallet(s); do not expose this server",
+                keys.len()
+            );
+            Ok(Arc::new(DevEcdsaVerifier::new(keys)))
+        }
+        other => Err(VerifierConfigError::UnknownMode(other.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_return_values() {
+        let valid = cairo_short_string_to_felt("VALID").unwrap();
+        assert!(is_valid_return(&[valid]));
+        assert!(is_valid_return(&[Felt::ONE]));
+        assert!(!is_valid_return(&[Felt::ZERO]));
+        assert!(!is_valid_return(&[]));
+    }
+
+    #[test]
+    fn parses_dev_keys() {
+        let keys = parse_dev_keys("0x1=0xa, 0x02=0xB,").unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[&Felt::TWO], Felt::from(0xb_u64));
+        assert!(parse_dev_keys("0x1").is_err());
+        assert!(parse_dev_keys("0x1=key").is_err());
+    }
+}
@@ -10,3 +10,12 @@ pub fn normalize_and_validate(wallet: &str) -> Result<String, ApiError> {
     let norm = normalize_address(felt);
     Ok(format!("{:#x}", norm))
 }
+
+// Class and transaction hashes as 0x-prefixed lowercase hex; unlike
+// addresses they are not reduced. `None` unless `value` is a non-zero felt.
+pub fn normalize_hash(value: &str) -> Option<String> {
+    Felt::from_str(value.trim())
+        .ok()
+        .filter(|f| *f != Felt::ZERO)
+        .map(|f| format!("{:#x}", f))
+}
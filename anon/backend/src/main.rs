@@ -4,10 +4,10 @@ use axum::{
     Router,
     http::{
         Method, StatusCode,
-        header::{AUTHORIZATION, CONTENT_TYPE, LOCATION},
+        header::{AUTHORIZATION, CONTENT_TYPE, ETAG, HeaderName, IF_NONE_MATCH, LOCATION},
     },
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 use tokio::net::TcpListener;
 use tower_http::{
@@ -37,10 +37,41 @@ async fn main() {
         .await
         .expect("migrations failed");
 
+    let state = match libs::db::AppState::new(pool.clone()) {
+        Ok(state) => state,
+        Err(e) => {
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    libs::jobs::spawn_workers(&state);
+
     let cors = CorsLayer::new()
         .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([CONTENT_TYPE, AUTHORIZATION]);
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            CONTENT_TYPE,
+            AUTHORIZATION,
+            IF_NONE_MATCH,
+            HeaderName::from_static(libs::api_keys::API_KEY_HEADER),
+            HeaderName::from_static(libs::idempotency::IDEMPOTENCY_KEY_HEADER),
+        ])
+        .expose_headers([
+            ETAG,
+            HeaderName::from_static(libs::idempotency::REPLAYED_HEADER),
+        ]);
+
+    // Replays retried requests sent with an Idempotency-Key
+    let idempotent = || {
+        axum::middleware::from_fn_with_state(state.clone(), middlewares::idempotency::idempotency)
+    };
 
     // Router
     let app = Router::new()
@@ -48,13 +79,98 @@ async fn main() {
         .route("/health", get(routes::health::health))
         .route("/healthz", get(routes::health::healthz))
         .route("/db/health", get(routes::health::db_health))
-        .route("/register", post(routes::register::register))
+        .route("/auth/nonce", post(routes::auth::nonce))
+        .route("/auth/verify", post(routes::auth::verify))
+        .route("/auth/refresh", post(routes::auth::refresh))
+        .route("/auth/logout", post(routes::auth::logout))
+        .route("/.well-known/jwks.json", get(routes::auth::jwks))
+        .route(
+            "/register",
+            post(routes::register::register).layer(idempotent()),
+        )
         .route("/user", get(routes::user::me))
-        .route("/generate", post(routes::generate::generate_contract))
+        .route(
+            "/api_keys",
+            post(routes::api_keys::create_api_key).get(routes::api_keys::list_api_keys),
+        )
+        .route("/api_keys/{id}", delete(routes::api_keys::revoke_api_key))
+        .nest("/admin", routes::admin::router())
+        .route("/templates", get(routes::templates::list_templates))
+        .route("/templates/{id}", get(routes::templates::get_template))
+        .route(
+            "/generate",
+            post(routes::generate::generate_contract).layer(idempotent()),
+        )
         .route(
             "/generated_contracts",
             get(routes::generate::list_generated_contracts),
         )
+        .route(
+            "/generated_contracts/{id}",
+            get(routes::generate::get_generated_contract)
+                .patch(routes::generate::update_generated_contract)
+                .delete(routes::generate::delete_contract),
+        )
+        .route(
+            "/generated_contracts/{id}/archive",
+            get(routes::generate::download_archive),
+        )
+        .route(
+            "/generated_contracts/{id}/compile",
+            post(routes::compile::compile_contract),
+        )
+        .route(
+            "/generated_contracts/{id}/compilation",
+            get(routes::compile::get_compilation),
+        )
+        .route(
+            "/generated_contracts/{id}/compilation/{artifact}",
+            get(routes::compile::get_artifact),
+        )
+        .route(
+            "/generated_contracts/{id}/audit",
+            post(routes::audit::audit_contract),
+        )
+        .route("/generated_contracts/{id}/abi", get(routes::abi::get_abi))
+        .route(
+            "/generated_contracts/{id}/calldata",
+            post(routes::abi::encode_calldata),
+        )
+        .route(
+            "/generated_contracts/{id}/deployments",
+            post(routes::deployments::record_deployment),
+        )
+        .route(
+            "/generated_contracts/{id}/deploy",
+            post(routes::deployments::deploy_contract).layer(idempotent()),
+        )
+        .route("/deployments", get(routes::deployments::list_deployments))
+        .route(
+            "/deployments/{id}",
+            get(routes::deployments::get_deployment),
+        )
+        .route(
+            "/generated_contracts/{id}/code",
+            put(routes::revisions::update_code),
+        )
+        .route(
+            "/generated_contracts/{id}/regenerate",
+            post(routes::revisions::regenerate_contract).layer(idempotent()),
+        )
+        .route(
+            "/generated_contracts/{id}/revisions",
+            get(routes::revisions::list_revisions),
+        )
+        .route(
+            "/generated_contracts/{id}/revisions/{revision}",
+            get(routes::revisions::get_revision),
+        )
+        .route(
+            "/generated_contracts/{id}/diff",
+            get(routes::revisions::diff_revisions),
+        )
+        .route("/jobs/{id}", get(routes::jobs::get_job))
+        .route("/jobs/{id}/events", get(routes::jobs::job_events))
         .route("/reviews", get(routes::reviews::list_reviews))
         // Swagger UI at /docs and OpenAPI JSON at /api-docs/openapi.json
         .merge(SwaggerUi::new("/docs").url(
@@ -69,7 +185,7 @@ async fn main() {
             TraceLayer::new_for_http().make_span_with(DefaultMakeSpan::new().include_headers(true)),
         )
         .layer(cors)
-        .with_state(libs::db::AppState { pool: pool.clone() });
+        .with_state(state);
 
     let addr = cfg.addr();
     let listener = TcpListener::bind(&addr).await.expect("bind failed");
@@ -1,26 +1,82 @@
+use std::marker::PhantomData;
+
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts},
     http::{header, request::Parts},
 };
 
-use crate::libs::{error::ApiError, jwt, wallet};
+use crate::libs::{
+    api_keys::{self, API_KEY_HEADER, Scope},
+    db::AppState,
+    error::ApiError,
+    jwt::{self, Claims},
+    roles::Role,
+    session, wallet,
+};
+
+// How the caller authenticated.
+#[derive(Clone, Debug)]
+pub enum Credential {
+    Jwt(Claims),
+    ApiKey(i64),
+}
 
 #[derive(Clone, Debug)]
 pub struct AuthUser {
     pub wallet: String,
+    pub credential: Credential,
+    /// Effective scopes: everything for a wallet session, the granted subset
+    /// for an API key
+    pub scopes: Vec<Scope>,
+    pub roles: Vec<Role>,
+}
+
+impl AuthUser {
+    pub fn require_scope(&self, scope: Scope) -> Result<(), ApiError> {
+        if self.scopes.contains(&scope) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden("missing required scope"))
+        }
+    }
+
+    // Session-only operations (logout, key management) need the JWT claims.
+    pub fn claims(&self) -> Result<&Claims, ApiError> {
+        match &self.credential {
+            Credential::Jwt(claims) => Ok(claims),
+            Credential::ApiKey(_) => Err(ApiError::Forbidden("not allowed with an api key")),
+        }
+    }
 }
 
 impl<S> FromRequestParts<S> for AuthUser
 where
+    AppState: FromRef<S>,
     S: Send + Sync,
 {
     type Rejection = ApiError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let hdr = parts
-            .headers
-            .get(header::AUTHORIZATION)
-            .ok_or(ApiError::Unauthorized("missing authorization"))?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AppState { pool, keys, .. } = AppState::from_ref(state);
+
+        let Some(hdr) = parts.headers.get(header::AUTHORIZATION) else {
+            // Programmatic clients authenticate with an API key instead
+            let key = parts
+                .headers
+                .get(API_KEY_HEADER)
+                .ok_or(ApiError::Unauthorized("missing authorization"))?
+                .to_str()
+                .map_err(|_| ApiError::Unauthorized("invalid api key"))?;
+            let identity = api_keys::authenticate(&pool, key.trim()).await?;
+            return Ok(AuthUser {
+                wallet: identity.wallet,
+                // API keys never carry roles
+                roles: Vec::new(),
+                credential: Credential::ApiKey(identity.key_id),
+                scopes: identity.scopes,
+            });
+        };
+
         let raw = hdr
             .to_str()
             .map_err(|_| ApiError::Unauthorized("invalid authorization"))?;
@@ -30,15 +86,55 @@ where
             .ok_or(ApiError::Unauthorized("invalid scheme"))?;
 
         // Decode JWT to get the wallet address
-        let secret = jwt::secret_from_env();
         let claims =
-            jwt::decode(token, &secret).map_err(|_| ApiError::Unauthorized("invalid token"))?;
+            jwt::decode(token, &keys).map_err(|_| ApiError::Unauthorized("invalid token"))?;
 
         // Normalize and validate the wallet from claims
         let wallet = wallet::normalize_and_validate(&claims.sub)
             .map_err(|_| ApiError::Unauthorized("invalid token"))?;
 
-        Ok(AuthUser { wallet })
+        // Reject tokens killed through /auth/logout, or whose wallet was
+        // disabled, before they expire. Roles are looked up on every request
+        // so that revoking one takes effect right away.
+        let roles = session::check_access(&pool, &claims).await?;
+
+        Ok(AuthUser {
+            wallet,
+            roles,
+            credential: Credential::Jwt(claims),
+            scopes: Scope::ALL.to_vec(),
+        })
+    }
+}
+
+// Marker types naming the role a route requires.
+pub trait RoleMarker: Send + Sync {
+    const ROLE: Role;
+}
+
+pub struct Admin;
+
+impl RoleMarker for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+// `AuthUser` that currently holds role `R`.
+pub struct RequireRole<R: RoleMarker>(pub AuthUser, pub PhantomData<R>);
+
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+    R: RoleMarker,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        if !user.roles.contains(&R::ROLE) {
+            return Err(ApiError::Forbidden("insufficient role"));
+        }
+        Ok(RequireRole(user, PhantomData))
     }
 }
 
@@ -47,30 +143,35 @@ mod tests {
     use super::*;
     use axum::http::Request;
 
-    #[tokio::test]
-    async fn extractor_ok_with_valid_bearer_jwt() {
-        unsafe { std::env::set_var("JWT_SECRET", "test-secret") };
-        let wallet = "0x01"; // valid felt, will normalize
-        let token = crate::libs::jwt::encode(wallet, &crate::libs::jwt::secret_from_env()).unwrap();
-
-        let req = Request::builder()
-            .uri("/")
-            .header(header::AUTHORIZATION, format!("Bearer {}", token))
-            .body(())
-            .unwrap();
-        let (mut parts, _) = req.into_parts();
-
-        let extracted = AuthUser::from_request_parts(&mut parts, &()).await;
-        assert!(extracted.is_ok());
-        let user = extracted.unwrap();
-        assert!(user.wallet.starts_with("0x"));
+    // Never connects: these tests fail before the database is consulted
+    fn test_state() -> AppState {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgresql://localhost/unused")
+            .expect("connect_lazy should succeed");
+        AppState::new(pool).unwrap()
     }
 
     #[tokio::test]
     async fn extractor_err_without_header() {
         let req = Request::builder().uri("/").body(()).unwrap();
         let (mut parts, _) = req.into_parts();
-        let extracted = AuthUser::from_request_parts(&mut parts, &()).await;
+        let extracted = AuthUser::from_request_parts(&mut parts, &test_state()).await;
         assert!(matches!(extracted, Err(ApiError::Unauthorized(_))));
     }
+
+    #[test]
+    fn api_key_scopes_are_enforced() {
+        let user = AuthUser {
+            wallet: "0x1".to_string(),
+            credential: Credential::ApiKey(1),
+            scopes: vec![Scope::ContractsRead],
+            roles: Vec::new(),
+        };
+        assert!(user.require_scope(Scope::ContractsRead).is_ok());
+        assert!(matches!(
+            user.require_scope(Scope::ContractsWrite),
+            Err(ApiError::Forbidden(_))
+        ));
+        assert!(user.claims().is_err());
+    }
 }
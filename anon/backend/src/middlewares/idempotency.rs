@@ -0,0 +1,140 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::libs::{
+    db::AppState,
+    error::ApiError,
+    idempotency::{
+        self, Claim, Held, IDEMPOTENCY_KEY_HEADER, REPLAYED_HEADER, StoredResponse, is_valid_key,
+    },
+};
+use crate::middlewares::auth::AuthUser;
+
+// Same as axum's default body limit for extractors
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+// Set per request by the request-id layer; a replay gets its own
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Answers about the moment rather than the request, worth retrying: server
+// errors, conflicts and rate limits.
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::CONFLICT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Honors an `Idempotency-Key` header on the routes it is layered on.
+///
+/// The first request with a key runs and its response is stored for the
+/// caller; retries with the same key and body get the stored response back
+/// with `Idempotent-Replayed: true`, retries with another body a 422, and
+/// retries while the first request is still running a 409. 5xx, 409 and 429
+/// responses are not stored, so those requests can be retried. Requests without the header
+/// pass through. Layer it on a route with
+/// `axum::middleware::from_fn_with_state`.
+pub async fn idempotency(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    match run(state, req, next).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn run(state: AppState, req: Request, next: Next) -> Result<Response, ApiError> {
+    let Some(key) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(next.run(req).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|k| is_valid_key(k))
+        .ok_or(ApiError::BadRequest("invalid idempotency key"))?
+        .to_string();
+
+    // Keys belong to the caller, so one user cannot replay another's response
+    let (mut parts, body) = req.into_parts();
+    let wallet = AuthUser::from_request_parts(&mut parts, &state)
+        .await?
+        .wallet;
+    let body = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| ApiError::BadRequest("request body too large"))?;
+    let hash = idempotency::request_hash(parts.method.as_str(), &parts.uri.to_string(), &body);
+
+    let pool = &state.pool;
+    let claim = idempotency::claim(pool, &wallet, &key, &hash, state.idempotency_ttl)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    let held = match claim {
+        Claim::New(held) => held,
+        Claim::Replay(stored) => return Ok(replay(stored)),
+        Claim::InProgress => {
+            return Err(ApiError::Conflict(
+                "a request with this idempotency key is in progress",
+            ));
+        }
+        Claim::Mismatch => {
+            return Err(ApiError::Unprocessable(
+                "idempotency key was used for a different request",
+            ));
+        }
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to read response for idempotency key: {:?}", e);
+            release(pool, &wallet, &key, held).await;
+            return Err(ApiError::Internal("failed to read response"));
+        }
+    };
+
+    if is_retryable(parts.status) {
+        release(pool, &wallet, &key, held).await;
+    } else {
+        let stored = StoredResponse {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .filter(|(name, _)| name.as_str() != REQUEST_ID_HEADER)
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: body.to_vec(),
+        };
+        if let Err(e) = idempotency::complete(pool, &wallet, &key, held, &stored).await {
+            // The request went through; only retries lose their replay
+            tracing::error!("Failed to store response for idempotency key: {:?}", e);
+            release(pool, &wallet, &key, held).await;
+        }
+    }
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+async fn release(pool: &sqlx::PgPool, wallet: &str, key: &str, held: Held) {
+    if let Err(e) = idempotency::release(pool, wallet, key, held).await {
+        tracing::error!("Failed to release idempotency key: {:?}", e);
+    }
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let headers = response.headers_mut();
+    for (name, value) in stored.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            headers.append(name, value);
+        }
+    }
+    headers.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
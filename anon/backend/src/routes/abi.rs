@@ -0,0 +1,185 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::libs::{
+    abi::{self, SourceAbi, StorageVar},
+    api_keys::Scope,
+    calldata,
+    db::AppState,
+    error::ApiError,
+};
+use crate::middlewares::auth::AuthUser;
+use crate::routes::generate::has_code;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AbiRes {
+    pub contract_id: i64,
+    /// `sierra` when taken from a build of the current code, `source` when
+    /// extracted from the code itself
+    pub source: String,
+    /// Starknet ABI entries
+    #[schema(value_type = Vec<Object>)]
+    pub abi: Vec<Value>,
+    /// Storage variables, from the code either way
+    pub storage: Vec<StorageVar>,
+}
+
+// contract_name, scarb_config, generated_code, status, abi
+type AbiSourceRow = (String, Option<String>, String, String, Option<Value>);
+
+/// ABI of a generated contract
+///
+/// Interfaces, entry points, events and the types they use, in the Starknet
+/// ABI format. When the current code has been compiled the Sierra class's
+/// ABI is served; otherwise it is extracted from the Cairo source, which
+/// does not see into impls embedded from components.
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}/abi",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 200, description = "Contract ABI", body = AbiRes),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued or failed", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_abi(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<AbiRes>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    load(&pool, id, &auth.wallet).await.map(Json)
+}
+
+// ABI of contract `id` of `wallet`: the Sierra class's when the current code
+// has been built, the one extracted from the source otherwise.
+async fn load(pool: &PgPool, id: i64, wallet: &str) -> Result<AbiRes, ApiError> {
+    let (contract_name, scarb_config, code, status, stored): AbiSourceRow = sqlx::query_as(
+        r#"SELECT g.contract_name, g.scarb_config, g.generated_code, g.status, g.abi
+           FROM generated_contracts g
+           JOIN users u ON u.id = g.user_id
+           WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(wallet)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if !has_code(&status) {
+        return Err(ApiError::Conflict("contract has not been generated"));
+    }
+
+    // Contracts generated before ABIs were stored, or renamed since
+    let source_abi = match stored.and_then(|v| serde_json::from_value::<SourceAbi>(v).ok()) {
+        Some(source_abi) => source_abi,
+        None => {
+            let source_abi = abi::of_contract(&contract_name, scarb_config.as_deref(), &code);
+            let value = serde_json::to_value(&source_abi).expect("ABI serializes");
+            sqlx::query("UPDATE generated_contracts SET abi = $2 WHERE id = $1 AND abi IS NULL")
+                .bind(id)
+                .bind(&value)
+                .execute(pool)
+                .await
+                .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+            source_abi
+        }
+    };
+
+    // A successful build of the latest revision
+    let sierra: Option<(Value,)> = sqlx::query_as(
+        r#"SELECT c.sierra
+           FROM contract_compilations c
+           WHERE c.contract_id = $1 AND c.success AND c.sierra IS NOT NULL
+             AND c.revision IS NOT DISTINCT FROM
+                 (SELECT MAX(r.revision) FROM contract_revisions r WHERE r.contract_id = $1)
+           ORDER BY c.id DESC
+           LIMIT 1"#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    let (source, entries) = match sierra.and_then(|(class,)| abi::sierra_abi(&class)) {
+        Some(entries) => ("sierra", entries),
+        None => ("source", source_abi.abi),
+    };
+    Ok(AbiRes {
+        contract_id: id,
+        source: source.to_string(),
+        abi: entries,
+        storage: source_abi.storage,
+    })
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CalldataReq {
+    /// Constructor arguments by name
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub args: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CalldataRes {
+    pub contract_id: i64,
+    /// ABI the constructor was read from: `sierra` or `source`
+    pub abi_source: String,
+    /// Felts, 0x-prefixed hex
+    pub calldata: Vec<String>,
+}
+
+/// Encode constructor calldata
+///
+/// Serializes constructor arguments given as JSON into felts, following the
+/// constructor in the contract's ABI: integers and addresses as numbers or
+/// decimal / `0x` strings, `ByteArray` as a string, u256 as its low and high
+/// halves, arrays, tuples, structs as objects and enum variants as
+/// `"Variant"` or `{"Variant": value}`. Every offending argument is listed.
+#[utoipa::path(
+    post,
+    path = "/generated_contracts/{id}/calldata",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    request_body = CalldataReq,
+    responses(
+        (status = 200, description = "Constructor calldata", body = CalldataRes),
+        (status = 400, description = "Invalid arguments, with `details` per argument", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued or failed", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn encode_calldata(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<CalldataReq>,
+) -> Result<Json<CalldataRes>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+
+    let abi = load(&pool, id, &auth.wallet).await?;
+    let calldata =
+        calldata::encode_constructor(&abi.abi, &req.args, "args").map_err(ApiError::Validation)?;
+    Ok(Json(CalldataRes {
+        contract_id: id,
+        abi_source: abi.source,
+        calldata: calldata.iter().map(|f| format!("{:#x}", f)).collect(),
+    }))
+}
@@ -0,0 +1,275 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::libs::{
+    db::AppState,
+    error::{ApiError, map_sqlx_error},
+    pagination::{decode_id_cursor, encode_id_cursor},
+    roles::{self, Role},
+    session,
+};
+use crate::middlewares::auth::{Admin, RequireRole};
+
+// Routes mounted under `/admin`; every handler requires the admin role.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/users", get(list_users))
+        .route("/users/{id}/disable", post(disable_user))
+        .route("/users/{id}/enable", post(enable_user))
+        .route("/reviews/{id}", delete(delete_review))
+        .route(
+            "/generated_contracts/{id}",
+            delete(delete_generated_contract),
+        )
+}
+
+#[derive(Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct AdminUsersQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdminUserItem {
+    pub id: i64,
+    pub wallet: String,
+    pub roles: Vec<Role>,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdminUsersListRes {
+    pub items: Vec<AdminUserItem>,
+    pub next_cursor: Option<String>,
+}
+
+// id, wallet, roles, disabled_at, created_at
+type AdminUserRow = (
+    i64,
+    String,
+    Vec<String>,
+    Option<DateTime<Utc>>,
+    DateTime<Utc>,
+);
+
+fn to_item((id, wallet, roles, disabled_at, created_at): AdminUserRow) -> AdminUserItem {
+    AdminUserItem {
+        id,
+        wallet,
+        roles: roles::roles_from_db(&roles),
+        disabled_at,
+        created_at,
+    }
+}
+
+/// List users
+#[utoipa::path(
+    get,
+    path = "/admin/users",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(AdminUsersQuery),
+    responses(
+        (status = 200, description = "Users, newest first", body = AdminUsersListRes),
+        (status = 400, description = "Invalid cursor", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Admin role required", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn list_users(
+    State(AppState { pool, .. }): State<AppState>,
+    _admin: RequireRole<Admin>,
+    Query(q): Query<AdminUsersQuery>,
+) -> Result<Json<AdminUsersListRes>, ApiError> {
+    let limit = q.limit.unwrap_or(20).clamp(1, 100);
+    let before = match q.cursor.as_deref() {
+        Some(s) => Some(decode_id_cursor(s).ok_or(ApiError::BadRequest("invalid cursor"))?),
+        None => None,
+    };
+
+    let rows: Vec<AdminUserRow> = sqlx::query_as(
+        r#"SELECT id, wallet, roles, disabled_at, created_at
+           FROM users
+           WHERE $1::BIGINT IS NULL OR id < $1
+           ORDER BY id DESC
+           LIMIT $2"#,
+    )
+    .bind(before)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    let items: Vec<AdminUserItem> = rows.into_iter().map(to_item).collect();
+    let next_cursor = if items.len() as i64 == limit {
+        items.last().map(|last| encode_id_cursor(last.id))
+    } else {
+        None
+    };
+
+    Ok(Json(AdminUsersListRes { items, next_cursor }))
+}
+
+async fn set_disabled(
+    pool: &sqlx::PgPool,
+    id: i64,
+    disabled: bool,
+) -> Result<AdminUserItem, ApiError> {
+    let rec: Option<AdminUserRow> = sqlx::query_as(
+        r#"UPDATE users
+           SET disabled_at = CASE WHEN $2 THEN COALESCE(disabled_at, NOW()) END,
+               updated_at = NOW()
+           WHERE id = $1
+           RETURNING id, wallet, roles, disabled_at, created_at"#,
+    )
+    .bind(id)
+    .bind(disabled)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    rec.map(to_item).ok_or(ApiError::NotFound("user not found"))
+}
+
+/// Disable a user
+///
+/// Disabled users are rejected on every authenticated request and their
+/// refresh tokens are revoked.
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/disable",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User disabled", body = AdminUserItem),
+        (status = 400, description = "Cannot disable yourself", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Admin role required", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "User not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn disable_user(
+    State(AppState { pool, .. }): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    Path(id): Path<i64>,
+) -> Result<Json<AdminUserItem>, ApiError> {
+    let (is_self,): (bool,) =
+        sqlx::query_as("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1 AND wallet = $2)")
+            .bind(id)
+            .bind(&admin.wallet)
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| map_sqlx_error(&e))?;
+    if is_self {
+        return Err(ApiError::BadRequest("cannot disable yourself"));
+    }
+
+    let user = set_disabled(&pool, id, true).await?;
+    session::revoke_all(&pool, &user.wallet).await?;
+
+    tracing::info!("Admin {} disabled user {}", admin.wallet, id);
+    Ok(Json(user))
+}
+
+/// Re-enable a disabled user
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/enable",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User enabled", body = AdminUserItem),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Admin role required", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "User not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn enable_user(
+    State(AppState { pool, .. }): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    Path(id): Path<i64>,
+) -> Result<Json<AdminUserItem>, ApiError> {
+    let user = set_disabled(&pool, id, false).await?;
+
+    tracing::info!("Admin {} enabled user {}", admin.wallet, id);
+    Ok(Json(user))
+}
+
+/// Delete a review
+#[utoipa::path(
+    delete,
+    path = "/admin/reviews/{id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "Review id")),
+    responses(
+        (status = 204, description = "Review deleted"),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Admin role required", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Review not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn delete_review(
+    State(AppState { pool, .. }): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let result = sqlx::query("DELETE FROM reviews WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| map_sqlx_error(&e))?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("review not found"));
+    }
+
+    tracing::info!("Admin {} deleted review {}", admin.wallet, id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete a generated contract
+#[utoipa::path(
+    delete,
+    path = "/admin/generated_contracts/{id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 204, description = "Contract deleted"),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Admin role required", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn delete_generated_contract(
+    State(AppState { pool, .. }): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let result = sqlx::query("DELETE FROM generated_contracts WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| map_sqlx_error(&e))?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("contract not found"));
+    }
+
+    tracing::info!("Admin {} deleted generated contract {}", admin.wallet, id);
+    Ok(StatusCode::NO_CONTENT)
+}
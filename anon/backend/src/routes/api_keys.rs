@@ -0,0 +1,263 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::libs::{
+    api_keys::{self, Scope},
+    db::AppState,
+    error::{ApiError, map_sqlx_error},
+    session::hash_token,
+};
+use crate::middlewares::auth::AuthUser;
+
+// Active keys a single user may hold at once
+const MAX_ACTIVE_KEYS: i64 = 20;
+
+// id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at
+type ApiKeyItemRow = (
+    i64,
+    String,
+    String,
+    Vec<String>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+    DateTime<Utc>,
+);
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateApiKeyReq {
+    pub name: String,
+    /// Any of `contracts:read`, `contracts:write`, `reviews:write`
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateApiKeyRes {
+    pub id: i64,
+    pub name: String,
+    pub prefix: String,
+    /// The full key. It is only ever returned here; store it safely.
+    pub key: String,
+    pub scopes: Vec<Scope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiKeyItem {
+    pub id: i64,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<Scope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiKeysListRes {
+    pub items: Vec<ApiKeyItem>,
+}
+
+async fn user_id_for(pool: &sqlx::PgPool, wallet: &str) -> Result<i64, ApiError> {
+    let (id,): (i64,) = sqlx::query_as("SELECT id FROM users WHERE wallet = $1")
+        .bind(wallet)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| map_sqlx_error(&e))?
+        .ok_or(ApiError::NotFound("user not found"))?;
+    Ok(id)
+}
+
+/// Create an API key
+#[utoipa::path(
+    post,
+    path = "/api_keys",
+    tag = "api_keys",
+    security(("bearer_auth" = [])),
+    request_body = CreateApiKeyReq,
+    responses(
+        (status = 201, description = "API key created", body = CreateApiKeyRes),
+        (status = 400, description = "Invalid request", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Called with an API key", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "User not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Too many active keys", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn create_api_key(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<CreateApiKeyReq>,
+) -> Result<impl IntoResponse, ApiError> {
+    // A leaked key must not be able to mint more keys
+    auth.claims()?;
+
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name is required"));
+    }
+    if name.len() > 100 {
+        return Err(ApiError::BadRequest(
+            "name must be less than 100 characters",
+        ));
+    }
+    if req.scopes.is_empty() {
+        return Err(ApiError::BadRequest("at least one scope is required"));
+    }
+    let mut scopes = Vec::new();
+    for s in &req.scopes {
+        let scope = Scope::parse(s.trim()).ok_or(ApiError::BadRequest("unknown scope"))?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    if req.expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(ApiError::BadRequest("expires_at must be in the future"));
+    }
+
+    let user_id = user_id_for(&pool, &auth.wallet).await?;
+
+    let (active,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM api_keys WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| map_sqlx_error(&e))?;
+    if active >= MAX_ACTIVE_KEYS {
+        return Err(ApiError::Conflict("too many active api keys"));
+    }
+
+    let (prefix, key) = api_keys::generate();
+    let scope_names: Vec<&str> = scopes.iter().map(|s| s.as_str()).collect();
+
+    let (id, created_at): (i64, DateTime<Utc>) = sqlx::query_as(
+        r#"INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           RETURNING id, created_at"#,
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(&prefix)
+    .bind(hash_token(&key))
+    .bind(&scope_names)
+    .bind(req.expires_at)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    tracing::info!("Created api key {} for user {}", prefix, user_id);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateApiKeyRes {
+            id,
+            name: name.to_string(),
+            prefix,
+            key,
+            scopes,
+            expires_at: req.expires_at,
+            created_at,
+        }),
+    ))
+}
+
+/// List the caller's API keys
+#[utoipa::path(
+    get,
+    path = "/api_keys",
+    tag = "api_keys",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "API keys of the current user", body = ApiKeysListRes),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Called with an API key", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn list_api_keys(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiKeysListRes>, ApiError> {
+    auth.claims()?;
+
+    let rows: Vec<ApiKeyItemRow> = sqlx::query_as(
+        r#"SELECT k.id, k.name, k.prefix, k.scopes, k.expires_at, k.last_used_at, k.revoked_at, k.created_at
+           FROM api_keys k
+           JOIN users u ON u.id = k.user_id
+           WHERE u.wallet = $1
+           ORDER BY k.created_at DESC, k.id DESC"#,
+    )
+    .bind(&auth.wallet)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    let items = rows
+        .into_iter()
+        .map(
+            |(id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at)| {
+                ApiKeyItem {
+                    id,
+                    name,
+                    prefix,
+                    scopes: api_keys::scopes_from_db(&scopes),
+                    expires_at,
+                    last_used_at,
+                    revoked_at,
+                    created_at,
+                }
+            },
+        )
+        .collect();
+
+    Ok(Json(ApiKeysListRes { items }))
+}
+
+/// Revoke an API key
+#[utoipa::path(
+    delete,
+    path = "/api_keys/{id}",
+    tag = "api_keys",
+    security(("bearer_auth" = [])),
+    params(("id" = i64, Path, description = "API key id")),
+    responses(
+        (status = 204, description = "API key revoked"),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Called with an API key", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "API key not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn revoke_api_key(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    auth.claims()?;
+
+    // Revoking twice is a no-op; keys of other users look like missing keys
+    let rec: Option<(i64,)> = sqlx::query_as(
+        r#"UPDATE api_keys SET revoked_at = COALESCE(revoked_at, NOW())
+           WHERE id = $1 AND user_id = (SELECT id FROM users WHERE wallet = $2)
+           RETURNING id"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| map_sqlx_error(&e))?;
+
+    rec.ok_or(ApiError::NotFound("api key not found"))?;
+    Ok(StatusCode::NO_CONTENT)
+}
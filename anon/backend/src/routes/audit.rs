@@ -0,0 +1,78 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::libs::{
+    api_keys::Scope,
+    audit::{self, AuditReport},
+    db::AppState,
+    error::ApiError,
+};
+use crate::middlewares::auth::AuthUser;
+use crate::routes::generate::has_code;
+
+/// Audit a generated contract
+///
+/// Runs static checks over the generated Cairo code: missing caller checks
+/// on privileged externals, unprotected `upgrade`, felt252 arithmetic,
+/// state changes without events, storage writes in view functions and
+/// leftover TODOs. Findings replace those of the previous audit.
+#[utoipa::path(
+    post,
+    path = "/generated_contracts/{id}/audit",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 200, description = "Findings, most severe first", body = AuditReport),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:write scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued or failed", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn audit_contract(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<AuditReport>, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+
+    let (code, status): (String, String) = sqlx::query_as(
+        r#"SELECT g.generated_code, g.status
+           FROM generated_contracts g
+           JOIN users u ON u.id = g.user_id
+           WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if !has_code(&status) {
+        return Err(ApiError::Conflict("contract has not been generated"));
+    }
+
+    let findings = audit::analyze(&code);
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    let report = audit::record(&mut tx, id, findings).await.map_err(|e| {
+        tracing::error!("Failed to store audit of contract {}: {:?}", id, e);
+        crate::libs::error::map_sqlx_error(&e)
+    })?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    tracing::info!(
+        "Audited contract {}: {} findings",
+        id,
+        report.findings.len()
+    );
+    Ok(Json(report))
+}
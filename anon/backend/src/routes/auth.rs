@@ -0,0 +1,284 @@
+use axum::{
+    Json,
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::libs::{
+    db::AppState,
+    error::ApiError,
+    session::{self, TokenPair},
+    siws::{self, SignInMessage},
+    verifier::SignatureCheck,
+    wallet,
+};
+use crate::middlewares::auth::AuthUser;
+
+#[derive(Deserialize, ToSchema)]
+pub struct NonceReq {
+    pub wallet: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct NonceRes {
+    pub wallet: String,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// SNIP-12 typed data to be signed by the wallet
+    #[schema(value_type = Object)]
+    pub typed_data: serde_json::Value,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyReq {
+    pub wallet: String,
+    pub nonce: String,
+    /// Signature felts as returned by the wallet (`[r, s]` for most
+    /// accounts); checked by the account contract itself
+    pub signature: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VerifyRes {
+    /// Short-lived access token
+    pub token: String,
+    pub token_type: String,
+    /// Access token lifetime in seconds
+    pub expires_in: u64,
+    /// Single-use token for `/auth/refresh`
+    pub refresh_token: String,
+    pub wallet: String,
+}
+
+impl VerifyRes {
+    fn new(wallet: String, pair: TokenPair) -> Self {
+        Self {
+            token: pair.access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: pair.expires_in,
+            refresh_token: pair.refresh_token,
+            wallet,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshReq {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, ToSchema, Default)]
+pub struct LogoutReq {
+    /// Refresh token of this session; its whole rotation chain is revoked
+    pub refresh_token: Option<String>,
+    /// Revoke every refresh token of the wallet (logout everywhere)
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// Issue a sign-in challenge for a wallet
+#[utoipa::path(
+    post,
+    path = "/auth/nonce",
+    tag = "auth",
+    request_body = NonceReq,
+    responses(
+        (status = 201, description = "Challenge issued", body = NonceRes),
+        (status = 400, description = "Invalid request", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn nonce(
+    State(AppState { pool, .. }): State<AppState>,
+    Json(req): Json<NonceReq>,
+) -> Result<impl IntoResponse, ApiError> {
+    let normalized_wallet = wallet::normalize_and_validate(&req.wallet)?;
+
+    // Truncate to whole seconds: the typed data carries unix timestamps.
+    let issued_at = DateTime::from_timestamp(Utc::now().timestamp(), 0)
+        .ok_or(ApiError::Internal("invalid clock"))?;
+    let expires_at = issued_at + chrono::Duration::seconds(siws::nonce_ttl_from_env());
+
+    let msg = SignInMessage {
+        wallet: normalized_wallet,
+        nonce: siws::generate_nonce(),
+        issued_at,
+        expires_at,
+    };
+
+    // Opportunistically sweep challenges that can no longer be used
+    sqlx::query("DELETE FROM auth_nonces WHERE expires_at < NOW()")
+        .execute(&pool)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    sqlx::query(
+        r#"INSERT INTO auth_nonces (wallet, nonce, issued_at, expires_at)
+           VALUES ($1, $2, $3, $4)"#,
+    )
+    .bind(&msg.wallet)
+    .bind(&msg.nonce)
+    .bind(msg.issued_at)
+    .bind(msg.expires_at)
+    .execute(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    let typed_data = siws::typed_data_json(&msg, &siws::chain_id_from_env());
+
+    Ok((
+        StatusCode::CREATED,
+        Json(NonceRes {
+            wallet: msg.wallet,
+            nonce: msg.nonce,
+            issued_at: msg.issued_at,
+            expires_at: msg.expires_at,
+            typed_data,
+        }),
+    ))
+}
+
+/// Verify a signed challenge and issue a JWT
+#[utoipa::path(
+    post,
+    path = "/auth/verify",
+    tag = "auth",
+    request_body = VerifyReq,
+    responses(
+        (status = 200, description = "Signature verified", body = VerifyRes),
+        (status = 400, description = "Invalid request", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Invalid signature or nonce", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody),
+        (status = 503, description = "Starknet node unavailable", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn verify(
+    State(AppState {
+        pool,
+        verifier,
+        keys,
+        ..
+    }): State<AppState>,
+    Json(req): Json<VerifyReq>,
+) -> Result<Json<VerifyRes>, ApiError> {
+    let normalized_wallet = wallet::normalize_and_validate(&req.wallet)?;
+    let signature = req
+        .signature
+        .iter()
+        .map(|s| siws::parse_felt(s))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Consume the challenge up front so it cannot be replayed, even when the
+    // signature turns out to be invalid.
+    let rec: Option<(DateTime<Utc>, DateTime<Utc>)> = sqlx::query_as(
+        r#"UPDATE auth_nonces SET consumed_at = NOW()
+           WHERE wallet = $1 AND nonce = $2 AND consumed_at IS NULL AND expires_at > NOW()
+           RETURNING issued_at, expires_at"#,
+    )
+    .bind(&normalized_wallet)
+    .bind(req.nonce.trim())
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    let (issued_at, expires_at) = rec.ok_or(ApiError::Unauthorized("invalid or expired nonce"))?;
+
+    let msg = SignInMessage {
+        wallet: normalized_wallet,
+        nonce: req.nonce.trim().to_string(),
+        issued_at,
+        expires_at,
+    };
+    let hash = siws::message_hash(&msg, &siws::chain_id_from_env())?;
+
+    let check = SignatureCheck {
+        account: siws::parse_felt(&msg.wallet)?,
+        message_hash: hash,
+        signature: &signature,
+    };
+    if !verifier.verify(&check).await? {
+        return Err(ApiError::Unauthorized("invalid signature"));
+    }
+
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|_| ApiError::Internal("failed to acquire connection"))?;
+    let (pair, _) = session::issue(&mut conn, &keys, &msg.wallet, None).await?;
+
+    Ok(Json(VerifyRes::new(msg.wallet, pair)))
+}
+
+/// Exchange a refresh token for a new token pair
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshReq,
+    responses(
+        (status = 200, description = "Tokens rotated", body = VerifyRes),
+        (status = 401, description = "Invalid, expired or reused refresh token", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn refresh(
+    State(AppState { pool, keys, .. }): State<AppState>,
+    Json(req): Json<RefreshReq>,
+) -> Result<Json<VerifyRes>, ApiError> {
+    let (wallet, pair) = session::rotate(&pool, &keys, req.refresh_token.trim()).await?;
+    Ok(Json(VerifyRes::new(wallet, pair)))
+}
+
+/// Revoke the current access token and, optionally, refresh tokens
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body(content = LogoutReq, description = "Optional refresh tokens to revoke"),
+    responses(
+        (status = 204, description = "Logged out"),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Called with an API key", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn logout(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    req: Option<Json<LogoutReq>>,
+) -> Result<StatusCode, ApiError> {
+    let Json(req) = req.unwrap_or_default();
+    let wallet = &auth.wallet;
+
+    session::revoke_access(&pool, auth.claims()?).await?;
+    if req.all {
+        session::revoke_all(&pool, wallet).await?;
+        session::revoke_issued_access(&pool, wallet).await?;
+    } else if let Some(token) = req.refresh_token.as_deref() {
+        session::revoke_family(&pool, wallet, token.trim()).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Public signing keys for verifying issued tokens
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "auth",
+    responses(
+        (status = 200, description = "JSON Web Key Set", body = Object)
+    )
+)]
+pub async fn jwks(State(AppState { keys, .. }): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(keys.jwks()),
+    )
+}
@@ -0,0 +1,204 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::libs::{
+    api_keys::Scope,
+    compiler::{self, CompileReport},
+    db::AppState,
+    error::ApiError,
+    scarb, templates,
+};
+use crate::middlewares::auth::AuthUser;
+use crate::routes::generate::{check_owner, has_code};
+
+// contract_name, description, template_id, generated_code, scarb_config, status,
+// latest revision
+type CompileSourceRow = (
+    String,
+    Option<String>,
+    Option<String>,
+    String,
+    Option<String>,
+    String,
+    Option<i32>,
+);
+
+/// Compile a generated contract
+///
+/// Builds the contract with its Scarb.toml using `scarb build` and stores the
+/// outcome: diagnostics, and the Sierra and CASM classes when it compiles.
+/// The contract's status becomes `compiled` or `compile_failed`; deployed and
+/// archived contracts keep theirs, and so does code changed while it built.
+#[utoipa::path(
+    post,
+    path = "/generated_contracts/{id}/compile",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 200, description = "Build finished; `status` tells whether it compiled", body = CompileReport),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:write scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued or failed", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody),
+        (status = 503, description = "Compiler not configured, unavailable or busy", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn compile_contract(
+    State(AppState { pool, compiler, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<CompileReport>, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+
+    let (contract_name, description, template_id, code, scarb_config, status, revision): CompileSourceRow =
+        sqlx::query_as(
+            r#"SELECT g.contract_name, g.description, g.template_id, g.generated_code,
+                      g.scarb_config, g.status,
+                      (SELECT MAX(r.revision) FROM contract_revisions r WHERE r.contract_id = g.id)
+               FROM generated_contracts g
+               JOIN users u ON u.id = g.user_id
+               WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+        )
+        .bind(id)
+        .bind(&auth.wallet)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+        .ok_or(ApiError::NotFound("contract not found"))?;
+    if !has_code(&status) {
+        return Err(ApiError::Conflict("contract has not been generated"));
+    }
+    let compiler = compiler.ok_or(ApiError::ServiceUnavailable("compiler not configured"))?;
+
+    // Contracts from before manifests were stored get one for the default Cairo
+    let scarb_config = match scarb_config {
+        Some(config) => config,
+        None => {
+            let template_name = template_id
+                .as_deref()
+                .and_then(templates::find)
+                .map(|t| t.name);
+            scarb::generate(
+                &contract_name,
+                &scarb::package_description(description.as_deref(), template_name),
+                &[&code],
+                &scarb::parse_cairo_version(scarb::DEFAULT_CAIRO_VERSION)?,
+            )?
+        }
+    };
+
+    let output = compiler.compile(&scarb_config, &code).await?;
+    let record = async {
+        let mut tx = pool.begin().await?;
+        let report = compiler::record(&mut tx, id, revision, &output).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(report)
+    };
+    let report = record.await.map_err(|e| {
+        tracing::error!("Failed to store compilation of contract {}: {:?}", id, e);
+        crate::libs::error::map_sqlx_error(&e)
+    })?;
+
+    tracing::info!(
+        "Compiled contract {}: {} in {}ms",
+        id,
+        report.status,
+        report.duration_ms
+    );
+    Ok(Json(report))
+}
+
+/// Get the latest compilation of a contract
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}/compilation",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 200, description = "Latest compilation", body = CompileReport),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found or never compiled", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_compilation(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<CompileReport>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    check_owner(&pool, id, &auth.wallet).await?;
+
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    compiler::latest(&mut conn, id)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+        .map(Json)
+        .ok_or(ApiError::NotFound("contract has not been compiled"))
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Artifact {
+    Sierra,
+    Casm,
+}
+
+/// Download a compiled class
+///
+/// `sierra` is the contract class to declare, `casm` the compiled class
+/// whose hash goes with it. Only available after a successful build.
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}/compilation/{artifact}",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(
+        ("id" = i64, Path, description = "Generated contract id"),
+        ("artifact" = Artifact, Path, description = "`sierra` or `casm`")
+    ),
+    responses(
+        (status = 200, description = "Class JSON", body = Object),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found or not compiled", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_artifact(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path((id, artifact)): Path<(i64, Artifact)>,
+) -> Result<Json<Value>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    check_owner(&pool, id, &auth.wallet).await?;
+
+    let column = match artifact {
+        Artifact::Sierra => "sierra",
+        Artifact::Casm => "casm",
+    };
+    let row: Option<(Option<Value>,)> = sqlx::query_as(&format!(
+        "SELECT {} FROM contract_compilations WHERE contract_id = $1 ORDER BY id DESC LIMIT 1",
+        column
+    ))
+    .bind(id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    row.and_then(|(class,)| class)
+        .map(Json)
+        .ok_or(ApiError::NotFound("contract has not been compiled"))
+}
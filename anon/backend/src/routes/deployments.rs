@@ -0,0 +1,631 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header::LOCATION},
+    response::{IntoResponse, Response},
+};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use starknet::core::types::Felt;
+use utoipa::ToSchema;
+
+use crate::libs::{
+    abi,
+    api_keys::Scope,
+    calldata,
+    db::AppState,
+    deployer::{Classes, DeployRequest, TxStatus},
+    deployments::{self, Deployment, Network, NewDeployment},
+    error::{ApiError, FieldError},
+    pagination::{decode_id_cursor, encode_id_cursor},
+    roles::Role,
+    wallet,
+};
+use crate::middlewares::auth::{Admin, AuthUser, RequireRole};
+use crate::routes::generate::has_code;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecordDeploymentReq {
+    /// `mainnet`, `sepolia` or `devnet`
+    pub network: String,
+    pub contract_address: String,
+    pub class_hash: String,
+    pub transaction_hash: String,
+    /// Transaction that declared the class, if it was declared for this
+    /// deployment
+    pub declare_transaction_hash: Option<String>,
+}
+
+// `value`, or an error for `field` in `errors` when there is none.
+fn field<T>(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    value: Option<T>,
+    message: &str,
+) -> Option<T> {
+    if value.is_none() {
+        errors.push(FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        });
+    }
+    value
+}
+
+// The deployment `req` describes, or every invalid field of it.
+fn validate(req: &RecordDeploymentReq) -> Result<NewDeployment, ApiError> {
+    const HASH: &str = "must be a non-zero felt";
+    let mut errors = Vec::new();
+    let network = field(
+        &mut errors,
+        "network",
+        Network::parse(req.network.trim()),
+        "must be `mainnet`, `sepolia` or `devnet`",
+    );
+    let contract_address = field(
+        &mut errors,
+        "contract_address",
+        wallet::normalize_and_validate(&req.contract_address)
+            .ok()
+            .filter(|a| a != "0x0"),
+        "must be a non-zero address",
+    );
+    let class_hash = field(
+        &mut errors,
+        "class_hash",
+        wallet::normalize_hash(&req.class_hash),
+        HASH,
+    );
+    let transaction_hash = field(
+        &mut errors,
+        "transaction_hash",
+        wallet::normalize_hash(&req.transaction_hash),
+        HASH,
+    );
+    let declare_transaction_hash = match req.declare_transaction_hash.as_deref() {
+        Some(h) => field(
+            &mut errors,
+            "declare_transaction_hash",
+            wallet::normalize_hash(h),
+            HASH,
+        )
+        .map(Some),
+        None => Some(None),
+    };
+
+    match (
+        network,
+        contract_address,
+        class_hash,
+        transaction_hash,
+        declare_transaction_hash,
+    ) {
+        (
+            Some(network),
+            Some(contract_address),
+            Some(class_hash),
+            Some(transaction_hash),
+            Some(declare_transaction_hash),
+        ) => Ok(NewDeployment {
+            network,
+            contract_address,
+            class_hash,
+            transaction_hash,
+            declare_transaction_hash,
+            // Set once the contract is locked
+            revision: None,
+        }),
+        _ => Err(ApiError::Validation(errors)),
+    }
+}
+
+/// Record a deployment of a generated contract
+///
+/// For contracts declared and deployed outside StarkFinder, e.g. from the
+/// user's wallet. Addresses and hashes are stored normalized (lowercase,
+/// without leading zeros) and the contract's status becomes `deployed`.
+#[utoipa::path(
+    post,
+    path = "/generated_contracts/{id}/deployments",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    request_body = RecordDeploymentReq,
+    responses(
+        (status = 201, description = "Deployment recorded", body = Deployment),
+        (status = 400, description = "Invalid fields, listed in `details`", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:write scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued or failed, or this contract already has a deployment at the address on that network", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn record_deployment(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<RecordDeploymentReq>,
+) -> Result<(StatusCode, Json<Deployment>), ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+    let mut new = validate(&req)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    // A recorded deployment is of the current code
+    let (status, revision): (String, Option<i32>) = sqlx::query_as(
+        r#"SELECT g.status,
+                  (SELECT MAX(r.revision) FROM contract_revisions r WHERE r.contract_id = g.id)
+           FROM generated_contracts g
+           JOIN users u ON u.id = g.user_id
+           WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL
+           FOR UPDATE OF g"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if !has_code(&status) {
+        return Err(ApiError::Conflict("contract has not been generated"));
+    }
+    new.revision = revision;
+
+    let deployment = deployments::record(&mut tx, id, &new).await.map_err(|e| {
+        match crate::libs::error::map_sqlx_error(&e) {
+            ApiError::Conflict(_) => {
+                ApiError::Conflict("this contract already has a deployment at this address")
+            }
+            e => e,
+        }
+    })?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    tracing::info!(
+        "Recorded deployment of contract {} at {} on {}",
+        id,
+        deployment.contract_address,
+        deployment.network
+    );
+    Ok((StatusCode::CREATED, Json(deployment)))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeployReq {
+    /// Constructor arguments by name, as for `/generated_contracts/{id}/calldata`
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub args: Map<String, Value>,
+    /// UDC salt, decimal or `0x`; random when omitted
+    pub salt: Option<String>,
+    /// Derive the address from the deployer account as well, so nobody else
+    /// can deploy to it
+    #[serde(default)]
+    pub unique: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeployRes {
+    #[serde(flatten)]
+    pub deployment: Deployment,
+    pub salt: String,
+    /// Estimated fee of the deployment, in fri
+    pub deploy_fee: String,
+    /// Estimated fee of the declaration, in fri, when the class was declared
+    /// for this deployment
+    pub declare_fee: Option<String>,
+}
+
+// status, sierra, casm, revision built
+type DeploySourceRow = (String, Option<Value>, Option<Value>, Option<i32>);
+
+/// Declare and deploy a generated contract
+///
+/// Sends the latest build of the current code to the network the server's
+/// deployer account is configured for: the class is declared unless the
+/// network already has it, then deployed through the Universal Deployer
+/// Contract. Each transaction's fee is estimated before it is sent. The
+/// deployment is recorded as for `/generated_contracts/{id}/deployments` as
+/// soon as its transaction is sent, and the request returns once it is
+/// confirmed; when that takes too long it returns 202 with the deployment
+/// still `pending`, to be followed with `GET /deployments/{id}`. The server's
+/// account pays, so only admins may deploy; they may deploy any user's
+/// contract, and the deployment is recorded under that contract.
+#[utoipa::path(
+    post,
+    path = "/generated_contracts/{id}/deploy",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    request_body = DeployReq,
+    responses(
+        (status = 201, description = "Contract deployed", body = DeployRes),
+        (status = 202, description = "Deployment sent but not confirmed yet; `Location` names it", body = DeployRes),
+        (status = 400, description = "Invalid arguments or salt, listed in `details`", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Admin role or contracts:write scope required", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued or failed, or the current code has not been built", body = crate::libs::error::ErrorBody),
+        (status = 422, description = "Invalid build, fee above the deployer's limit, or transaction rejected or reverted", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody),
+        (status = 502, description = "Starknet node failed or the declaration was not confirmed in time", body = crate::libs::error::ErrorBody),
+        (status = 503, description = "Deployer not configured or busy", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn deploy_contract(
+    State(AppState { pool, deployer, .. }): State<AppState>,
+    RequireRole(auth, _): RequireRole<Admin>,
+    Path(id): Path<i64>,
+    Json(req): Json<DeployReq>,
+) -> Result<Response, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+
+    // The latest successful build of the latest revision
+    let (status, sierra, casm, revision): DeploySourceRow = sqlx::query_as(
+        r#"SELECT g.status, c.sierra, c.casm, c.revision
+           FROM generated_contracts g
+           LEFT JOIN LATERAL (
+               SELECT c.sierra, c.casm, c.revision
+               FROM contract_compilations c
+               WHERE c.contract_id = g.id AND c.success
+                 AND c.sierra IS NOT NULL AND c.casm IS NOT NULL
+                 AND c.revision IS NOT DISTINCT FROM
+                     (SELECT MAX(r.revision) FROM contract_revisions r WHERE r.contract_id = g.id)
+               ORDER BY c.id DESC
+               LIMIT 1
+           ) c ON TRUE
+           WHERE g.id = $1 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if !has_code(&status) {
+        return Err(ApiError::Conflict("contract has not been generated"));
+    }
+    let deployer = deployer.ok_or(ApiError::ServiceUnavailable("deployer not configured"))?;
+    let (Some(sierra), Some(casm)) = (sierra, casm) else {
+        return Err(ApiError::Conflict("current code has not been compiled"));
+    };
+
+    let entries = abi::sierra_abi(&sierra).unwrap_or_default();
+    let (constructor_calldata, mut errors) =
+        match calldata::encode_constructor(&entries, &req.args, "args") {
+            Ok(calldata) => (calldata, Vec::new()),
+            Err(errors) => (Vec::new(), errors),
+        };
+    let salt = match req.salt.as_deref() {
+        Some(s) => field(
+            &mut errors,
+            "salt",
+            Felt::from_str(s.trim()).ok(),
+            "must be a felt (decimal or 0x string)",
+        ),
+        None => Some(Felt::from_bytes_be(&rand::random::<[u8; 32]>())),
+    };
+    let Some(salt) = salt.filter(|_| errors.is_empty()) else {
+        return Err(ApiError::Validation(errors));
+    };
+    let classes = Classes::from_artifacts(&sierra, &casm)?;
+    let class_hash = classes.class_hash;
+
+    let sent = deployer
+        .send(
+            classes,
+            DeployRequest {
+                constructor_calldata,
+                salt,
+                unique: req.unique,
+            },
+        )
+        .await?;
+    let outcome = &sent.outcome;
+    let new = NewDeployment {
+        network: deployer.network,
+        contract_address: format!("{:#x}", outcome.contract_address),
+        class_hash: format!("{:#x}", class_hash),
+        transaction_hash: format!("{:#x}", outcome.transaction_hash),
+        declare_transaction_hash: outcome
+            .declare_transaction_hash
+            .map(|h| format!("{:#x}", h)),
+        revision,
+    };
+    let deploy_fee = outcome.deploy_fee.overall_fee.to_string();
+    let declare_fee = outcome
+        .declare_fee
+        .as_ref()
+        .map(|f| f.overall_fee.to_string());
+
+    // Stored before it is followed, so the sent transaction is never lost
+    let record = async {
+        let mut tx = pool.begin().await?;
+        let deployment = deployments::record_pending(&mut tx, id, &new).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(deployment)
+    };
+    let mut deployment = record.await.map_err(|e| {
+        // On chain already; leave enough in the logs to record it by hand
+        tracing::error!(
+            "Failed to store deployment of contract {} at {} on {} (tx {}): {:?}",
+            id,
+            new.contract_address,
+            new.network.as_str(),
+            new.transaction_hash,
+            e
+        );
+        crate::libs::error::map_sqlx_error(&e)
+    })?;
+
+    let status = deployer.confirm(sent).await;
+    if status != TxStatus::Pending {
+        let settle = async {
+            let mut tx = pool.begin().await?;
+            let settled =
+                deployments::settle(&mut tx, deployment.id, status == TxStatus::Succeeded).await?;
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(settled)
+        };
+        match settle.await {
+            Ok(Some(settled)) => deployment.status = settled,
+            Ok(None) => {}
+            // Left pending; GET /deployments/{id} settles it
+            Err(e) => tracing::error!("Failed to settle deployment {}: {:?}", deployment.id, e),
+        }
+    }
+    if deployment.status == "failed" {
+        return Err(ApiError::Unprocessable("transaction reverted"));
+    }
+
+    let pending = deployment.status == "pending";
+    let location = format!("/deployments/{}", deployment.id);
+    let res = Json(DeployRes {
+        deployment,
+        salt: format!("{:#x}", salt),
+        deploy_fee,
+        declare_fee,
+    });
+    if pending {
+        tracing::info!(
+            "Deployment of contract {} is pending (tx {})",
+            id,
+            res.deployment.transaction_hash
+        );
+        return Ok((StatusCode::ACCEPTED, [(LOCATION, location)], res).into_response());
+    }
+    tracing::info!(
+        "Deployed contract {} at {} on {}",
+        id,
+        res.deployment.contract_address,
+        res.deployment.network
+    );
+    Ok((StatusCode::CREATED, res).into_response())
+}
+
+#[derive(Debug, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct DeploymentsQuery {
+    pub network: Option<Network>,
+    /// Only deployments of this generated contract
+    pub contract_id: Option<i64>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeploymentItem {
+    #[serde(flatten)]
+    pub deployment: Deployment,
+    pub contract_name: String,
+    pub contract_type: String,
+    /// Current status of the generated contract
+    pub contract_status: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeploymentsListRes {
+    pub items: Vec<DeploymentItem>,
+    pub next_cursor: Option<String>,
+}
+
+// id, contract_id, network, contract_address, class_hash, transaction_hash,
+// declare_transaction_hash, revision, status, created_at, contract_name,
+// contract_type, contract status
+type DeploymentRow = (
+    i64,
+    i64,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<i32>,
+    String,
+    DateTime<Utc>,
+    String,
+    String,
+    String,
+);
+
+const DEPLOYMENT_SELECT: &str = r#"SELECT d.id, d.contract_id, d.network, d.contract_address, d.class_hash,
+          d.transaction_hash, d.declare_transaction_hash, d.revision, d.status, d.created_at,
+          g.contract_name, g.contract_type, g.status
+   FROM deployed_contracts d
+   JOIN generated_contracts g ON g.id = d.contract_id
+   JOIN users u ON u.id = g.user_id
+   WHERE ($1::TEXT IS NULL OR u.wallet = $1) AND g.deleted_at IS NULL"#;
+
+fn deployment_item(
+    (
+        id,
+        contract_id,
+        network,
+        contract_address,
+        class_hash,
+        transaction_hash,
+        declare_transaction_hash,
+        revision,
+        status,
+        created_at,
+        contract_name,
+        contract_type,
+        contract_status,
+    ): DeploymentRow,
+) -> DeploymentItem {
+    DeploymentItem {
+        deployment: Deployment {
+            id,
+            contract_id,
+            network,
+            contract_address,
+            class_hash,
+            transaction_hash,
+            declare_transaction_hash,
+            revision,
+            status,
+            created_at,
+        },
+        contract_name,
+        contract_type,
+        contract_status,
+    }
+}
+
+/// List the authenticated user's deployments
+///
+/// Newest first, each with the generated contract it was deployed from.
+#[utoipa::path(
+    get,
+    path = "/deployments",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(DeploymentsQuery),
+    responses(
+        (status = 200, description = "Deployments", body = DeploymentsListRes),
+        (status = 400, description = "Invalid cursor or network", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn list_deployments(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Query(q): Query<DeploymentsQuery>,
+) -> Result<Json<DeploymentsListRes>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    let limit = q.limit.unwrap_or(20).clamp(1, 50);
+    let before = match q.cursor.as_deref() {
+        Some(s) => Some(decode_id_cursor(s).ok_or(ApiError::BadRequest("invalid cursor"))?),
+        None => None,
+    };
+
+    let rows: Vec<DeploymentRow> = sqlx::query_as(&format!(
+        r#"{}
+             AND ($2::TEXT IS NULL OR d.network = $2)
+             AND ($3::BIGINT IS NULL OR d.contract_id = $3)
+             AND ($4::BIGINT IS NULL OR d.id < $4)
+           ORDER BY d.id DESC
+           LIMIT $5"#,
+        DEPLOYMENT_SELECT
+    ))
+    .bind(&auth.wallet)
+    .bind(q.network.map(Network::as_str))
+    .bind(q.contract_id)
+    .bind(before)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    let items: Vec<DeploymentItem> = rows.into_iter().map(deployment_item).collect();
+    let next_cursor = if items.len() as i64 == limit {
+        items
+            .last()
+            .map(|last| encode_id_cursor(last.deployment.id))
+    } else {
+        None
+    };
+
+    Ok(Json(DeploymentsListRes { items, next_cursor }))
+}
+
+// Deployment `id` of a contract of `wallet`, or of anyone's without one
+async fn fetch_deployment(
+    pool: &sqlx::PgPool,
+    wallet: Option<&str>,
+    id: i64,
+) -> Result<DeploymentItem, ApiError> {
+    let row: DeploymentRow = sqlx::query_as(&format!("{} AND d.id = $2", DEPLOYMENT_SELECT))
+        .bind(wallet)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+        .ok_or(ApiError::NotFound("deployment not found"))?;
+    Ok(deployment_item(row))
+}
+
+/// Get one of the authenticated user's deployments
+///
+/// A `pending` deployment sent by `/generated_contracts/{id}/deploy` is
+/// checked against the network first, and settled as `confirmed` or
+/// `failed` once its receipt is there. Admins may get any user's
+/// deployment, as they may deploy any user's contract.
+#[utoipa::path(
+    get,
+    path = "/deployments/{id}",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Deployment id")),
+    responses(
+        (status = 200, description = "Deployment", body = DeploymentItem),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Deployment not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_deployment(
+    State(AppState { pool, deployer, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<DeploymentItem>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    let owner = (!auth.roles.contains(&Role::Admin)).then_some(auth.wallet.as_str());
+    let item = fetch_deployment(&pool, owner, id).await?;
+    if item.deployment.status != "pending" {
+        return Ok(Json(item));
+    }
+    // Only the deployer's own network can say what became of it
+    let Some(deployer) = deployer.filter(|d| d.network.as_str() == item.deployment.network) else {
+        return Ok(Json(item));
+    };
+    let Ok(hash) = Felt::from_hex(&item.deployment.transaction_hash) else {
+        return Ok(Json(item));
+    };
+    // A node failure leaves it pending for the next look
+    let status = deployer.status(hash).await.unwrap_or(TxStatus::Pending);
+    if status == TxStatus::Pending {
+        return Ok(Json(item));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    deployments::settle(&mut tx, id, status == TxStatus::Succeeded)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    Ok(Json(fetch_deployment(&pool, owner, id).await?))
+}
@@ -1,21 +1,75 @@
 use axum::{
     Json,
-    extract::{Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{
+        StatusCode,
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, LOCATION},
+    },
+    response::{IntoResponse, Response},
 };
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use tracing;
 use utoipa::ToSchema;
 
-use crate::libs::{db::AppState, error::ApiError};
+use crate::libs::{
+    api_keys::Scope,
+    archive::{self, ArchiveFormat},
+    cairo,
+    db::AppState,
+    error::ApiError,
+    generator::{GenerationRequest, GeneratorKind},
+    jobs,
+    revisions::{self, Source},
+    scarb, templates,
+};
 use crate::middlewares::auth::AuthUser;
 
+/// Key `/generated_contracts` is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    ContractName,
+}
+
+impl ContractSort {
+    fn column(self) -> &'static str {
+        match self {
+            ContractSort::CreatedAt => "created_at",
+            ContractSort::UpdatedAt => "updated_at",
+            ContractSort::ContractName => "contract_name",
+        }
+    }
+
+    // Newest first for times, alphabetical for names
+    fn default_order(self) -> SortOrder {
+        match self {
+            ContractSort::ContractName => SortOrder::Asc,
+            ContractSort::CreatedAt | ContractSort::UpdatedAt => SortOrder::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Position after the last item of a page, under the sort it was listed with
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GeneratedContractsCursor {
-    pub created_at: DateTime<Utc>,
+    pub sort: ContractSort,
+    pub order: SortOrder,
+    /// Sort key of the last item: an RFC 3339 time or a contract name
+    pub key: String,
     pub id: i64,
 }
 
@@ -31,12 +85,27 @@ pub fn decode_cursor(s: &str) -> Option<GeneratedContractsCursor> {
 
 #[derive(Deserialize, ToSchema)]
 pub struct GenerateContractReq {
-    pub user_id: i64,
+    /// Deprecated: the owner is taken from the token. If sent, it must match
+    /// the authenticated user.
+    pub user_id: Option<i64>,
     pub contract_type: String,
     pub contract_name: String,
     pub description: Option<String>,
+    /// Template parameters, validated against the template's schema
+    /// (`Erc20Parameters`, `MultisigParameters`, ...). Omitted parameters
+    /// take their defaults.
     pub parameters: Option<serde_json::Value>,
     pub template_id: Option<String>,
+    /// Cairo compiler version to target in the generated Scarb.toml
+    /// (default 2.11.4); dependency versions are picked to match it.
+    pub cairo_version: Option<String>,
+    /// Force the template renderer or the LLM. By default the server picks
+    /// per template. With `llm`, `contract_type` may be any free-form kind.
+    pub generator: Option<GeneratorKind>,
+    /// Queue the generation and answer 202 with a job to poll at
+    /// `/jobs/{id}` instead of waiting for the code
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -49,15 +118,100 @@ pub struct GenerateContractRes {
     pub parameters: Option<serde_json::Value>,
     pub template_id: Option<String>,
     pub generated_code: String,
+    /// Scarb.toml for building `generated_code`
+    pub scarb_config: Option<String>,
+    /// Generator that wrote the code: `template` or `llm`
+    pub generator: String,
+    /// `queued`, `generating`, `generated` or `failed`; once built,
+    /// `compiled` or `compile_failed`; `deployed` once a deployment is
+    /// recorded; `archived` when set aside by the owner
     pub status: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+// Whether a contract in `status` has its code; asynchronous generations
+// fill it in later.
+pub(crate) fn has_code(status: &str) -> bool {
+    !matches!(status, "queued" | "generating" | "failed")
+}
+
+// Whether the contract's code is still being written by a job.
+pub(crate) fn in_progress(status: &str) -> bool {
+    matches!(status, "queued" | "generating")
+}
+
+// Fails with 404 unless contract `id` belongs to `wallet`.
+pub(crate) async fn check_owner(pool: &PgPool, id: i64, wallet: &str) -> Result<(), ApiError> {
+    sqlx::query_as::<_, (i64,)>(
+        r#"SELECT g.id FROM generated_contracts g
+           JOIN users u ON u.id = g.user_id
+           WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(wallet)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .map(|_| ())
+    .ok_or(ApiError::NotFound("contract not found"))
+}
+
+// Contract `id` as returned by `/generate`.
+pub(crate) async fn fetch_contract(
+    pool: &PgPool,
+    id: i64,
+) -> Result<Option<GenerateContractRes>, ApiError> {
+    let rec = sqlx::query!(
+        r#"
+        SELECT
+            id, user_id, contract_type, contract_name, description,
+            parameters, template_id, generated_code, scarb_config, generator, status,
+            created_at, updated_at
+        FROM generated_contracts
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    Ok(rec.map(|rec| GenerateContractRes {
+        contract_id: rec.id,
+        user_id: rec.user_id,
+        contract_type: rec.contract_type,
+        contract_name: rec.contract_name,
+        description: rec.description,
+        parameters: rec.parameters,
+        template_id: rec.template_id,
+        generated_code: rec.generated_code,
+        scarb_config: rec.scarb_config,
+        generator: rec.generator,
+        status: rec.status,
+        created_at: rec.created_at,
+        updated_at: rec.updated_at,
+    }))
+}
+
 #[derive(Deserialize, ToSchema, utoipa::IntoParams)]
 pub struct GeneratedContractsQuery {
     pub cursor: Option<String>,
     pub limit: Option<i64>,
+    pub contract_type: Option<String>,
+    pub status: Option<String>,
+    pub template_id: Option<String>,
+    /// Only contracts created at or after this time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only contracts created before this time
+    pub created_before: Option<DateTime<Utc>>,
+    /// Words to look for in the name or description; also matched as a
+    /// substring
+    pub q: Option<String>,
+    /// `created_at` by default
+    pub sort: Option<ContractSort>,
+    /// `desc` by default for times, `asc` for `contract_name`
+    pub order: Option<SortOrder>,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -70,6 +224,8 @@ pub struct GeneratedContractItem {
     pub parameters: Option<serde_json::Value>,
     pub template_id: Option<String>,
     pub status: String,
+    /// Why generation failed, when `status` is `failed`
+    pub error: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -80,43 +236,73 @@ pub struct GeneratedContractsListRes {
     pub next_cursor: Option<String>,
 }
 
-/// Generate a new contract for a user
+/// Generate a new contract for the authenticated user
+///
+/// `contract_type` selects a built-in template (e.g. `erc20`, `nft`,
+/// `multisig`) and `parameters` fill it in; see `libs::templates` for the
+/// parameters each template takes. The code is written by the template
+/// renderer or, when selected, an LLM. A Scarb.toml with the dependencies the
+/// code imports is returned and stored alongside it.
+///
+/// Send an `Idempotency-Key` header to make retries safe: a retry with the
+/// same key and body gets the first response back instead of generating
+/// another contract.
+///
+/// With `"async": true` the request is validated, the contract is stored as
+/// `queued` and a 202 with the job is returned; a background worker fills in
+/// the code, retrying upstream failures with backoff.
 #[utoipa::path(
     post,
     path = "/generate",
     tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key get the first response back")),
     request_body = GenerateContractReq,
     responses(
         (status = 201, description = "Contract generated successfully", body = GenerateContractRes),
-        (status = 400, description = "Invalid request", body = crate::libs::error::ErrorBody),
+        (status = 202, description = "Generation queued; poll the job at the Location header", body = crate::routes::jobs::JobRes),
+        (status = 400, description = "Invalid request, unknown contract_type or unsupported cairo_version; invalid parameters are listed in `details`", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "user_id does not match token or missing contracts:write scope", body = crate::libs::error::ErrorBody),
         (status = 404, description = "User not found", body = crate::libs::error::ErrorBody),
-        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+        (status = 409, description = "A request with the same idempotency key is in progress", body = crate::libs::error::ErrorBody),
+        (status = 422, description = "Idempotency key used for a different request", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody),
+        (status = 502, description = "LLM reply unusable", body = crate::libs::error::ErrorBody),
+        (status = 503, description = "LLM generator unavailable or not configured", body = crate::libs::error::ErrorBody)
     )
 )]
 pub async fn generate_contract(
-    State(AppState { pool }): State<AppState>,
+    State(AppState {
+        pool,
+        generators,
+        jobs: queue,
+        ..
+    }): State<AppState>,
+    auth: AuthUser,
     Json(req): Json<GenerateContractReq>,
-) -> Result<impl IntoResponse, ApiError> {
-    tracing::info!(
-        "Generating contract for user_id: {}, type: {}, name: {}",
-        req.user_id,
-        req.contract_type,
-        req.contract_name
-    );
+) -> Result<Response, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
 
-    // TODO: Implement the actual contract generation logic
-    // For now, we'll create a placeholder implementation
-
-    // Validate user exists
-    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", req.user_id as i64)
+    // Get user ID from wallet
+    let (user_id,): (i64,) = sqlx::query_as("SELECT id FROM users WHERE wallet = $1")
+        .bind(&auth.wallet)
         .fetch_optional(&pool)
         .await
-        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+        .ok_or(ApiError::NotFound("user not found"))?;
 
-    if user.is_none() {
-        return Err(ApiError::NotFound("user not found"));
+    if req.user_id.is_some_and(|id| id != user_id) {
+        return Err(ApiError::Forbidden("user_id does not match token"));
     }
 
+    tracing::info!(
+        "Generating contract for user_id: {}, type: {}, name: {}",
+        user_id,
+        req.contract_type,
+        req.contract_name
+    );
+
     // Validate required fields
     if req.contract_type.trim().is_empty() {
         return Err(ApiError::BadRequest("contract_type is required"));
@@ -158,65 +344,127 @@ pub async fn generate_contract(
         }
     }
 
-    // Generate placeholder contract code (this would be replaced with actual generation logic)
-    let generated_code = format!(
-        r#"// Generated contract: {}
-// Type: {}
-// Generated at: {}
-
-#[starknet::contract]
-mod {} {{
-    use starknet::{{get_caller_address, contract_address_const}};
+    let cairo_version = scarb::parse_cairo_version(
+        req.cairo_version
+            .as_deref()
+            .unwrap_or(scarb::DEFAULT_CAIRO_VERSION),
+    )?;
+    if !templates::supports_cairo(&cairo_version) {
+        return Err(ApiError::BadRequest("unsupported cairo_version"));
+    }
 
-    #[storage]
-    struct Storage {{
-        // TODO: Add storage variables based on contract type
-    }}
+    let template = match templates::resolve(&req.contract_type, req.template_id.as_deref()) {
+        Ok(template) => Some(template),
+        // Free-form contract types are left to the LLM
+        Err(_) if req.generator == Some(GeneratorKind::Llm) && req.template_id.is_none() => None,
+        Err(e) => return Err(e),
+    };
+    let kind = generators.select(req.generator, template);
+    let generation = GenerationRequest {
+        contract_type: &req.contract_type,
+        template,
+        contract_name: &req.contract_name,
+        description: req.description.as_deref(),
+        parameters: req.parameters.as_ref(),
+    };
 
-    #[external(v0)]
-    fn constructor(ref self: ContractState) {{
-        // TODO: Add constructor logic
-    }}
+    let (generated_code, scarb_config, status) = if req.run_async {
+        // Reject what the worker would reject before queueing it
+        generators.get(kind)?;
+        if let Some(t) = template {
+            templates::resolve_params(t, req.parameters.as_ref())?;
+        }
+        (String::new(), None, "queued")
+    } else {
+        let out = generators
+            .write(kind, &generation, &cairo_version, None)
+            .await?;
+        (out.code, Some(out.scarb_config), "generated")
+    };
 
-    // TODO: Add contract-specific functions
-}}"#,
-        req.contract_name,
-        req.contract_type,
-        chrono::Utc::now().to_rfc3339(),
-        req.contract_name.to_lowercase().replace(" ", "_")
-    );
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
 
     // Insert the generated contract into the database
     let rec = sqlx::query!(
         r#"
         INSERT INTO generated_contracts (
             user_id, contract_type, contract_name, description,
-            parameters, template_id, generated_code, status
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            parameters, template_id, generated_code, scarb_config, generator, status
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING
             id, user_id, contract_type, contract_name, description,
-            parameters, template_id, generated_code, status, created_at, updated_at
+            parameters, template_id, generated_code, scarb_config, generator, status,
+            created_at, updated_at
         "#,
-        req.user_id,
+        user_id,
         req.contract_type,
         req.contract_name,
         req.description,
         req.parameters,
-        req.template_id,
+        template.map(|t| t.id),
         generated_code,
-        "generated"
+        scarb_config,
+        kind.as_str(),
+        status
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         tracing::error!("Database error while inserting generated contract: {:?}", e);
         crate::libs::error::map_sqlx_error(&e)
     })?;
 
+    if req.run_async {
+        let job_id = jobs::enqueue(
+            &mut tx,
+            rec.id,
+            &cairo_version.to_string(),
+            queue.max_attempts,
+        )
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+        tx.commit()
+            .await
+            .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+        queue.notify.notify_one();
+
+        tracing::info!(
+            "Queued job {} for contract {} of user {}",
+            job_id,
+            rec.id,
+            user_id
+        );
+        let job = crate::routes::jobs::find_job(&pool, job_id, &auth.wallet)
+            .await?
+            .ok_or(ApiError::Internal("queued job not found"))?;
+        return Ok((
+            StatusCode::ACCEPTED,
+            [(LOCATION, format!("/jobs/{}", job_id))],
+            Json(job),
+        )
+            .into_response());
+    }
+    revisions::add(
+        &mut tx,
+        rec.id,
+        Source::Generated,
+        &rec.generated_code,
+        rec.scarb_config.as_deref(),
+        None,
+    )
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
     tracing::info!(
         "Successfully generated contract with id: {} for user: {}",
         rec.id,
-        req.user_id
+        user_id
     );
 
     Ok((
@@ -230,37 +478,85 @@ mod {} {{
             parameters: rec.parameters,
             template_id: rec.template_id,
             generated_code: rec.generated_code,
+            scarb_config: rec.scarb_config,
+            generator: rec.generator,
             status: rec.status,
             created_at: rec.created_at,
             updated_at: rec.updated_at,
         }),
-    ))
+    )
+        .into_response())
+}
+
+// id, user_id, contract_type, contract_name, description, parameters,
+// template_id, status, error, created_at, updated_at
+type ContractItemRow = (
+    i64,
+    i64,
+    String,
+    String,
+    Option<String>,
+    Option<serde_json::Value>,
+    Option<String>,
+    String,
+    Option<String>,
+    DateTime<Utc>,
+    DateTime<Utc>,
+);
+
+// `s` with LIKE wildcards escaped.
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
+/// List the authenticated user's generated contracts
+///
+/// Filters combine with AND. `q` matches whole words of the name or
+/// description (full-text) as well as any substring of them. Follow
+/// `next_cursor` with the same `sort` and `order` for the next page.
 #[utoipa::path(
     get,
     path = "/generated_contracts",
     tag = "contracts",
-    security(("bearer_auth" = [])),
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
     params(GeneratedContractsQuery),
     responses(
         (status = 200, description = "List of generated contracts", body = GeneratedContractsListRes),
+        (status = 400, description = "Invalid cursor, cursor issued for another sort, or q too long", body = crate::libs::error::ErrorBody),
         (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
         (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
     )
 )]
 pub async fn list_generated_contracts(
-    State(AppState { pool }): State<AppState>,
-    AuthUser { wallet }: AuthUser,
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
     Query(q): Query<GeneratedContractsQuery>,
 ) -> Result<Json<GeneratedContractsListRes>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    let wallet = auth.wallet;
     let limit = q.limit.unwrap_or(20).clamp(1, 50);
+    let sort = q.sort.unwrap_or_default();
+    let order = q.order.unwrap_or(sort.default_order());
 
-    // Decode cursor if provided
     let cursor = match q.cursor.as_deref() {
-        Some(s) => decode_cursor(s),
         None => None,
+        Some(s) => Some(decode_cursor(s).ok_or(ApiError::BadRequest("invalid cursor"))?),
     };
+    if cursor
+        .as_ref()
+        .is_some_and(|c| c.sort != sort || c.order != order)
+    {
+        return Err(ApiError::BadRequest(
+            "cursor was issued for another sort or order",
+        ));
+    }
+    let text = q.q.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    if text.is_some_and(|t| t.len() > 200) {
+        return Err(ApiError::BadRequest("q must be less than 200 characters"));
+    }
 
     // Get user ID from wallet
     let user_id: (i64,) = sqlx::query_as("SELECT id FROM users WHERE wallet = $1")
@@ -270,58 +566,70 @@ pub async fn list_generated_contracts(
         .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
         .ok_or(ApiError::NotFound("user not found"))?;
 
-    // Build query for generated contracts
-    let rows = if let Some(c) = &cursor {
-        sqlx::query_as::<_, (
-            i64,            // id
-            i64,            // user_id
-            String,         // contract_type
-            String,         // contract_name
-            Option<String>, // description
-            Option<serde_json::Value>, // parameters
-            Option<String>, // template_id
-            String,         // status
-            DateTime<Utc>,  // created_at
-            DateTime<Utc>,  // updated_at
-        )>(
-            r#"SELECT id, user_id, contract_type, contract_name, description, parameters, template_id, status, created_at, updated_at
-                FROM generated_contracts
-                WHERE user_id = $1 AND (created_at < $2 OR (created_at = $2 AND id < $3))
-                ORDER BY created_at DESC, id DESC
-        LIMIT $4"#
-        )
-        .bind(user_id.0)
-    .bind(c.created_at) // $2
-    .bind(c.id)         // $3
-    .bind(limit)        // $4
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
-    } else {
-        sqlx::query_as::<_, (
-            i64,
-            i64,
-            String,
-            String,
-            Option<String>,
-            Option<serde_json::Value>,
-            Option<String>,
-            String,
-            DateTime<Utc>,
-            DateTime<Utc>,
-        )>(
-            r#"SELECT id, user_id, contract_type, contract_name, description, parameters, template_id, status, created_at, updated_at
-                FROM generated_contracts
-                WHERE user_id = $1
-                ORDER BY created_at DESC, id DESC
-                LIMIT $2"#
-        )
-        .bind(user_id.0)
-        .bind(limit)
+    let mut query = QueryBuilder::<Postgres>::new(
+        r#"SELECT id, user_id, contract_type, contract_name, description, parameters, template_id, status, error, created_at, updated_at
+           FROM generated_contracts
+           WHERE deleted_at IS NULL AND user_id = "#,
+    );
+    query.push_bind(user_id.0);
+    if let Some(contract_type) = q.contract_type {
+        query.push(" AND contract_type = ").push_bind(contract_type);
+    }
+    if let Some(status) = q.status {
+        query.push(" AND status = ").push_bind(status);
+    }
+    if let Some(template_id) = q.template_id {
+        query.push(" AND template_id = ").push_bind(template_id);
+    }
+    if let Some(after) = q.created_after {
+        query.push(" AND created_at >= ").push_bind(after);
+    }
+    if let Some(before) = q.created_before {
+        query.push(" AND created_at < ").push_bind(before);
+    }
+    if let Some(text) = text {
+        let pattern = format!("%{}%", escape_like(text));
+        query
+            .push(" AND (search @@ plainto_tsquery('simple', ")
+            .push_bind(text.to_string())
+            .push(") OR contract_name ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR description ILIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+
+    // Ties on the sort key are broken by id, in the same direction, so the
+    // row comparison below picks up exactly where the last page stopped.
+    let column = sort.column();
+    let (after, direction) = match order {
+        SortOrder::Asc => (">", "ASC"),
+        SortOrder::Desc => ("<", "DESC"),
+    };
+    if let Some(c) = &cursor {
+        query.push(format!(" AND ({}, id) {} (", column, after));
+        match sort {
+            ContractSort::ContractName => query.push_bind(c.key.clone()),
+            ContractSort::CreatedAt | ContractSort::UpdatedAt => {
+                let key = DateTime::parse_from_rfc3339(&c.key)
+                    .map_err(|_| ApiError::BadRequest("invalid cursor"))?;
+                query.push_bind(key.with_timezone(&Utc))
+            }
+        };
+        query.push(", ").push_bind(c.id).push(")");
+    }
+    query
+        .push(format!(
+            " ORDER BY {} {}, id {} LIMIT ",
+            column, direction, direction
+        ))
+        .push_bind(limit);
+
+    let rows: Vec<ContractItemRow> = query
+        .build_query_as()
         .fetch_all(&pool)
         .await
-        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
-    };
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
 
     let items: Vec<GeneratedContractItem> = rows
         .into_iter()
@@ -335,6 +643,7 @@ pub async fn list_generated_contracts(
                 parameters,
                 template_id,
                 status,
+                error,
                 created_at,
                 updated_at,
             )| {
@@ -347,6 +656,7 @@ pub async fn list_generated_contracts(
                     parameters,
                     template_id,
                     status,
+                    error,
                     created_at,
                     updated_at,
                 }
@@ -356,11 +666,17 @@ pub async fn list_generated_contracts(
 
     let next_cursor = if items.len() as i64 == limit {
         items.last().map(|last| {
-            let c = GeneratedContractsCursor {
-                created_at: last.created_at,
-                id: last.id,
+            let key = match sort {
+                ContractSort::CreatedAt => last.created_at.to_rfc3339(),
+                ContractSort::UpdatedAt => last.updated_at.to_rfc3339(),
+                ContractSort::ContractName => last.contract_name.clone(),
             };
-            encode_cursor(&c)
+            encode_cursor(&GeneratedContractsCursor {
+                sort,
+                order,
+                key,
+                id: last.id,
+            })
         })
     } else {
         None
@@ -368,3 +684,390 @@ pub async fn list_generated_contracts(
 
     Ok(Json(GeneratedContractsListRes { items, next_cursor }))
 }
+
+/// Get a generated contract, with its code and parameters
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 200, description = "Generated contract", body = GenerateContractRes),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_generated_contract(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<GenerateContractRes>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    check_owner(&pool, id, &auth.wallet).await?;
+    fetch_contract(&pool, id)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound("contract not found"))
+}
+
+// Statuses an owner may set; the others follow generation and builds.
+const SETTABLE_STATUSES: &[&str] = &["generated", "archived"];
+
+// Tells an explicit `null` (`Some(None)`) from a missing field (`None`).
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateContractReq {
+    pub contract_name: Option<String>,
+    /// `null` clears the description
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    pub description: Option<Option<String>>,
+    /// `archived` to set the contract aside, `generated` to bring it back to
+    /// the status its deployments and builds give it
+    pub status: Option<String>,
+}
+
+/// Rename a generated contract or update its description or status
+///
+/// Fields left out are kept. Contracts still being generated cannot be
+/// changed. Only archiving touches the status: an unarchived contract is
+/// `deployed` once a deployment of it is confirmed, else `compiled` or
+/// `compile_failed` by the build of its current code, else `generated`.
+#[utoipa::path(
+    patch,
+    path = "/generated_contracts/{id}",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    request_body = UpdateContractReq,
+    responses(
+        (status = 200, description = "Updated contract", body = GenerateContractRes),
+        (status = 400, description = "Invalid field or nothing to update", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:write scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued, running or failed", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn update_generated_contract(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateContractReq>,
+) -> Result<Json<GenerateContractRes>, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+
+    if req.contract_name.is_none() && req.description.is_none() && req.status.is_none() {
+        return Err(ApiError::BadRequest("nothing to update"));
+    }
+    if let Some(ref name) = req.contract_name {
+        if name.trim().is_empty() {
+            return Err(ApiError::BadRequest("contract_name is required"));
+        }
+        if name.len() > 200 {
+            return Err(ApiError::BadRequest(
+                "contract_name must be less than 200 characters",
+            ));
+        }
+    }
+    if let Some(Some(ref desc)) = req.description {
+        if desc.len() > 1000 {
+            return Err(ApiError::BadRequest(
+                "description must be less than 1000 characters",
+            ));
+        }
+    }
+    if let Some(ref status) = req.status {
+        if !SETTABLE_STATUSES.contains(&status.as_str()) {
+            return Err(ApiError::BadRequest(
+                "status must be `generated` or `archived`",
+            ));
+        }
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    let (status,): (String,) = sqlx::query_as(
+        r#"SELECT g.status
+           FROM generated_contracts g
+           JOIN users u ON u.id = g.user_id
+           WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL
+           FOR UPDATE OF g"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if in_progress(&status) {
+        return Err(ApiError::Conflict("contract is being generated"));
+    }
+    if req.status.is_some() && !has_code(&status) {
+        return Err(ApiError::Conflict("contract has not been generated"));
+    }
+
+    sqlx::query(
+        r#"UPDATE generated_contracts g
+           SET contract_name = COALESCE($2, contract_name),
+               description = CASE WHEN $3 THEN $4 ELSE description END,
+               status = CASE
+                   WHEN $5 IS NULL THEN status
+                   WHEN $5 = 'archived' THEN 'archived'
+                   WHEN status <> 'archived' THEN status
+                   WHEN EXISTS (SELECT 1 FROM deployed_contracts d
+                                WHERE d.contract_id = g.id AND d.status = 'confirmed')
+                       THEN 'deployed'
+                   ELSE COALESCE(
+                       (SELECT CASE WHEN c.success THEN 'compiled' ELSE 'compile_failed' END
+                        FROM contract_compilations c
+                        WHERE c.contract_id = g.id
+                          AND c.revision IS NOT DISTINCT FROM
+                              (SELECT MAX(r.revision) FROM contract_revisions r
+                               WHERE r.contract_id = g.id)
+                        ORDER BY c.id DESC
+                        LIMIT 1),
+                       'generated')
+               END,
+               -- Without a manifest the package, and so the ABI's paths, follow the name
+               abi = CASE WHEN $2 IS NOT NULL AND scarb_config IS NULL THEN NULL ELSE abi END
+           WHERE g.id = $1"#,
+    )
+    .bind(id)
+    .bind(req.contract_name.as_deref().map(str::trim))
+    .bind(req.description.is_some())
+    .bind(req.description.flatten())
+    .bind(&req.status)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    tracing::info!("Updated contract {} of {}", id, auth.wallet);
+    fetch_contract(&pool, id)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound("contract not found"))
+}
+
+/// Delete a generated contract
+///
+/// The contract is hidden from every route but kept in the database; a
+/// generation still queued or running for it is cancelled.
+#[utoipa::path(
+    delete,
+    path = "/generated_contracts/{id}",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 204, description = "Contract deleted"),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:write scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn delete_contract(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    let result = sqlx::query(
+        r#"UPDATE generated_contracts g
+           SET deleted_at = NOW()
+           FROM users u
+           WHERE u.id = g.user_id AND g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("contract not found"));
+    }
+
+    sqlx::query(
+        r#"UPDATE generation_jobs
+           SET status = 'failed', last_error = 'contract deleted', updated_at = NOW()
+           WHERE contract_id = $1 AND status = 'queued'"#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    // Nothing will ever generate the contract now
+    sqlx::query(
+        r#"UPDATE generated_contracts
+           SET status = 'failed', error = 'contract deleted', updated_at = NOW()
+           WHERE id = $1 AND status = 'queued'"#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    tracing::info!("Deleted contract {} of {}", id, auth.wallet);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct ArchiveQuery {
+    /// `zip` (default) or `tar.gz`
+    pub format: Option<ArchiveFormat>,
+}
+
+// contract_name, contract_type, description, template_id, generated_code,
+// scarb_config, status, created_at
+type ArchiveRow = (
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    String,
+    Option<String>,
+    String,
+    DateTime<Utc>,
+);
+
+/// Download a generated contract as a Scarb project
+///
+/// The archive holds `Scarb.toml`, `src/lib.cairo`, a Starknet Foundry
+/// smoke test and a README under a directory named after the package, ready
+/// for `scarb build`.
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}/archive",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Generated contract id"), ArchiveQuery),
+    responses(
+        (status = 200, description = "Project archive", content(
+            (Vec<u8> = "application/zip"),
+            (Vec<u8> = "application/gzip")
+        )),
+        (status = 400, description = "Unknown format", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still queued or failed", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn download_archive(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Query(q): Query<ArchiveQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    let format = q.format.unwrap_or_default();
+
+    // Someone else's contract looks the same as a missing one
+    let (
+        contract_name,
+        contract_type,
+        description,
+        template_id,
+        code,
+        scarb_config,
+        status,
+        created_at,
+    ): ArchiveRow = sqlx::query_as(
+        r#"SELECT g.contract_name, g.contract_type, g.description, g.template_id,
+                      g.generated_code, g.scarb_config, g.status, g.created_at
+               FROM generated_contracts g
+               JOIN users u ON u.id = g.user_id
+               WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if !has_code(&status) {
+        return Err(ApiError::Conflict("contract has not been generated"));
+    }
+
+    // The stored manifest names the package the contract was built as,
+    // whatever the contract was renamed to since
+    let package = scarb_config
+        .as_deref()
+        .and_then(scarb::package_name_of)
+        .unwrap_or_else(|| scarb::package_name(&contract_name));
+    // The code may no longer match the contract's name: renamed since,
+    // edited by hand, or named otherwise by the LLM
+    let module =
+        cairo::contract_module(&code).unwrap_or_else(|| templates::module_name(&contract_name));
+    let template_name = template_id
+        .as_deref()
+        .and_then(templates::find)
+        .map(|t| t.name);
+    let description = scarb::package_description(description.as_deref(), template_name);
+    let cairo = scarb_config
+        .as_deref()
+        .and_then(scarb::cairo_version_of)
+        .map_or_else(
+            || scarb::parse_cairo_version(scarb::DEFAULT_CAIRO_VERSION),
+            Ok,
+        )?;
+
+    // The stored manifest only covers the contract; the test file also
+    // needs snforge as a dev-dependency. Contracts from before manifests
+    // were stored get a fresh one.
+    let test = archive::test_file(&module);
+    let manifest = match scarb_config.as_deref() {
+        Some(config) => scarb::add_dev_dependencies(config, &[&test], &cairo)?,
+        None => scarb::generate(&contract_name, &description, &[&code, &test], &cairo)?,
+    };
+
+    let project = archive::Project {
+        package: &package,
+        module: &module,
+        description: &description,
+        contract_type: &contract_type,
+        template_id: template_id.as_deref(),
+        code: &code,
+        created_at,
+    };
+    let files = archive::project_files(&project, manifest, &cairo.to_string());
+    let filename = format!("{}.{}", package, format.extension());
+    let body = Body::from_stream(archive::stream(format, package, files, created_at));
+
+    Ok((
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    ))
+}
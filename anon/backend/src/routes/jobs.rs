@@ -0,0 +1,307 @@
+use std::convert::Infallible;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::{Stream, wrappers::ReceiverStream};
+use utoipa::ToSchema;
+
+use crate::libs::{
+    api_keys::Scope,
+    db::AppState,
+    error::{ApiError, ErrorBody},
+    jobs::JobEvent,
+};
+use crate::middlewares::auth::AuthUser;
+use crate::routes::generate::{self, GenerateContractRes};
+
+#[derive(Serialize, ToSchema)]
+pub struct JobRes {
+    pub id: i64,
+    pub contract_id: i64,
+    /// `queued`, `running`, `succeeded` or `failed`
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// Why the last attempt failed; kept while a retry is pending
+    pub error: Option<String>,
+    /// Earliest time a queued job runs
+    pub run_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// The generated contract, once the job succeeded
+    pub contract: Option<GenerateContractRes>,
+}
+
+// id, contract_id, status, attempts, max_attempts, last_error, run_at,
+// created_at, updated_at
+type JobRow = (
+    i64,
+    i64,
+    String,
+    i32,
+    i32,
+    Option<String>,
+    DateTime<Utc>,
+    DateTime<Utc>,
+    DateTime<Utc>,
+);
+
+// Job `id` if it belongs to `wallet`.
+pub(crate) async fn find_job(
+    pool: &PgPool,
+    id: i64,
+    wallet: &str,
+) -> Result<Option<JobRes>, ApiError> {
+    let row: Option<JobRow> = sqlx::query_as(
+        r#"SELECT j.id, j.contract_id, j.status, j.attempts, j.max_attempts, j.last_error,
+                  j.run_at, j.created_at, j.updated_at
+           FROM generation_jobs j
+           JOIN generated_contracts g ON g.id = j.contract_id
+           JOIN users u ON u.id = g.user_id
+           WHERE j.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(wallet)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    let Some((
+        id,
+        contract_id,
+        status,
+        attempts,
+        max_attempts,
+        error,
+        run_at,
+        created_at,
+        updated_at,
+    )) = row
+    else {
+        return Ok(None);
+    };
+
+    let contract = if status == "succeeded" {
+        generate::fetch_contract(pool, contract_id).await?
+    } else {
+        None
+    };
+
+    Ok(Some(JobRes {
+        id,
+        contract_id,
+        status,
+        attempts,
+        max_attempts,
+        error,
+        run_at,
+        created_at,
+        updated_at,
+        contract,
+    }))
+}
+
+/// Get a generation job
+///
+/// Poll a job queued by `POST /generate` with `"async": true`. Failed
+/// attempts are retried with backoff; once the job succeeds the generated
+/// contract is included.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Job status", body = JobRes),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Job not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_job(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<JobRes>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    // Someone else's job looks the same as a missing one
+    find_job(&pool, id, &auth.wallet)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound("job not found"))
+}
+
+/// Payload of a `status` event
+#[derive(Serialize, ToSchema)]
+pub struct JobStatusEvent {
+    pub status: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+    pub run_at: DateTime<Utc>,
+}
+
+/// Payload of an `output` event
+#[derive(Serialize, ToSchema)]
+pub struct JobOutputEvent {
+    /// Next piece of generator output, e.g. LLM tokens
+    pub text: String,
+}
+
+fn event(name: &str, data: &impl Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .expect("job event serializes")
+}
+
+type EventSender = mpsc::Sender<Result<Event, Infallible>>;
+
+// Forward `job`'s progress to `tx` until it succeeds, fails or the client
+// goes away. Output comes from this process's workers; status changes are
+// also picked up by re-reading the job, so jobs run by another instance are
+// followed too.
+async fn follow_job(
+    pool: PgPool,
+    wallet: String,
+    mut job: JobRes,
+    mut updates: broadcast::Receiver<JobEvent>,
+    poll_interval: std::time::Duration,
+    tx: EventSender,
+) {
+    let mut last = None;
+    loop {
+        if last != Some((job.status.clone(), job.attempts)) {
+            last = Some((job.status.clone(), job.attempts));
+            let status = JobStatusEvent {
+                status: job.status.clone(),
+                attempts: job.attempts,
+                error: job.error.clone(),
+                run_at: job.run_at,
+            };
+            if tx.send(Ok(event("status", &status))).await.is_err() {
+                return;
+            }
+        }
+        match job.status.as_str() {
+            "succeeded" => {
+                if let Some(contract) = &job.contract {
+                    let _ = tx.send(Ok(event("done", contract))).await;
+                }
+                return;
+            }
+            "failed" => {
+                let body = ErrorBody {
+                    error: job.error.clone().unwrap_or_default(),
+                    details: None,
+                };
+                let _ = tx.send(Ok(event("failed", &body))).await;
+                return;
+            }
+            _ => {}
+        }
+
+        loop {
+            tokio::select! {
+                update = updates.recv() => match update {
+                    Ok(JobEvent::Output(text)) => {
+                        if tx.send(Ok(event("output", &JobOutputEvent { text }))).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(JobEvent::Compiled(report)) => {
+                        if tx.send(Ok(event("compile", &report))).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(JobEvent::Audited(report)) => {
+                        if tx.send(Ok(event("audit", &report))).await.is_err() {
+                            return;
+                        }
+                    }
+                    // Missed events only cost output; the job is re-read
+                    Ok(JobEvent::Updated) | Err(broadcast::error::RecvError::Lagged(_)) => break,
+                    Err(broadcast::error::RecvError::Closed) => {
+                        tokio::time::sleep(poll_interval).await;
+                        break;
+                    }
+                },
+                _ = tokio::time::sleep(poll_interval) => break,
+                _ = tx.closed() => return,
+            }
+        }
+
+        job = match find_job(&pool, job.id, &wallet).await {
+            Ok(Some(job)) => job,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!("Failed to reload job {} for its event stream", job.id);
+                // Tell the client the stream ends on our side, not the job
+                let body = ErrorBody {
+                    error: e.message().to_string(),
+                    details: None,
+                };
+                let _ = tx.send(Ok(event("error", &body))).await;
+                return;
+            }
+        };
+    }
+}
+
+/// Stream a generation job's progress
+///
+/// Server-Sent Events: `status` (`JobStatusEvent`) whenever the job changes,
+/// `output` (`JobOutputEvent`) with generator output as it is written,
+/// `audit` (`AuditReport`) with the findings on the generated code,
+/// `compile` (`CompileReport`) when a compiler is configured, then `done`
+/// with the stored `GenerateContractRes` or `failed` with an
+/// `ErrorBody`, after which the stream ends. `error` (`ErrorBody`) ends it
+/// early when the server cannot follow the job any more. A finished job
+/// answers with its final events right away.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}/events",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Event stream", content_type = "text/event-stream", body = String),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Job not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn job_events(
+    State(AppState {
+        pool, jobs: queue, ..
+    }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    // Subscribe before reading the job so no change falls in between
+    let updates = queue.events.subscribe(id);
+    let job = find_job(&pool, id, &auth.wallet)
+        .await?
+        .ok_or(ApiError::NotFound("job not found"))?;
+
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(follow_job(
+        pool,
+        auth.wallet,
+        job,
+        updates,
+        queue.poll_interval,
+        tx,
+    ));
+    Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default()))
+}
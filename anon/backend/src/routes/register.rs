@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 use crate::libs::{db::AppState, error::ApiError, wallet};
+use crate::middlewares::auth::AuthUser;
 
 #[derive(Deserialize, ToSchema)]
 pub struct RegisterReq {
@@ -17,23 +18,34 @@ pub struct RegisterRes {
 }
 
 /// Register a new user by Starknet wallet address
+///
+/// Requires a token from `/auth/verify` proving ownership of the wallet.
 #[utoipa::path(
     post,
     path = "/register",
     tag = "auth",
+    security(("bearer_auth" = [])),
+    params(("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key get the first response back")),
     request_body = RegisterReq,
     responses(
         (status = 201, description = "User registered", body = RegisterRes),
         (status = 400, description = "Invalid request", body = crate::libs::error::ErrorBody),
-        (status = 409, description = "Wallet already registered", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Wallet does not match token", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Wallet already registered, or a request with the same idempotency key is in progress", body = crate::libs::error::ErrorBody),
+        (status = 422, description = "Idempotency key used for a different request", body = crate::libs::error::ErrorBody),
         (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
     )
 )]
 pub async fn register(
-    State(AppState { pool }): State<AppState>,
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
     Json(req): Json<RegisterReq>,
 ) -> Result<impl IntoResponse, ApiError> {
     let normalized_wallet = wallet::normalize_and_validate(&req.wallet)?;
+    if normalized_wallet != auth.wallet {
+        return Err(ApiError::Forbidden("wallet does not match token"));
+    }
 
     let mut tx = pool
         .begin()
@@ -56,7 +56,7 @@ type ReviewRow = (
     )
 )]
 pub async fn list_reviews(
-    State(AppState { pool }): State<AppState>,
+    State(AppState { pool, .. }): State<AppState>,
     Query(q): Query<ReviewsQuery>,
 ) -> Result<Json<ReviewsListRes>, ApiError> {
     let limit = q.limit.unwrap_or(20).clamp(1, 50);
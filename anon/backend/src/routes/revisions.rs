@@ -0,0 +1,448 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State, rejection::QueryRejection},
+    http::header::CONTENT_TYPE,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::libs::{
+    api_keys::Scope,
+    db::AppState,
+    error::ApiError,
+    generator::{GenerationRequest, GeneratorKind},
+    revisions::{self, Revision, Source},
+    scarb, templates,
+};
+use crate::middlewares::auth::AuthUser;
+use crate::routes::generate::{check_owner, in_progress};
+
+/// Hand-edited code larger than this is rejected
+const MAX_CODE_BYTES: usize = 256 * 1024;
+
+// Cairo version the contract's manifest targets, or the default one.
+fn cairo_version(scarb_config: Option<&str>) -> Result<Version, ApiError> {
+    scarb_config.and_then(scarb::cairo_version_of).map_or_else(
+        || scarb::parse_cairo_version(scarb::DEFAULT_CAIRO_VERSION),
+        Ok,
+    )
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RevisionItem {
+    pub revision: i32,
+    /// `generated`, `regenerated` or `edited`
+    pub source: String,
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RevisionsListRes {
+    /// Oldest first
+    pub items: Vec<RevisionItem>,
+}
+
+/// List the revisions of a contract
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}/revisions",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    responses(
+        (status = 200, description = "Revisions, oldest first", body = RevisionsListRes),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn list_revisions(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<RevisionsListRes>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    check_owner(&pool, id, &auth.wallet).await?;
+
+    let rows: Vec<(i32, String, Option<String>, DateTime<Utc>)> = sqlx::query_as(
+        r#"SELECT revision, source, message, created_at
+           FROM contract_revisions
+           WHERE contract_id = $1
+           ORDER BY revision"#,
+    )
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    let items = rows
+        .into_iter()
+        .map(|(revision, source, message, created_at)| RevisionItem {
+            revision,
+            source,
+            message,
+            created_at,
+        })
+        .collect();
+    Ok(Json(RevisionsListRes { items }))
+}
+
+async fn find_revision(
+    pool: &sqlx::PgPool,
+    id: i64,
+    revision: Option<i32>,
+) -> Result<Revision, ApiError> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    revisions::find(&mut conn, id, revision)
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+        .ok_or(ApiError::NotFound("revision not found"))
+}
+
+/// Get one revision of a contract, with its code
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}/revisions/{revision}",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(
+        ("id" = i64, Path, description = "Generated contract id"),
+        ("revision" = i32, Path, description = "Revision number")
+    ),
+    responses(
+        (status = 200, description = "Revision", body = Revision),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract or revision not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_revision(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path((id, revision)): Path<(i64, i32)>,
+) -> Result<Json<Revision>, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    check_owner(&pool, id, &auth.wallet).await?;
+    find_revision(&pool, id, Some(revision)).await.map(Json)
+}
+
+#[derive(Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct DiffQuery {
+    /// Old revision
+    pub from: i32,
+    /// New revision; the latest by default
+    pub to: Option<i32>,
+}
+
+/// Diff two revisions of a contract
+///
+/// Unified diff of `src/lib.cairo` from revision `from` to `to`; empty when
+/// the code is the same.
+#[utoipa::path(
+    get,
+    path = "/generated_contracts/{id}/diff",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:read"])),
+    params(("id" = i64, Path, description = "Generated contract id"), DiffQuery),
+    responses(
+        (status = 200, description = "Unified diff", content_type = "text/x-diff", body = String),
+        (status = 400, description = "Missing or invalid revision numbers", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:read scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract or revision not found", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn diff_revisions(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    q: Result<Query<DiffQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    auth.require_scope(Scope::ContractsRead)?;
+    let Query(q) = q.map_err(|_| ApiError::BadRequest("from and to must be revision numbers"))?;
+    check_owner(&pool, id, &auth.wallet).await?;
+
+    let from = find_revision(&pool, id, Some(q.from)).await?;
+    let to = find_revision(&pool, id, q.to).await?;
+    Ok((
+        [(CONTENT_TYPE, "text/x-diff; charset=utf-8")],
+        revisions::diff(&from, &to),
+    ))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateCodeReq {
+    pub generated_code: String,
+    /// What changed, kept with the revision
+    pub message: Option<String>,
+}
+
+// contract_name, description, template_id, scarb_config, status
+type EditRow = (
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+);
+
+/// Replace a contract's code by hand
+///
+/// Stores the code as a new `edited` revision, with a Scarb.toml matching
+/// its imports, and makes it the current code. The contract goes back to
+/// `generated` until it is built again, unless it is deployed or archived.
+#[utoipa::path(
+    put,
+    path = "/generated_contracts/{id}/code",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(("id" = i64, Path, description = "Generated contract id")),
+    request_body = UpdateCodeReq,
+    responses(
+        (status = 200, description = "New revision", body = Revision),
+        (status = 400, description = "Empty or oversized code", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:write scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still in progress", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn update_code(
+    State(AppState { pool, .. }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateCodeReq>,
+) -> Result<Json<Revision>, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+
+    if req.generated_code.trim().is_empty() {
+        return Err(ApiError::BadRequest("generated_code is required"));
+    }
+    if req.generated_code.len() > MAX_CODE_BYTES {
+        return Err(ApiError::BadRequest(
+            "generated_code must be less than 256 KiB",
+        ));
+    }
+    if req.message.as_ref().is_some_and(|m| m.len() > 500) {
+        return Err(ApiError::BadRequest(
+            "message must be less than 500 characters",
+        ));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    let (contract_name, description, template_id, scarb_config, status): EditRow = sqlx::query_as(
+        r#"SELECT g.contract_name, g.description, g.template_id, g.scarb_config, g.status
+               FROM generated_contracts g
+               JOIN users u ON u.id = g.user_id
+               WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL
+               FOR UPDATE OF g"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if in_progress(&status) {
+        return Err(ApiError::Conflict("contract is being generated"));
+    }
+
+    let template_name = template_id
+        .as_deref()
+        .and_then(templates::find)
+        .map(|t| t.name);
+    let scarb_config = scarb::generate(
+        &contract_name,
+        &scarb::package_description(description.as_deref(), template_name),
+        &[&req.generated_code],
+        &cairo_version(scarb_config.as_deref())?,
+    )?;
+
+    let revision = revisions::add(
+        &mut tx,
+        id,
+        Source::Edited,
+        &req.generated_code,
+        Some(&scarb_config),
+        req.message.as_deref(),
+    )
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    tracing::info!("Contract {} edited as revision {}", id, revision.revision);
+    Ok(Json(revision))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RegenerateReq {
+    /// New template parameters; the stored ones if omitted
+    pub parameters: Option<Value>,
+    /// New description; the stored one if omitted
+    pub description: Option<String>,
+    /// Cairo version to target; the one of the current Scarb.toml if omitted
+    pub cairo_version: Option<String>,
+    /// Generator to use; the one that wrote the contract if omitted
+    pub generator: Option<GeneratorKind>,
+}
+
+// contract_type, contract_name, description, parameters, template_id,
+// generator, scarb_config, status
+type RegenerateRow = (
+    String,
+    String,
+    Option<String>,
+    Option<Value>,
+    Option<String>,
+    String,
+    Option<String>,
+    String,
+);
+
+/// Regenerate a contract in place
+///
+/// Runs the generator again, optionally with new parameters, and stores the
+/// result as a new `regenerated` revision of the same contract.
+#[utoipa::path(
+    post,
+    path = "/generated_contracts/{id}/regenerate",
+    tag = "contracts",
+    security(("bearer_auth" = []), ("api_key" = ["contracts:write"])),
+    params(
+        ("id" = i64, Path, description = "Generated contract id"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key get the first response back")
+    ),
+    request_body = RegenerateReq,
+    responses(
+        (status = 200, description = "New revision", body = Revision),
+        (status = 400, description = "Invalid parameters or unsupported cairo_version", body = crate::libs::error::ErrorBody),
+        (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
+        (status = 403, description = "Missing contracts:write scope", body = crate::libs::error::ErrorBody),
+        (status = 404, description = "Contract not found", body = crate::libs::error::ErrorBody),
+        (status = 409, description = "Generation still in progress, or a request with the same idempotency key is", body = crate::libs::error::ErrorBody),
+        (status = 422, description = "Idempotency key used for a different request", body = crate::libs::error::ErrorBody),
+        (status = 500, description = "Internal error", body = crate::libs::error::ErrorBody),
+        (status = 502, description = "LLM reply unusable", body = crate::libs::error::ErrorBody),
+        (status = 503, description = "LLM generator unavailable or not configured", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn regenerate_contract(
+    State(AppState {
+        pool, generators, ..
+    }): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<RegenerateReq>,
+) -> Result<Json<Revision>, ApiError> {
+    auth.require_scope(Scope::ContractsWrite)?;
+
+    if req.description.as_ref().is_some_and(|d| d.len() > 1000) {
+        return Err(ApiError::BadRequest(
+            "description must be less than 1000 characters",
+        ));
+    }
+
+    let (
+        contract_type,
+        contract_name,
+        description,
+        parameters,
+        template_id,
+        generator,
+        scarb_config,
+        status,
+    ): RegenerateRow = sqlx::query_as(
+        r#"SELECT g.contract_type, g.contract_name, g.description, g.parameters,
+                  g.template_id, g.generator, g.scarb_config, g.status
+           FROM generated_contracts g
+           JOIN users u ON u.id = g.user_id
+           WHERE g.id = $1 AND u.wallet = $2 AND g.deleted_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(&auth.wallet)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?
+    .ok_or(ApiError::NotFound("contract not found"))?;
+    if in_progress(&status) {
+        return Err(ApiError::Conflict("contract is being generated"));
+    }
+
+    let cairo = match req.cairo_version.as_deref() {
+        Some(v) => scarb::parse_cairo_version(v)?,
+        None => cairo_version(scarb_config.as_deref())?,
+    };
+    if !templates::supports_cairo(&cairo) {
+        return Err(ApiError::BadRequest("unsupported cairo_version"));
+    }
+
+    let description = req.description.or(description);
+    let parameters = req.parameters.or(parameters);
+    let template = template_id.as_deref().and_then(templates::find);
+    let kind = generators.select(
+        req.generator.or_else(|| GeneratorKind::parse(&generator)),
+        template,
+    );
+    let generation = GenerationRequest {
+        contract_type: &contract_type,
+        template,
+        contract_name: &contract_name,
+        description: description.as_deref(),
+        parameters: parameters.as_ref(),
+    };
+    let out = generators.write(kind, &generation, &cairo, None).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    sqlx::query(
+        r#"UPDATE generated_contracts
+           SET description = $2, parameters = $3, generator = $4
+           WHERE id = $1"#,
+    )
+    .bind(id)
+    .bind(&description)
+    .bind(&parameters)
+    .bind(kind.as_str())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    let revision = revisions::add(
+        &mut tx,
+        id,
+        Source::Regenerated,
+        &out.code,
+        Some(&out.scarb_config),
+        None,
+    )
+    .await
+    .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+    tx.commit()
+        .await
+        .map_err(|e| crate::libs::error::map_sqlx_error(&e))?;
+
+    tracing::info!(
+        "Contract {} regenerated as revision {}",
+        id,
+        revision.revision
+    );
+    Ok(Json(revision))
+}
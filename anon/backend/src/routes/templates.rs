@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use axum::{
+    extract::Path,
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use crate::libs::{
+    error::ApiError,
+    templates::{self, CAIRO_VERSION_REQ, TEMPLATES, Template},
+};
+
+// Contract name used for the example renderings
+const EXAMPLE_CONTRACT_NAME: &str = "MyContract";
+
+#[derive(Serialize, ToSchema)]
+pub struct TemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub version: String,
+    /// Semver requirement on the Cairo compiler
+    pub cairo_versions: String,
+    /// Other `contract_type` values accepted by `/generate` for this template
+    pub aliases: Vec<String>,
+    /// JSON Schema of `parameters`
+    #[schema(value_type = Object)]
+    pub parameters_schema: serde_json::Value,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TemplatesListRes {
+    pub items: Vec<TemplateSummary>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TemplateExample {
+    pub contract_name: String,
+    /// Parameters after defaults were applied
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Value,
+    pub code: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TemplateDetail {
+    #[serde(flatten)]
+    pub template: TemplateSummary,
+    pub example: TemplateExample,
+}
+
+fn summary(t: &Template) -> TemplateSummary {
+    TemplateSummary {
+        id: t.id.to_string(),
+        name: t.name.to_string(),
+        description: t.description.to_string(),
+        version: t.version.to_string(),
+        cairo_versions: CAIRO_VERSION_REQ.to_string(),
+        aliases: t.aliases.iter().map(|a| a.to_string()).collect(),
+        parameters_schema: t.parameters_schema(),
+    }
+}
+
+fn detail(t: &Template) -> TemplateDetail {
+    let given = t.example_parameters();
+    let parameters =
+        templates::resolve_params(t, Some(&given)).expect("template examples are valid");
+    let code = templates::render(t, EXAMPLE_CONTRACT_NAME, Some(&given))
+        .expect("template renders with examples");
+    TemplateDetail {
+        template: summary(t),
+        example: TemplateExample {
+            contract_name: EXAMPLE_CONTRACT_NAME.to_string(),
+            parameters: parameters.into(),
+            code,
+        },
+    }
+}
+
+// The catalog only changes with a deploy, so bodies and their ETags are
+// built once per process.
+struct Cached {
+    etag: String,
+    body: String,
+}
+
+impl Cached {
+    fn new(value: &impl Serialize) -> Self {
+        let body = serde_json::to_string(value).expect("catalog serializes");
+        let digest = Sha256::digest(body.as_bytes());
+        let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+        Cached {
+            etag: format!("\"{}\"", hex),
+            body,
+        }
+    }
+
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get_all(IF_NONE_MATCH)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == self.etag)
+    }
+
+    fn respond(&self, headers: &HeaderMap) -> Response {
+        let etag = HeaderValue::from_str(&self.etag).expect("etag is a valid header");
+        let cache_control = HeaderValue::from_static("public, max-age=300");
+        if self.matches(headers) {
+            return (
+                StatusCode::NOT_MODIFIED,
+                [(ETAG, etag), (CACHE_CONTROL, cache_control)],
+            )
+                .into_response();
+        }
+        (
+            [
+                (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+                (ETAG, etag),
+                (CACHE_CONTROL, cache_control),
+            ],
+            self.body.clone(),
+        )
+            .into_response()
+    }
+}
+
+fn catalog() -> &'static Cached {
+    static CATALOG: OnceLock<Cached> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        Cached::new(&TemplatesListRes {
+            items: TEMPLATES.iter().map(summary).collect(),
+        })
+    })
+}
+
+fn details() -> &'static HashMap<&'static str, Cached> {
+    static DETAILS: OnceLock<HashMap<&'static str, Cached>> = OnceLock::new();
+    DETAILS.get_or_init(|| {
+        TEMPLATES
+            .iter()
+            .map(|t| (t.id, Cached::new(&detail(t))))
+            .collect()
+    })
+}
+
+/// List contract templates
+///
+/// Every template `/generate` can render, with its parameter schema.
+/// Responses carry an `ETag`; send it back in `If-None-Match` to get a 304.
+#[utoipa::path(
+    get,
+    path = "/templates",
+    tag = "templates",
+    params(("If-None-Match" = Option<String>, Header, description = "ETag of a cached catalog")),
+    responses(
+        (status = 200, description = "Template catalog", body = TemplatesListRes),
+        (status = 304, description = "Catalog unchanged")
+    )
+)]
+pub async fn list_templates(headers: HeaderMap) -> Response {
+    catalog().respond(&headers)
+}
+
+/// Get a contract template
+///
+/// Includes an example rendering with default parameters.
+#[utoipa::path(
+    get,
+    path = "/templates/{id}",
+    tag = "templates",
+    params(
+        ("id" = String, Path, description = "Template id, e.g. `erc20`"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a cached template")
+    ),
+    responses(
+        (status = 200, description = "Template", body = TemplateDetail),
+        (status = 304, description = "Template unchanged"),
+        (status = 404, description = "Template not found", body = crate::libs::error::ErrorBody)
+    )
+)]
+pub async fn get_template(
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let cached = details()
+        .get(id.as_str())
+        .ok_or(ApiError::NotFound("template not found"))?;
+    Ok(cached.respond(&headers))
+}
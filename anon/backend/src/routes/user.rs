@@ -24,7 +24,7 @@ pub struct UserMeRes {
     get,
     path = "/user",
     tag = "auth",
-    security(("bearer_auth" = [])),
+    security(("bearer_auth" = []), ("api_key" = [])),
     responses(
         (status = 200, description = "Current user", body = UserMeRes),
         (status = 401, description = "Unauthorized", body = crate::libs::error::ErrorBody),
@@ -33,8 +33,8 @@ pub struct UserMeRes {
     )
 )]
 pub async fn me(
-    State(AppState { pool }): State<AppState>,
-    AuthUser { wallet }: AuthUser,
+    State(AppState { pool, .. }): State<AppState>,
+    AuthUser { wallet, .. }: AuthUser,
 ) -> Result<Json<UserMeRes>, ApiError> {
     // Fetch user and profile
     let rec = sqlx::query!(
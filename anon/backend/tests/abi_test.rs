@@ -0,0 +1,230 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+
+use common::{create_test_server, create_user};
+
+const VAULT: &str = r#"#[starknet::interface]
+pub trait IVault<TContractState> {
+    fn deposit(ref self: TContractState, amount: u256);
+    fn balance_of(self: @TContractState, account: starknet::ContractAddress) -> u256;
+}
+
+#[starknet::contract]
+mod Vault {
+    use starknet::storage::Map;
+    use starknet::{ContractAddress, get_caller_address};
+
+    #[storage]
+    struct Storage {
+        balances: Map<ContractAddress, u256>,
+    }
+
+    #[abi(embed_v0)]
+    impl VaultImpl of super::IVault<ContractState> {
+        fn deposit(ref self: ContractState, amount: u256) {}
+
+        fn balance_of(self: @ContractState, account: ContractAddress) -> u256 {
+            self.balances.read(account)
+        }
+    }
+}
+"#;
+
+const TOKEN: &str = r#"#[starknet::contract]
+mod TestToken {
+    use starknet::ContractAddress;
+
+    #[storage]
+    struct Storage {}
+
+    #[constructor]
+    fn constructor(
+        ref self: ContractState,
+        name: ByteArray,
+        initial_supply: u256,
+        recipient: ContractAddress,
+    ) {}
+}
+"#;
+
+// Generates a counter contract and returns its id
+async fn generate(server: &TestServer, token: &str) -> i64 {
+    let body: Value = server
+        .post("/generate")
+        .authorization_bearer(token)
+        .json(&json!({"contract_type": "counter", "contract_name": "Tally"}))
+        .await
+        .json();
+    body["contract_id"].as_i64().unwrap()
+}
+
+fn names<'a>(abi: &'a Value, kind: &str) -> Vec<&'a str> {
+    abi["abi"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["type"] == kind)
+        .map(|e| e["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_abi_from_source() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/abi", id);
+
+    // Extracted when the code is stored
+    let (stored,): (Option<Value>,) =
+        sqlx::query_as("SELECT abi FROM generated_contracts WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(stored.is_some());
+
+    let response = server.get(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let abi: Value = response.json();
+    assert_eq!(abi["contract_id"], id);
+    assert_eq!(abi["source"], "source");
+    assert_eq!(
+        names(&abi, "function"),
+        vec!["get_count", "increment", "decrement", "reset"]
+    );
+    assert_eq!(names(&abi, "constructor"), vec!["constructor"]);
+    assert_eq!(
+        abi["storage"][0],
+        json!({"name": "count", "type": "core::integer::u64"})
+    );
+
+    // A hand edit replaces it
+    let response = server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&token)
+        .json(&json!({"generated_code": VAULT}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let abi: Value = server.get(&path).authorization_bearer(&token).await.json();
+    assert_eq!(names(&abi, "impl"), vec!["VaultImpl"]);
+    assert_eq!(names(&abi, "interface"), vec!["tally::IVault"]);
+    assert_eq!(
+        abi["abi"][1]["items"][1]["inputs"][0]["type"],
+        "core::starknet::contract_address::ContractAddress"
+    );
+    assert_eq!(
+        abi["storage"],
+        json!([{
+            "name": "balances",
+            "type": "core::starknet::storage::map::Map::<core::starknet::contract_address::ContractAddress, core::integer::u256>"
+        }])
+    );
+
+    // Contracts stored before ABIs were get theirs on first read
+    sqlx::query("UPDATE generated_contracts SET abi = NULL WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let again: Value = server.get(&path).authorization_bearer(&token).await.json();
+    assert_eq!(again, abi);
+    let (stored,): (Option<Value>,) =
+        sqlx::query_as("SELECT abi FROM generated_contracts WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(stored.unwrap()["abi"], abi["abi"]);
+}
+
+#[tokio::test]
+async fn test_abi_errors() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let other = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/abi", id);
+
+    let response = server.get(&path).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server.get(&path).authorization_bearer(&other).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    sqlx::query("UPDATE generated_contracts SET status = 'failed' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = server.get(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_constructor_calldata() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/calldata", id);
+
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"args": {"owner": "0x0123"}}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["contract_id"], id);
+    assert_eq!(body["abi_source"], "source");
+    assert_eq!(body["calldata"], json!(["0x123"]));
+
+    server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&token)
+        .json(&json!({"generated_code": TOKEN}))
+        .await;
+    let body: Value = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"args": {
+            "name": "TestToken",
+            "initial_supply": "1000000000000000000",
+            "recipient": "0x42"
+        }}))
+        .await
+        .json();
+    assert_eq!(
+        body["calldata"],
+        json!([
+            "0x0",
+            "0x54657374546f6b656e",
+            "0x9",
+            "0xde0b6b3a7640000",
+            "0x0",
+            "0x42"
+        ])
+    );
+
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"args": {"name": "TestToken", "initial_supply": 1.5}}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "validation failed");
+    assert_eq!(
+        body["details"],
+        json!([
+            {
+                "field": "args.initial_supply",
+                "message": "must be an unsigned integer (number, decimal or 0x string)"
+            },
+            {"field": "args.recipient", "message": "is required"}
+        ])
+    );
+}
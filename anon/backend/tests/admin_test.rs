@@ -0,0 +1,170 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use backend::libs::{
+    jwt::{self, KeyRing},
+    roles::Role,
+    session,
+};
+use common::{create_test_server, token_for};
+
+// Registered user holding `roles`; returns `(id, wallet)`
+async fn create_user(pool: &PgPool, roles: &[&str]) -> (i64, String) {
+    let raw_wallet = format!("0x{:060x}", rand::random::<u128>());
+    let wallet = backend::libs::wallet::normalize_and_validate(&raw_wallet).unwrap();
+
+    let (id,): (i64,) =
+        sqlx::query_as("INSERT INTO users (wallet, roles) VALUES ($1, $2) RETURNING id")
+            .bind(&wallet)
+            .bind(roles)
+            .fetch_one(pool)
+            .await
+            .expect("Failed to create test user");
+    (id, wallet)
+}
+
+#[tokio::test]
+async fn test_issued_tokens_carry_roles() {
+    let (_, pool) = create_test_server().await;
+    let (_, wallet) = create_user(&pool, &["admin"]).await;
+
+    let token = token_for(&pool, &wallet).await;
+    let claims = jwt::decode(&token, &KeyRing::from_env().unwrap()).unwrap();
+    assert_eq!(claims.roles, vec![Role::Admin]);
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_admin_role() {
+    let (server, pool) = create_test_server().await;
+    let (_, wallet) = create_user(&pool, &[]).await;
+    let token = token_for(&pool, &wallet).await;
+
+    let response = server
+        .get("/admin/users")
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server.get("/admin/users").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_admin_lists_users() {
+    let (server, pool) = create_test_server().await;
+    let (admin_id, admin_wallet) = create_user(&pool, &["admin"]).await;
+    let token = token_for(&pool, &admin_wallet).await;
+
+    let response = server
+        .get("/admin/users")
+        .authorization_bearer(&token)
+        .add_query_param("limit", 100)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let body: Value = response.json();
+    let admin = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|u| u["id"] == admin_id)
+        .expect("admin listed");
+    assert_eq!(admin["roles"], serde_json::json!(["admin"]));
+
+    let page: Value = server
+        .get("/admin/users")
+        .authorization_bearer(&token)
+        .add_query_param("limit", 1)
+        .await
+        .json();
+    assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    let next: Value = server
+        .get("/admin/users")
+        .authorization_bearer(&token)
+        .add_query_param("cursor", page["next_cursor"].as_str().unwrap())
+        .await
+        .json();
+    assert!(next["items"][0]["id"].as_i64() < page["items"][0]["id"].as_i64());
+}
+
+#[tokio::test]
+async fn test_disabled_user_is_locked_out() {
+    let (server, pool) = create_test_server().await;
+    let (_, admin_wallet) = create_user(&pool, &["admin"]).await;
+    let (user_id, user_wallet) = create_user(&pool, &[]).await;
+    let admin_token = token_for(&pool, &admin_wallet).await;
+    let user_token = token_for(&pool, &user_wallet).await;
+
+    let response = server
+        .post(&format!("/admin/users/{}/disable", user_id))
+        .authorization_bearer(&admin_token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    assert!(body["disabled_at"].is_string());
+
+    let response = server.get("/user").authorization_bearer(&user_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "account disabled");
+
+    // No fresh tokens either
+    let keys = KeyRing::from_env().unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    assert!(
+        session::issue(&mut conn, &keys, &user_wallet, None)
+            .await
+            .is_err()
+    );
+
+    let response = server
+        .post(&format!("/admin/users/{}/enable", user_id))
+        .authorization_bearer(&admin_token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server.get("/user").authorization_bearer(&user_token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_admin_cannot_disable_self() {
+    let (server, pool) = create_test_server().await;
+    let (admin_id, admin_wallet) = create_user(&pool, &["admin"]).await;
+    let token = token_for(&pool, &admin_wallet).await;
+
+    let response = server
+        .post(&format!("/admin/users/{}/disable", admin_id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_admin_deletes_review() {
+    let (server, pool) = create_test_server().await;
+    let (_, admin_wallet) = create_user(&pool, &["admin"]).await;
+    let token = token_for(&pool, &admin_wallet).await;
+
+    let (review_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO reviews (company, sentiment, body) VALUES ('acme', 1.0, 'spam') RETURNING id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let response = server
+        .delete(&format!("/admin/reviews/{}", review_id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .delete(&format!("/admin/reviews/{}", review_id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use backend::libs::jwt::KeyRing;
+use common::create_test_server;
+
+// Registered user with a wallet session; returns `(wallet, jwt)`
+async fn create_user_with_token(pool: &PgPool) -> (String, String) {
+    let raw_wallet = format!("0x{:060x}", rand::random::<u128>());
+    let wallet = backend::libs::wallet::normalize_and_validate(&raw_wallet).unwrap();
+
+    sqlx::query("INSERT INTO users (wallet) VALUES ($1)")
+        .bind(&wallet)
+        .execute(pool)
+        .await
+        .expect("Failed to create test user");
+
+    let keys = KeyRing::from_env().unwrap();
+    let token = backend::libs::jwt::encode(&wallet, &keys).unwrap();
+    (wallet, token)
+}
+
+async fn create_key(server: &TestServer, token: &str, scopes: Value) -> Value {
+    let response = server
+        .post("/api_keys")
+        .authorization_bearer(token)
+        .json(&json!({ "name": "ci", "scopes": scopes }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    response.json()
+}
+
+#[tokio::test]
+async fn test_create_api_key_returns_key_once() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_user_with_token(&pool).await;
+
+    let created = create_key(&server, &token, json!(["contracts:read"])).await;
+    let key = created["key"].as_str().unwrap();
+    let prefix = created["prefix"].as_str().unwrap();
+    assert!(key.starts_with(&format!("sfk_{}_", prefix)));
+    assert_eq!(created["scopes"], json!(["contracts:read"]));
+
+    // Only the hash is stored
+    let (stored,): (String,) = sqlx::query_as("SELECT key_hash FROM api_keys WHERE prefix = $1")
+        .bind(prefix)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_ne!(stored, key);
+
+    let list: Value = server
+        .get("/api_keys")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    let items = list["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["prefix"], prefix);
+    assert!(items[0].get("key").is_none());
+}
+
+#[tokio::test]
+async fn test_create_api_key_rejects_unknown_scope() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_user_with_token(&pool).await;
+
+    let response = server
+        .post("/api_keys")
+        .authorization_bearer(&token)
+        .json(&json!({ "name": "ci", "scopes": ["admin"] }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "unknown scope");
+}
+
+#[tokio::test]
+async fn test_api_key_authenticates_with_its_scopes() {
+    let (server, pool) = create_test_server().await;
+    let (wallet, token) = create_user_with_token(&pool).await;
+
+    let read = create_key(&server, &token, json!(["contracts:read"])).await;
+    let response = server
+        .get("/generated_contracts")
+        .add_header("x-api-key", read["key"].as_str().unwrap())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let me: Value = server
+        .get("/user")
+        .add_header("x-api-key", read["key"].as_str().unwrap())
+        .await
+        .json();
+    assert_eq!(me["wallet"], wallet);
+
+    let (last_used,): (Option<chrono::DateTime<chrono::Utc>>,) =
+        sqlx::query_as("SELECT last_used_at FROM api_keys WHERE id = $1")
+            .bind(read["id"].as_i64().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(last_used.is_some());
+
+    let write = create_key(&server, &token, json!(["reviews:write"])).await;
+    let response = server
+        .get("/generated_contracts")
+        .add_header("x-api-key", write["key"].as_str().unwrap())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_api_key_cannot_manage_keys() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_user_with_token(&pool).await;
+    let created = create_key(&server, &token, json!(["contracts:read"])).await;
+
+    let response = server
+        .post("/api_keys")
+        .add_header("x-api-key", created["key"].as_str().unwrap())
+        .json(&json!({ "name": "escalate", "scopes": ["contracts:write"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_revoked_api_key_is_rejected() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_user_with_token(&pool).await;
+    let created = create_key(&server, &token, json!(["contracts:read"])).await;
+
+    let response = server
+        .delete(&format!("/api_keys/{}", created["id"]))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get("/generated_contracts")
+        .add_header("x-api-key", created["key"].as_str().unwrap())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "api key revoked");
+}
+
+#[tokio::test]
+async fn test_cannot_revoke_other_users_key() {
+    let (server, pool) = create_test_server().await;
+    let (_, owner_token) = create_user_with_token(&pool).await;
+    let (_, other_token) = create_user_with_token(&pool).await;
+    let created = create_key(&server, &owner_token, json!(["contracts:read"])).await;
+
+    let response = server
+        .delete(&format!("/api_keys/{}", created["id"]))
+        .authorization_bearer(&other_token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invalid_api_key_is_rejected() {
+    let (server, _pool) = create_test_server().await;
+
+    let response = server
+        .get("/generated_contracts")
+        .add_header("x-api-key", "sfk_000000000000_nope")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use common::{create_test_server, create_user};
+
+const FLAWED: &str = r#"#[starknet::contract]
+mod Flawed {
+    #[storage]
+    struct Storage {
+        supply: u256,
+    }
+
+    #[external(v0)]
+    fn mint(ref self: ContractState, amount: u256) {
+        // TODO: restrict to the owner
+        self.supply.write(self.supply.read() + amount);
+    }
+}
+"#;
+
+// Generates a counter contract and returns its id
+async fn generate(server: &TestServer, token: &str) -> i64 {
+    let body: Value = server
+        .post("/generate")
+        .authorization_bearer(token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C"}))
+        .await
+        .json();
+    body["contract_id"].as_i64().unwrap()
+}
+
+async fn stored_rules(pool: &PgPool, id: i64) -> Vec<String> {
+    sqlx::query_as::<_, (String,)>(
+        "SELECT rule FROM audit_findings WHERE contract_id = $1 ORDER BY id",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|(rule,)| rule)
+    .collect()
+}
+
+#[tokio::test]
+async fn test_audit_persists_findings() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/audit", id);
+
+    // Templates pass every rule
+    let response = server.post(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let report: Value = response.json();
+    assert_eq!(report["contract_id"], id);
+    assert_eq!(report["findings"], json!([]));
+
+    sqlx::query("UPDATE generated_contracts SET generated_code = $2 WHERE id = $1")
+        .bind(id)
+        .bind(FLAWED)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let report: Value = server.post(&path).authorization_bearer(&token).await.json();
+    assert_eq!(
+        report["findings"][0],
+        json!({
+            "rule": "missing-caller-check",
+            "severity": "high",
+            "message": "`mint` looks privileged but never checks the caller",
+            "function": "mint",
+            "line_start": 9,
+            "line_end": 12
+        })
+    );
+    let rules: Vec<&str> = report["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["rule"].as_str().unwrap())
+        .collect();
+    assert_eq!(rules, ["missing-caller-check", "missing-event", "todo"]);
+    assert_eq!(stored_rules(&pool, id).await, rules);
+
+    // A new audit replaces the stored findings
+    sqlx::query("UPDATE generated_contracts SET generated_code = '' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    server.post(&path).authorization_bearer(&token).await;
+    assert!(stored_rules(&pool, id).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_audit_errors() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+
+    let other = create_user(&pool).await;
+    let response = server
+        .post(&format!("/generated_contracts/{}/audit", id))
+        .authorization_bearer(&other)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server
+        .post(&format!("/generated_contracts/{}/audit", id))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Queued generations have no code to audit yet
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let response = server
+        .post(&format!(
+            "/generated_contracts/{}/audit",
+            job["contract_id"]
+        ))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{Request, header},
+};
+
+use backend::libs::{
+    api_keys::{self, API_KEY_HEADER},
+    db::AppState,
+    error::ApiError,
+    jwt,
+    roles::Role,
+    session,
+};
+use backend::middlewares::auth::{Admin, AuthUser, RequireRole};
+
+// Test helper to create app state on the migrated test database
+async fn test_state() -> AppState {
+    AppState::new(common::test_pool().await).unwrap()
+}
+
+// Unique wallet per test run so tests don't collide on revocations
+fn random_wallet() -> String {
+    format!("{:#x}", rand::random::<u128>())
+}
+
+fn bearer(token: &str) -> axum::http::request::Parts {
+    let req = Request::builder()
+        .uri("/")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(())
+        .unwrap();
+    req.into_parts().0
+}
+
+#[tokio::test]
+async fn test_extractor_ok_with_valid_bearer_jwt() {
+    let state = test_state().await;
+    let token = jwt::encode(&random_wallet(), &state.keys).unwrap();
+
+    let user = AuthUser::from_request_parts(&mut bearer(&token), &state)
+        .await
+        .unwrap();
+    assert!(user.wallet.starts_with("0x"));
+}
+
+#[tokio::test]
+async fn test_extractor_err_with_revoked_token() {
+    let state = test_state().await;
+    let claims = jwt::new_claims(&random_wallet());
+    let token = state.keys.encode(&claims).unwrap();
+
+    session::revoke_access(&state.pool, &claims).await.unwrap();
+
+    let extracted = AuthUser::from_request_parts(&mut bearer(&token), &state).await;
+    assert!(matches!(
+        extracted,
+        Err(ApiError::Unauthorized("token revoked"))
+    ));
+}
+
+#[tokio::test]
+async fn test_extractor_err_after_logout_everywhere() {
+    let state = test_state().await;
+    let wallet = random_wallet();
+    let token = jwt::encode(&wallet, &state.keys).unwrap();
+
+    session::revoke_issued_access(&state.pool, &wallet)
+        .await
+        .unwrap();
+
+    let extracted = AuthUser::from_request_parts(&mut bearer(&token), &state).await;
+    assert!(matches!(
+        extracted,
+        Err(ApiError::Unauthorized("token revoked"))
+    ));
+
+    // Other wallets keep their sessions
+    let other = jwt::encode(&random_wallet(), &state.keys).unwrap();
+    assert!(
+        AuthUser::from_request_parts(&mut bearer(&other), &state)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_extractor_err_with_unknown_api_key() {
+    let state = test_state().await;
+    let (_, key) = api_keys::generate();
+
+    let req = Request::builder()
+        .uri("/")
+        .header(API_KEY_HEADER, key)
+        .body(())
+        .unwrap();
+    let (mut parts, _) = req.into_parts();
+
+    let extracted = AuthUser::from_request_parts(&mut parts, &state).await;
+    assert!(matches!(
+        extracted,
+        Err(ApiError::Unauthorized("invalid api key"))
+    ));
+}
+
+#[tokio::test]
+async fn test_require_role_checks_current_roles() {
+    let state = test_state().await;
+    let wallet = random_wallet();
+    sqlx::query("INSERT INTO users (wallet, roles) VALUES ($1, '{admin}')")
+        .bind(&wallet)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+
+    let token = jwt::encode(&wallet, &state.keys).unwrap();
+    assert!(
+        RequireRole::<Admin>::from_request_parts(&mut bearer(&token), &state)
+            .await
+            .is_ok()
+    );
+
+    // Revoking the role takes effect before the token expires
+    sqlx::query("UPDATE users SET roles = '{}' WHERE wallet = $1")
+        .bind(&wallet)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+    let extracted = RequireRole::<Admin>::from_request_parts(&mut bearer(&token), &state).await;
+    assert!(matches!(
+        extracted,
+        Err(ApiError::Forbidden("insufficient role"))
+    ));
+
+    // Roles baked into a token grant nothing by themselves
+    let mut claims = jwt::new_claims(&random_wallet());
+    claims.roles = vec![Role::Admin];
+    let forged = state.keys.encode(&claims).unwrap();
+    assert!(
+        RequireRole::<Admin>::from_request_parts(&mut bearer(&forged), &state)
+            .await
+            .is_err()
+    );
+}
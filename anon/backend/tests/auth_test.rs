@@ -0,0 +1,661 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{Json, http::StatusCode};
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use starknet::{
+    core::types::Felt,
+    providers::Url,
+    signers::{SigningKey, VerifyingKey},
+};
+
+use backend::libs::{
+    db::AppState,
+    jwt::{JwtKey, KeyConfig, KeyRing},
+    siws::{self, SignInMessage},
+    verifier::{AccountVerifier, DevEcdsaVerifier},
+};
+
+// Test helper to create a test server whose accounts are all owned by
+// `owner_key()`
+async fn create_test_server() -> (TestServer, PgPool) {
+    let rpc_url = spawn_mock_rpc(owner_key().verifying_key()).await;
+    create_account_verifier_server(rpc_url).await
+}
+
+// Test helper to create a test server verifying through the node at `rpc_url`
+async fn create_account_verifier_server(rpc_url: Url) -> (TestServer, PgPool) {
+    let (server, _, pool) = common::create_test_server_with(|state| {
+        state.verifier = Arc::new(AccountVerifier::new(rpc_url));
+    })
+    .await;
+    (server, pool)
+}
+
+// Minimal JSON-RPC node standing in for starknet-devnet: every address is an
+// OpenZeppelin-style account owned by `owner`.
+async fn spawn_mock_rpc(owner: VerifyingKey) -> Url {
+    let app = axum::Router::new().route(
+        "/",
+        axum::routing::post(move |Json(req): Json<Value>| async move {
+            let params = &req["params"];
+            let call = if params.is_array() {
+                &params[0]
+            } else {
+                &params["request"]
+            };
+            let calldata: Vec<Felt> = call["calldata"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| Felt::from_hex(v.as_str().unwrap()).unwrap())
+                .collect();
+
+            let valid = calldata.len() == 4
+                && owner
+                    .verify(
+                        &calldata[0],
+                        &starknet::core::crypto::Signature {
+                            r: calldata[2],
+                            s: calldata[3],
+                        },
+                    )
+                    .unwrap_or(false);
+            let result = if valid { "0x56414c4944" } else { "0x0" };
+
+            Json(json!({ "jsonrpc": "2.0", "id": req["id"], "result": [result] }))
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    Url::parse(&format!("http://{}", addr)).unwrap()
+}
+
+// Unique wallet per test run so tests don't collide on the users table
+fn random_wallet() -> String {
+    let raw = format!("0x{:040x}", rand::random::<u128>());
+    backend::libs::wallet::normalize_and_validate(&raw).unwrap()
+}
+
+// Signer of every account on the node `create_test_server` starts
+fn owner_key() -> SigningKey {
+    SigningKey::from_secret_scalar(Felt::from(0xC0FFEE_u64))
+}
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_secret_scalar(Felt::from(rand::random::<u64>() | 1))
+}
+
+// Sign the typed data returned by /auth/nonce the way a wallet would
+fn sign_challenge(key: &SigningKey, challenge: &Value) -> Vec<String> {
+    let msg = SignInMessage {
+        wallet: challenge["wallet"].as_str().unwrap().to_string(),
+        nonce: challenge["nonce"].as_str().unwrap().to_string(),
+        issued_at: challenge["issued_at"].as_str().unwrap().parse().unwrap(),
+        expires_at: challenge["expires_at"].as_str().unwrap().parse().unwrap(),
+    };
+    let hash = siws::message_hash(&msg, &siws::chain_id_from_env()).unwrap();
+    let sig = key.sign(&hash).unwrap();
+    vec![format!("{:#x}", sig.r), format!("{:#x}", sig.s)]
+}
+
+async fn sign_in(server: &TestServer, key: &SigningKey, wallet: &str) -> String {
+    sign_in_session(server, key, wallet).await["token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+// Full /auth/verify response: access token plus refresh token
+async fn sign_in_session(server: &TestServer, key: &SigningKey, wallet: &str) -> Value {
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": wallet,
+            "nonce": challenge["nonce"],
+            "signature": sign_challenge(key, &challenge),
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    response.json()
+}
+
+#[tokio::test]
+async fn test_nonce_returns_typed_data() {
+    let (server, _pool) = create_test_server().await;
+    let wallet = random_wallet();
+
+    let response = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let body: Value = response.json();
+    assert_eq!(body["wallet"], wallet);
+    assert!(body["nonce"].as_str().unwrap().starts_with("0x"));
+    assert_eq!(body["typed_data"]["primaryType"], "SignIn");
+    assert_eq!(body["typed_data"]["domain"]["name"], siws::DOMAIN_NAME);
+    assert_eq!(body["typed_data"]["message"]["nonce"], body["nonce"]);
+}
+
+#[tokio::test]
+async fn test_nonce_rejects_invalid_wallet() {
+    let (server, _pool) = create_test_server().await;
+
+    let response = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": "not-a-wallet" }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_verify_issues_token_and_register_succeeds() {
+    let (server, _pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let key = owner_key();
+
+    let token = sign_in(&server, &key, &wallet).await;
+
+    let response = server
+        .post("/register")
+        .authorization_bearer(&token)
+        .json(&json!({ "wallet": wallet, "referral_code": "REF" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let response = server.get("/user").authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["wallet"], wallet);
+}
+
+#[tokio::test]
+async fn test_verify_rejects_wrong_key() {
+    let (server, _pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let other = signing_key();
+
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+
+    // A key of the caller's choosing does not vouch for the wallet
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": wallet,
+            "nonce": challenge["nonce"],
+            "public_key": format!("{:#x}", other.verifying_key().scalar()),
+            "signature": sign_challenge(&other, &challenge),
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "invalid signature");
+}
+
+#[tokio::test]
+async fn test_verify_rejects_replayed_nonce() {
+    let (server, _pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let key = owner_key();
+
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+    let body = json!({
+        "wallet": wallet,
+        "nonce": challenge["nonce"],
+        "signature": sign_challenge(&key, &challenge),
+    });
+
+    let first = server.post("/auth/verify").json(&body).await;
+    assert_eq!(first.status_code(), StatusCode::OK);
+
+    let second = server.post("/auth/verify").json(&body).await;
+    assert_eq!(second.status_code(), StatusCode::UNAUTHORIZED);
+    let error: Value = second.json();
+    assert_eq!(error["error"], "invalid or expired nonce");
+}
+
+#[tokio::test]
+async fn test_verify_rejects_expired_nonce() {
+    let (server, pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let key = owner_key();
+
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+
+    sqlx::query("UPDATE auth_nonces SET expires_at = NOW() - INTERVAL '1 second' WHERE nonce = $1")
+        .bind(challenge["nonce"].as_str().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": wallet,
+            "nonce": challenge["nonce"],
+            "signature": sign_challenge(&key, &challenge),
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_register_requires_token() {
+    let (server, _pool) = create_test_server().await;
+
+    let response = server
+        .post("/register")
+        .json(&json!({ "wallet": random_wallet() }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_register_rejects_other_wallet() {
+    let (server, _pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let key = owner_key();
+
+    let token = sign_in(&server, &key, &wallet).await;
+
+    let response = server
+        .post("/register")
+        .authorization_bearer(&token)
+        .json(&json!({ "wallet": random_wallet() }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_account_verifier_accepts_owner_signature() {
+    let key = owner_key();
+    let rpc_url = spawn_mock_rpc(key.verifying_key()).await;
+    let (server, _pool) = create_account_verifier_server(rpc_url).await;
+    let wallet = random_wallet();
+
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+
+    // No public key: the account contract is the source of truth
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": wallet,
+            "nonce": challenge["nonce"],
+            "signature": sign_challenge(&key, &challenge),
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["wallet"], wallet);
+}
+
+#[tokio::test]
+async fn test_account_verifier_rejects_foreign_signature() {
+    let owner = signing_key();
+    let attacker = signing_key();
+    let rpc_url = spawn_mock_rpc(owner.verifying_key()).await;
+    let (server, _pool) = create_account_verifier_server(rpc_url).await;
+    let wallet = random_wallet();
+
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+
+    // A self-consistent key pair is not enough when the account disagrees
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": wallet,
+            "nonce": challenge["nonce"],
+            "public_key": format!("{:#x}", attacker.verifying_key().scalar()),
+            "signature": sign_challenge(&attacker, &challenge),
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_account_verifier_node_unreachable() {
+    let (server, _pool) =
+        create_account_verifier_server(Url::parse("http://127.0.0.1:9").unwrap()).await;
+    let wallet = random_wallet();
+    let key = owner_key();
+
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": wallet,
+            "nonce": challenge["nonce"],
+            "signature": sign_challenge(&key, &challenge),
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_dev_ecdsa_verifier_only_trusts_listed_keys() {
+    let (_, pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let key = signing_key();
+    let keys = [(
+        siws::parse_felt(&wallet).unwrap(),
+        key.verifying_key().scalar(),
+    )];
+    let state = AppState {
+        verifier: Arc::new(DevEcdsaVerifier::new(keys.into_iter().collect())),
+        ..AppState::new(pool.clone()).unwrap()
+    };
+    let server = TestServer::new(backend::create_app(state)).unwrap();
+
+    sign_in(&server, &key, &wallet).await;
+
+    // Same key, but the wallet is not listed
+    let other = random_wallet();
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": other }))
+        .await
+        .json();
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": other,
+            "nonce": challenge["nonce"],
+            "signature": sign_challenge(&key, &challenge),
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+// Against a real starknet-devnet/katana predeployed account, e.g.
+// `starknet-devnet --seed 0` and the first account it prints.
+#[tokio::test]
+#[ignore] // Requires DEVNET_RPC_URL, DEVNET_ACCOUNT_ADDRESS and DEVNET_ACCOUNT_PRIVATE_KEY
+async fn test_account_verifier_against_devnet() {
+    let rpc_url = Url::parse(&std::env::var("DEVNET_RPC_URL").unwrap()).unwrap();
+    let wallet = backend::libs::wallet::normalize_and_validate(
+        &std::env::var("DEVNET_ACCOUNT_ADDRESS").unwrap(),
+    )
+    .unwrap();
+    let key = SigningKey::from_secret_scalar(
+        Felt::from_hex(&std::env::var("DEVNET_ACCOUNT_PRIVATE_KEY").unwrap()).unwrap(),
+    );
+    let (server, _pool) = create_account_verifier_server(rpc_url).await;
+
+    let challenge: Value = server
+        .post("/auth/nonce")
+        .json(&json!({ "wallet": wallet }))
+        .await
+        .json();
+
+    let response = server
+        .post("/auth/verify")
+        .json(&json!({
+            "wallet": wallet,
+            "nonce": challenge["nonce"],
+            "signature": sign_challenge(&key, &challenge),
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_verify_returns_refresh_token() {
+    let (server, _pool) = create_test_server().await;
+    let session = sign_in_session(&server, &owner_key(), &random_wallet()).await;
+
+    assert_eq!(session["token_type"], "Bearer");
+    assert!(session["expires_in"].as_u64().unwrap() > 0);
+    assert!(!session["refresh_token"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_refresh_rotates_tokens() {
+    let (server, pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let session = sign_in_session(&server, &owner_key(), &wallet).await;
+
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": session["refresh_token"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let rotated: Value = response.json();
+    assert_eq!(rotated["wallet"], wallet);
+    assert_ne!(rotated["refresh_token"], session["refresh_token"]);
+    assert_ne!(rotated["token"], session["token"]);
+
+    // Only hashes are stored
+    let (stored,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM refresh_tokens WHERE token_hash = $1")
+            .bind(session["refresh_token"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(stored, 0);
+}
+
+#[tokio::test]
+async fn test_refresh_reuse_revokes_family() {
+    let (server, _pool) = create_test_server().await;
+    let session = sign_in_session(&server, &owner_key(), &random_wallet()).await;
+
+    let rotated: Value = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": session["refresh_token"] }))
+        .await
+        .json();
+
+    // Replaying the retired token is treated as theft...
+    let replay = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": session["refresh_token"] }))
+        .await;
+    assert_eq!(replay.status_code(), StatusCode::UNAUTHORIZED);
+    let body: Value = replay.json();
+    assert_eq!(body["error"], "refresh token reused");
+
+    // ...and kills the descendant as well
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": rotated["refresh_token"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_refresh_rejects_unknown_token() {
+    let (server, _pool) = create_test_server().await;
+
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": "not-a-token" }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_logout_revokes_access_and_refresh_tokens() {
+    let (server, _pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let session = sign_in_session(&server, &owner_key(), &wallet).await;
+    let token = session["token"].as_str().unwrap();
+
+    let response = server
+        .post("/register")
+        .authorization_bearer(token)
+        .json(&json!({ "wallet": wallet }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let response = server
+        .post("/auth/logout")
+        .authorization_bearer(token)
+        .json(&json!({ "refresh_token": session["refresh_token"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server.get("/user").authorization_bearer(token).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "token revoked");
+
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": session["refresh_token"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_logout_all_revokes_every_session() {
+    let (server, _pool) = create_test_server().await;
+    let wallet = random_wallet();
+    let key = owner_key();
+    let first = sign_in_session(&server, &key, &wallet).await;
+    let second = sign_in_session(&server, &key, &wallet).await;
+
+    let response = server
+        .post("/auth/logout")
+        .authorization_bearer(first["token"].as_str().unwrap())
+        .json(&json!({ "all": true }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": second["refresh_token"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    // Access tokens of other sessions stop working right away as well
+    let response = server
+        .get("/user")
+        .authorization_bearer(second["token"].as_str().unwrap())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_logout_without_body() {
+    let (server, _pool) = create_test_server().await;
+    let token = sign_in(&server, &owner_key(), &random_wallet()).await;
+
+    let response = server
+        .post("/auth/logout")
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .post("/auth/logout")
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_jwks_with_symmetric_key_is_empty() {
+    let (server, _pool) = create_test_server().await;
+
+    let response = server.get("/.well-known/jwks.json").await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(
+        response
+            .header("cache-control")
+            .to_str()
+            .unwrap()
+            .contains("max-age")
+    );
+    let body: Value = response.json();
+    assert_eq!(body["keys"], json!([]));
+}
+
+#[tokio::test]
+async fn test_eddsa_tokens_verify_and_publish_jwk() {
+    let (_, pool) = create_test_server().await;
+    let key = JwtKey::from_config(
+        serde_json::from_value::<KeyConfig>(json!({
+            "kid": "ed-2025",
+            "alg": "EdDSA",
+            "private_key_file": concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jwt/ed25519.pem"),
+        }))
+        .unwrap(),
+    )
+    .unwrap();
+    let rpc_url = spawn_mock_rpc(owner_key().verifying_key()).await;
+    let state = AppState {
+        keys: Arc::new(KeyRing::new(vec![key], None, chrono::Duration::zero()).unwrap()),
+        verifier: Arc::new(AccountVerifier::new(rpc_url)),
+        ..AppState::new(pool.clone()).unwrap()
+    };
+    let server = TestServer::new(backend::create_app(state)).unwrap();
+
+    let wallet = random_wallet();
+    let token = sign_in(&server, &owner_key(), &wallet).await;
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("ed-2025"));
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::EdDSA);
+
+    let response = server
+        .post("/register")
+        .authorization_bearer(&token)
+        .json(&json!({ "wallet": wallet }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let jwks: Value = server.get("/.well-known/jwks.json").await.json();
+    assert_eq!(jwks["keys"][0]["kid"], "ed-2025");
+    assert_eq!(jwks["keys"][0]["kty"], "OKP");
+    assert_eq!(jwks["keys"][0]["crv"], "Ed25519");
+}
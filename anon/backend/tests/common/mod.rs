@@ -1,3 +1,8 @@
+// Each test binary uses its own part of these helpers
+#![allow(dead_code)]
+
+use axum_test::TestServer;
+use backend::libs::{db::AppState, jwt::KeyRing, session};
 use sqlx::PgPool;
 use std::env;
 
@@ -22,3 +27,73 @@ impl TestConfig {
         Ok(pool)
     }
 }
+
+// Pool on the test database with all migrations run
+pub async fn test_pool() -> PgPool {
+    let pool = backend::libs::db::new_pool(&TestConfig::from_env().database_url)
+        .await
+        .expect("Failed to create test database pool");
+
+    backend::libs::db::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+// Test helper to create a test server over the default state after
+// `configure` changed it; returns the state served as well
+pub async fn create_test_server_with(
+    configure: impl FnOnce(&mut AppState),
+) -> (TestServer, AppState, PgPool) {
+    let pool = test_pool().await;
+    let mut state = AppState::new(pool.clone()).unwrap();
+    configure(&mut state);
+    let server = TestServer::new(backend::create_app(state.clone())).unwrap();
+    (server, state, pool)
+}
+
+// Test helper to create a test server
+pub async fn create_test_server() -> (TestServer, PgPool) {
+    let (server, _, pool) = create_test_server_with(|_| {}).await;
+    (server, pool)
+}
+
+// Drops queued jobs of earlier runs, which a worker would claim first
+pub async fn clear_jobs(pool: &PgPool) {
+    sqlx::query("DELETE FROM generation_jobs")
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+pub fn random_wallet() -> String {
+    let raw_wallet = format!("0x{:060x}", rand::random::<u128>());
+    backend::libs::wallet::normalize_and_validate(&raw_wallet).unwrap()
+}
+
+// Access token for `wallet`, registered or not, issued the way sign-in does
+pub async fn token_for(pool: &PgPool, wallet: &str) -> String {
+    let keys = KeyRing::from_env().unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    let (pair, _) = session::issue(&mut conn, &keys, wallet, None)
+        .await
+        .unwrap();
+    pair.access_token
+}
+
+// Registered user holding `roles` and an access token for it
+pub async fn create_user_with_roles(pool: &PgPool, roles: &[&str]) -> String {
+    let wallet = random_wallet();
+    sqlx::query("INSERT INTO users (wallet, roles) VALUES ($1, $2)")
+        .bind(&wallet)
+        .bind(roles)
+        .execute(pool)
+        .await
+        .expect("Failed to create test user");
+    token_for(pool, &wallet).await
+}
+
+// Registered user and an access token for it
+pub async fn create_user(pool: &PgPool) -> String {
+    create_user_with_roles(pool, &[]).await
+}
@@ -0,0 +1,368 @@
+mod common;
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use backend::libs::{compiler::Compiler, db::AppState, jobs};
+use common::create_user;
+
+// Test helper to create a test server building with `compiler`, with no
+// jobs queued
+async fn create_test_server(compiler: Option<Compiler>) -> (TestServer, AppState, PgPool) {
+    let (server, state, pool) =
+        common::create_test_server_with(|state| state.compiler = compiler.map(Arc::new)).await;
+    common::clear_jobs(&pool).await;
+    (server, state, pool)
+}
+
+// Stand-in for `scarb build`: fails on code containing BROKEN, hangs on SLOW,
+// takes a moment on NAP, and otherwise writes a Sierra and a CASM class like
+// Scarb does.
+const STUB_SCARB: &str = r#"#!/bin/sh
+[ "$1" = "--offline" ] && shift
+[ "$1" = "build" ] || { echo "error: unexpected arguments: $*" >&2; exit 2; }
+[ -n "$TEST_DATABASE_URL$DATABASE_URL" ] && { echo "error: environment leaked"; exit 1; }
+grep -q SLOW src/lib.cairo && sleep 30
+grep -q NAP src/lib.cairo && sleep 2
+if grep -q BROKEN src/lib.cairo; then
+  echo "   Compiling pkg v0.1.0 ($PWD/Scarb.toml)"
+  echo "error[E0006]: Identifier not found."
+  echo " --> $PWD/src/lib.cairo:3:9"
+  echo "error: could not compile \`pkg\` due to previous error" >&2
+  exit 1
+fi
+mkdir -p target/dev
+printf '{"sierra_program":["0x1"],"abi":[{"type":"function","name":"stub"}]}' > target/dev/pkg_C.contract_class.json
+printf '{"bytecode":["0x2"]}' > target/dev/pkg_C.compiled_contract_class.json
+printf '{"version":1}' > target/dev/pkg.starknet_artifacts.json
+"#;
+
+fn stub_scarb() -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = std::env::temp_dir().join(format!("stub-scarb-{:016x}", rand::random::<u64>()));
+    std::fs::write(&path, STUB_SCARB).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+// Generates a counter contract and returns its id
+async fn generate(server: &TestServer, token: &str) -> i64 {
+    let body: Value = server
+        .post("/generate")
+        .authorization_bearer(token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C"}))
+        .await
+        .json();
+    body["contract_id"].as_i64().unwrap()
+}
+
+async fn append_code(pool: &PgPool, id: i64, marker: &str) {
+    sqlx::query(
+        "UPDATE generated_contracts SET generated_code = generated_code || $2 WHERE id = $1",
+    )
+    .bind(id)
+    .bind(format!("// {}\n", marker))
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_compile_success_stores_artifacts() {
+    let (server, _, pool) = create_test_server(Some(Compiler::new(stub_scarb()))).await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+
+    let response = server
+        .post(&format!("/generated_contracts/{}/compile", id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let report: Value = response.json();
+    assert_eq!(report["contract_id"], id);
+    assert_eq!(report["status"], "compiled");
+    assert_eq!(report["diagnostics"], json!([]));
+
+    let latest: Value = server
+        .get(&format!("/generated_contracts/{}/compilation", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(latest["id"], report["id"]);
+    assert_eq!(latest["status"], "compiled");
+
+    let sierra: Value = server
+        .get(&format!("/generated_contracts/{}/compilation/sierra", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(sierra["sierra_program"], json!(["0x1"]));
+    let casm: Value = server
+        .get(&format!("/generated_contracts/{}/compilation/casm", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(casm["bytecode"], json!(["0x2"]));
+
+    let list: Value = server
+        .get("/generated_contracts")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"][0]["status"], "compiled");
+}
+
+#[tokio::test]
+async fn test_abi_prefers_current_build() {
+    let (server, _, pool) = create_test_server(Some(Compiler::new(stub_scarb()))).await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/abi", id);
+
+    let abi: Value = server.get(&path).authorization_bearer(&token).await.json();
+    assert_eq!(abi["source"], "source");
+
+    server
+        .post(&format!("/generated_contracts/{}/compile", id))
+        .authorization_bearer(&token)
+        .await;
+    let abi: Value = server.get(&path).authorization_bearer(&token).await.json();
+    assert_eq!(abi["source"], "sierra");
+    assert_eq!(abi["abi"], json!([{"type": "function", "name": "stub"}]));
+    assert_eq!(abi["storage"][0]["name"], "count");
+
+    // The build no longer matches once the code changes
+    let code: Value = server
+        .get(&format!("/generated_contracts/{}", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    let edited = format!("{}// edited\n", code["generated_code"].as_str().unwrap());
+    server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&token)
+        .json(&json!({"generated_code": edited}))
+        .await;
+    let abi: Value = server.get(&path).authorization_bearer(&token).await.json();
+    assert_eq!(abi["source"], "source");
+}
+
+#[tokio::test]
+async fn test_compile_failure_reports_diagnostics() {
+    let (server, _, pool) = create_test_server(Some(Compiler::new(stub_scarb()))).await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    append_code(&pool, id, "BROKEN").await;
+
+    let report: Value = server
+        .post(&format!("/generated_contracts/{}/compile", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(report["status"], "compile_failed");
+    assert_eq!(
+        report["diagnostics"],
+        json!([{
+            "severity": "error",
+            "file": "src/lib.cairo",
+            "line": 3,
+            "column": 9,
+            "message": "Identifier not found."
+        }])
+    );
+    assert!(report["log"].as_str().unwrap().contains("Compiling pkg"));
+
+    let (status,): (String,) =
+        sqlx::query_as("SELECT status FROM generated_contracts WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "compile_failed");
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/compilation/sierra", id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_compile_timeout() {
+    let compiler = Compiler {
+        timeout: Duration::from_secs(1),
+        ..Compiler::new(stub_scarb())
+    };
+    let (server, _, pool) = create_test_server(Some(compiler)).await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    append_code(&pool, id, "SLOW").await;
+
+    let started = std::time::Instant::now();
+    let report: Value = server
+        .post(&format!("/generated_contracts/{}/compile", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(report["status"], "compile_failed");
+    assert_eq!(
+        report["diagnostics"][0]["message"],
+        "compilation timed out after 1s"
+    );
+}
+
+#[tokio::test]
+async fn test_compile_waits_for_a_slot() {
+    // Every slot is taken
+    let compiler = Compiler {
+        timeout: Duration::from_secs(1),
+        slots: tokio::sync::Semaphore::new(0),
+        ..Compiler::new(stub_scarb())
+    };
+    let (server, _, pool) = create_test_server(Some(compiler)).await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+
+    let response = server
+        .post(&format!("/generated_contracts/{}/compile", id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.json::<Value>()["error"], "compiler busy");
+}
+
+#[tokio::test]
+async fn test_compile_keeps_deployed_and_archived_status() {
+    let (server, _, pool) = create_test_server(Some(Compiler::new(stub_scarb()))).await;
+    let token = create_user(&pool).await;
+
+    for status in ["deployed", "archived"] {
+        let id = generate(&server, &token).await;
+        sqlx::query("UPDATE generated_contracts SET status = $2 WHERE id = $1")
+            .bind(id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let report: Value = server
+            .post(&format!("/generated_contracts/{}/compile", id))
+            .authorization_bearer(&token)
+            .await
+            .json();
+        assert_eq!(report["status"], "compiled");
+
+        let (current,): (String,) =
+            sqlx::query_as("SELECT status FROM generated_contracts WHERE id = $1")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(current, status);
+    }
+}
+
+#[tokio::test]
+async fn test_code_changed_during_build_stays_unbuilt() {
+    let (server, _, pool) = create_test_server(Some(Compiler::new(stub_scarb()))).await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    append_code(&pool, id, "NAP").await;
+
+    let compile = async {
+        server
+            .post(&format!("/generated_contracts/{}/compile", id))
+            .authorization_bearer(&token)
+            .await
+    };
+    let edit = async {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        server
+            .put(&format!("/generated_contracts/{}/code", id))
+            .authorization_bearer(&token)
+            .json(&json!({"generated_code": "mod Edited {}"}))
+            .await
+    };
+    let (compiled, edited) = tokio::join!(compile, edit);
+    assert_eq!(edited.status_code(), StatusCode::OK);
+    let report: Value = compiled.json();
+    assert_eq!(report["status"], "compiled");
+    assert_eq!(report["revision"], 1);
+
+    // The build is of the code before the edit
+    let contract: Value = server
+        .get(&format!("/generated_contracts/{}", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(contract["status"], "generated");
+    let abi: Value = server
+        .get(&format!("/generated_contracts/{}/abi", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(abi["source"], "source");
+}
+
+#[tokio::test]
+async fn test_compile_errors() {
+    let (server, _, pool) = create_test_server(None).await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/compile", id);
+
+    let response = server.post(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let other = create_user(&pool).await;
+    let response = server.post(&path).authorization_bearer(&other).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server
+        .get(&format!("/generated_contracts/{}/compilation", id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Queued generations have nothing to build yet
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let response = server
+        .post(&format!(
+            "/generated_contracts/{}/compile",
+            job["contract_id"]
+        ))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_queued_generation_is_compiled() {
+    let (server, state, pool) = create_test_server(Some(Compiler::new(stub_scarb()))).await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    assert!(jobs::run_next(&state).await.unwrap());
+
+    let job: Value = server
+        .get(&format!("/jobs/{}", job["id"]))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["contract"]["status"], "compiled");
+}
@@ -0,0 +1,261 @@
+mod common;
+
+use std::{path::PathBuf, sync::Arc};
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt},
+    providers::{JsonRpcClient, Provider, Url, jsonrpc::HttpTransport},
+    signers::SigningKey,
+};
+
+use backend::libs::{compiler::Compiler, deployer::Deployer, deployments::Network};
+use common::create_user_with_roles;
+
+// Test helper to create a test server building with `compiler` and
+// deploying with `deployer`
+async fn create_test_server(
+    compiler: Option<Compiler>,
+    deployer: Option<Deployer>,
+) -> (TestServer, PgPool) {
+    let (server, _, pool) = common::create_test_server_with(|state| {
+        state.compiler = compiler.map(Arc::new);
+        state.deployer = deployer.map(Arc::new);
+    })
+    .await;
+    (server, pool)
+}
+
+// Stand-in for `scarb build` writing classes no network would accept
+const STUB_SCARB: &str = r#"#!/bin/sh
+mkdir -p target/dev
+printf '{"sierra_program":["0x1"],"abi":[{"type":"function","name":"stub"}]}' > target/dev/pkg_C.contract_class.json
+printf '{"bytecode":["0x2"]}' > target/dev/pkg_C.compiled_contract_class.json
+"#;
+
+fn stub_scarb() -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = std::env::temp_dir().join(format!("stub-scarb-{:016x}", rand::random::<u64>()));
+    std::fs::write(&path, STUB_SCARB).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+// Generates a counter contract and returns its id
+async fn generate(server: &TestServer, token: &str) -> i64 {
+    let body: Value = server
+        .post("/generate")
+        .authorization_bearer(token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C"}))
+        .await
+        .json();
+    body["contract_id"].as_i64().unwrap()
+}
+
+// A deployer whose node is never reached
+fn unreachable_deployer() -> Deployer {
+    Deployer::new(
+        Network::Devnet,
+        Url::parse("http://127.0.0.1:9").unwrap(),
+        Felt::ONE,
+        SigningKey::from_secret_scalar(Felt::ONE),
+        u128::MAX,
+    )
+}
+
+#[tokio::test]
+async fn test_deploy_requires_admin() {
+    let (server, pool) = create_test_server(None, Some(unreachable_deployer())).await;
+    let token = create_user_with_roles(&pool, &[]).await;
+    let id = generate(&server, &token).await;
+
+    let response = server
+        .post(&format!("/generated_contracts/{}/deploy", id))
+        .authorization_bearer(&token)
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Admins deploy any user's contract, once it is built
+    let admin = create_user_with_roles(&pool, &["admin"]).await;
+    let response = server
+        .post(&format!("/generated_contracts/{}/deploy", id))
+        .authorization_bearer(&admin)
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_deploy_not_configured() {
+    let (server, pool) = create_test_server(None, None).await;
+    let token = create_user_with_roles(&pool, &["admin"]).await;
+    let id = generate(&server, &token).await;
+
+    let response = server
+        .post(&format!("/generated_contracts/{}/deploy", id))
+        .authorization_bearer(&token)
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let response = server
+        .post("/generated_contracts/999999999/deploy")
+        .authorization_bearer(&token)
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_deploy_checks_build_and_arguments() {
+    let (server, pool) = create_test_server(
+        Some(Compiler::new(stub_scarb())),
+        Some(unreachable_deployer()),
+    )
+    .await;
+    let token = create_user_with_roles(&pool, &["admin"]).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/deploy", id);
+
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+    server
+        .post(&format!("/generated_contracts/{}/compile", id))
+        .authorization_bearer(&token)
+        .await;
+
+    // Arguments follow the built class's ABI, which has no constructor
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"args": {"owner": "0x1"}, "salt": "salt"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["args.owner", "salt"]);
+
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"salt": "0x5"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let deployments: Value = server
+        .get(&format!("/deployments?contract_id={}", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(deployments["items"], json!([]));
+
+    // A build of earlier code is not deployed
+    server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&token)
+        .json(&json!({"generated_code": "mod Edited {}"}))
+        .await;
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
+
+// Needs a Scarb with openzeppelin cached, and a local node such as
+// `starknet-devnet --seed 0` with the first account it prints.
+#[tokio::test]
+#[ignore] // Requires SCARB_PATH, DEVNET_RPC_URL, DEVNET_ACCOUNT_ADDRESS and DEVNET_ACCOUNT_PRIVATE_KEY
+async fn test_declare_and_deploy_on_devnet() {
+    let rpc_url = Url::parse(&std::env::var("DEVNET_RPC_URL").unwrap()).unwrap();
+    let account = Felt::from_hex(&std::env::var("DEVNET_ACCOUNT_ADDRESS").unwrap()).unwrap();
+    let key = Felt::from_hex(&std::env::var("DEVNET_ACCOUNT_PRIVATE_KEY").unwrap()).unwrap();
+    let deployer = Deployer::new(
+        Network::Devnet,
+        rpc_url.clone(),
+        account,
+        SigningKey::from_secret_scalar(key),
+        u128::MAX,
+    );
+    let (server, pool) = create_test_server(
+        Some(Compiler::new(std::env::var("SCARB_PATH").unwrap())),
+        Some(deployer),
+    )
+    .await;
+    let token = create_user_with_roles(&pool, &["admin"]).await;
+    let id = generate(&server, &token).await;
+    let path = format!("/generated_contracts/{}/deploy", id);
+
+    let report: Value = server
+        .post(&format!("/generated_contracts/{}/compile", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(report["status"], "compiled", "{}", report["log"]);
+
+    let salt = format!("{:#x}", rand::random::<u64>());
+    let args = json!({"owner": format!("{:#x}", account)});
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"args": args, "salt": salt}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let first: Value = response.json();
+    assert_eq!(first["network"], "devnet");
+    assert_eq!(first["salt"], salt);
+    assert!(first["declare_transaction_hash"].is_string());
+    assert!(first["declare_fee"].is_string());
+    assert!(first["deploy_fee"].is_string());
+
+    let provider = JsonRpcClient::new(HttpTransport::new(rpc_url));
+    let address = Felt::from_hex(first["contract_address"].as_str().unwrap()).unwrap();
+    let class_hash = provider
+        .get_class_hash_at(BlockId::Tag(BlockTag::Latest), address)
+        .await
+        .unwrap();
+    assert_eq!(format!("{:#x}", class_hash), first["class_hash"]);
+
+    // The address is taken
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"args": args, "salt": salt}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // The class is already declared
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"args": args, "unique": true}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let second: Value = response.json();
+    assert_eq!(second["class_hash"], first["class_hash"]);
+    assert!(second["declare_transaction_hash"].is_null());
+    assert!(second["declare_fee"].is_null());
+
+    let deployments: Value = server
+        .get(&format!("/deployments?contract_id={}", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(deployments["items"].as_array().unwrap().len(), 2);
+    assert_eq!(deployments["items"][0]["contract_status"], "deployed");
+}
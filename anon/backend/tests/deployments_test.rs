@@ -0,0 +1,340 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+
+use common::{create_test_server, create_user, create_user_with_roles};
+
+// Generates a counter contract and returns its id
+async fn generate(server: &TestServer, token: &str, name: &str) -> i64 {
+    let body: Value = server
+        .post("/generate")
+        .authorization_bearer(token)
+        .json(&json!({"contract_type": "counter", "contract_name": name}))
+        .await
+        .json();
+    body["contract_id"].as_i64().unwrap()
+}
+
+// Random address, as a wallet would show it
+fn address() -> String {
+    format!("0x{:064X}", rand::random::<u128>())
+}
+
+#[tokio::test]
+async fn test_record_deployment() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token, "Counter").await;
+    let address = address();
+
+    let response = server
+        .post(&format!("/generated_contracts/{}/deployments", id))
+        .authorization_bearer(&token)
+        .json(&json!({
+            "network": "sepolia",
+            "contract_address": address,
+            "class_hash": "0x00ABC",
+            "transaction_hash": "0x0def",
+            "declare_transaction_hash": "0x123"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let deployment: Value = response.json();
+    assert_eq!(deployment["contract_id"], id);
+    assert_eq!(deployment["network"], "sepolia");
+    assert_eq!(
+        deployment["contract_address"],
+        format!("0x{}", address[2..].trim_start_matches('0').to_lowercase())
+    );
+    assert_eq!(deployment["class_hash"], "0xabc");
+    assert_eq!(deployment["transaction_hash"], "0xdef");
+    assert_eq!(deployment["declare_transaction_hash"], "0x123");
+    assert_eq!(deployment["revision"], 1);
+    assert_eq!(deployment["status"], "confirmed");
+
+    let contract: Value = server
+        .get(&format!("/generated_contracts/{}", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(contract["status"], "deployed");
+
+    // The same address twice on a network, in any spelling
+    let response = server
+        .post(&format!("/generated_contracts/{}/deployments", id))
+        .authorization_bearer(&token)
+        .json(&json!({
+            "network": "sepolia",
+            "contract_address": address.to_lowercase(),
+            "class_hash": "0xabc",
+            "transaction_hash": "0x1"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+    // but it can exist on another one
+    let response = server
+        .post(&format!("/generated_contracts/{}/deployments", id))
+        .authorization_bearer(&token)
+        .json(&json!({
+            "network": "devnet",
+            "contract_address": address,
+            "class_hash": "0xabc",
+            "transaction_hash": "0x1"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    assert_eq!(
+        response.json::<Value>()["declare_transaction_hash"],
+        Value::Null
+    );
+
+    // Another user's record of the address neither blocks nor reveals it
+    let other = create_user(&pool).await;
+    let other_id = generate(&server, &other, "Counter").await;
+    let response = server
+        .post(&format!("/generated_contracts/{}/deployments", other_id))
+        .authorization_bearer(&other)
+        .json(&json!({
+            "network": "sepolia",
+            "contract_address": address,
+            "class_hash": "0xabc",
+            "transaction_hash": "0x2"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_record_deployment_errors() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let other = create_user(&pool).await;
+    let id = generate(&server, &token, "Counter").await;
+    let path = format!("/generated_contracts/{}/deployments", id);
+    let valid = json!({
+        "network": "mainnet",
+        "contract_address": address(),
+        "class_hash": "0xabc",
+        "transaction_hash": "0xdef"
+    });
+
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&json!({
+            "network": "goerli",
+            "contract_address": "0x0",
+            "class_hash": "hash",
+            "transaction_hash": "0xdef",
+            "declare_transaction_hash": "0x0"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let fields: Vec<String> = response.json::<Value>()["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "network",
+            "contract_address",
+            "class_hash",
+            "declare_transaction_hash"
+        ]
+    );
+
+    let response = server.post(&path).json(&valid).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post(&path)
+        .authorization_bearer(&other)
+        .json(&valid)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    sqlx::query("UPDATE generated_contracts SET status = 'failed' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = server
+        .post(&path)
+        .authorization_bearer(&token)
+        .json(&valid)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_list_deployments() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let other = create_user(&pool).await;
+    let first = generate(&server, &token, "First").await;
+    let second = generate(&server, &token, "Second").await;
+    for (id, network) in [(first, "sepolia"), (second, "sepolia"), (second, "mainnet")] {
+        let response = server
+            .post(&format!("/generated_contracts/{}/deployments", id))
+            .authorization_bearer(&token)
+            .json(&json!({
+                "network": network,
+                "contract_address": address(),
+                "class_hash": "0xabc",
+                "transaction_hash": "0xdef"
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+
+    let list: Value = server
+        .get("/deployments")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    let items = list["items"].as_array().unwrap();
+    let summary: Vec<(&str, &str)> = items
+        .iter()
+        .map(|i| {
+            (
+                i["contract_name"].as_str().unwrap(),
+                i["network"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Second", "mainnet"),
+            ("Second", "sepolia"),
+            ("First", "sepolia")
+        ]
+    );
+    assert_eq!(items[0]["contract_id"], second);
+    assert_eq!(items[0]["contract_type"], "counter");
+    assert_eq!(items[0]["contract_status"], "deployed");
+    assert_eq!(list["next_cursor"], Value::Null);
+
+    let list: Value = server
+        .get("/deployments?network=sepolia&limit=1")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"][0]["contract_name"], "Second");
+    let cursor = list["next_cursor"].as_str().unwrap();
+    let list: Value = server
+        .get(&format!(
+            "/deployments?network=sepolia&limit=1&cursor={}",
+            cursor
+        ))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"][0]["contract_name"], "First");
+
+    let list: Value = server
+        .get(&format!("/deployments?contract_id={}", first))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"].as_array().unwrap().len(), 1);
+
+    // Deleted contracts and other users' deployments are left out
+    server
+        .delete(&format!("/generated_contracts/{}", second))
+        .authorization_bearer(&token)
+        .await;
+    let list: Value = server
+        .get("/deployments")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"].as_array().unwrap().len(), 1);
+    let list: Value = server
+        .get("/deployments")
+        .authorization_bearer(&other)
+        .await
+        .json();
+    assert_eq!(list["items"], json!([]));
+
+    let response = server
+        .get("/deployments?network=goerli")
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_get_deployment() {
+    let (server, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let other = create_user(&pool).await;
+    let id = generate(&server, &token, "Counter").await;
+    let address = address();
+    let recorded: Value = server
+        .post(&format!("/generated_contracts/{}/deployments", id))
+        .authorization_bearer(&token)
+        .json(&json!({
+            "network": "devnet",
+            "contract_address": address,
+            "class_hash": "0xabc",
+            "transaction_hash": "0xdef"
+        }))
+        .await
+        .json();
+    let path = format!("/deployments/{}", recorded["id"]);
+
+    let response = server.get(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let deployment: Value = response.json();
+    assert_eq!(deployment["status"], "confirmed");
+    assert_eq!(deployment["contract_name"], "Counter");
+    assert_eq!(deployment["contract_status"], "deployed");
+
+    let response = server.get(&path).authorization_bearer(&other).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    // but an admin, who may have sent it, can
+    let admin = create_user_with_roles(&pool, &["admin"]).await;
+    let response = server.get(&path).authorization_bearer(&admin).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    // The list still holds only the admin's own
+    let list: Value = server
+        .get("/deployments")
+        .authorization_bearer(&admin)
+        .await
+        .json();
+    assert_eq!(list["items"], json!([]));
+
+    // Without a deployer a pending deployment stays as it is
+    sqlx::query("UPDATE deployed_contracts SET status = 'pending' WHERE id = $1")
+        .bind(recorded["id"].as_i64().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let deployment: Value = server.get(&path).authorization_bearer(&token).await.json();
+    assert_eq!(deployment["status"], "pending");
+
+    // A failed deployment holds no address
+    sqlx::query("UPDATE deployed_contracts SET status = 'failed' WHERE id = $1")
+        .bind(recorded["id"].as_i64().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = server
+        .post(&format!("/generated_contracts/{}/deployments", id))
+        .authorization_bearer(&token)
+        .json(&json!({
+            "network": "devnet",
+            "contract_address": address,
+            "class_hash": "0xabc",
+            "transaction_hash": "0x1"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+}
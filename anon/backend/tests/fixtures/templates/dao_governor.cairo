@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: MIT
+// Generated by StarkFinder from the `dao_governor` template.
+// Compatible with OpenZeppelin Contracts for Cairo 1.0.0
+//
+// Voting power is read from an IVotes token (e.g. OpenZeppelin ERC20Votes) at
+// the moment voting opens. Each proposal executes a single call; only a hash
+// of its calldata is stored and the calldata is passed again on execution.
+
+#[starknet::contract]
+mod Golden {
+    use core::poseidon::poseidon_hash_span;
+    use openzeppelin::governance::votes::interface::{IVotesDispatcher, IVotesDispatcherTrait};
+    use starknet::storage::{
+        Map, StorageMapReadAccess, StorageMapWriteAccess, StoragePointerReadAccess,
+        StoragePointerWriteAccess,
+    };
+    use starknet::syscalls::call_contract_syscall;
+    use starknet::{ContractAddress, SyscallResultTrait, get_block_timestamp, get_caller_address};
+
+    const VOTING_DELAY: u64 = 86400;
+    const VOTING_PERIOD: u64 = 604800;
+    const QUORUM: u256 = 4000000000000000000000;
+    const PROPOSAL_THRESHOLD: u256 = 1000000000000000000;
+
+    // Proposal states returned by `state`
+    const PENDING: u8 = 0;
+    const ACTIVE: u8 = 1;
+    const DEFEATED: u8 = 2;
+    const SUCCEEDED: u8 = 3;
+    const EXECUTED: u8 = 4;
+
+    // Vote types accepted by `cast_vote`
+    const AGAINST: u8 = 0;
+    const FOR: u8 = 1;
+    const ABSTAIN: u8 = 2;
+
+    #[storage]
+    struct Storage {
+        token: ContractAddress,
+        proposal_count: u64,
+        proposer: Map<u64, ContractAddress>,
+        target: Map<u64, ContractAddress>,
+        selector: Map<u64, felt252>,
+        calldata_hash: Map<u64, felt252>,
+        vote_start: Map<u64, u64>,
+        vote_end: Map<u64, u64>,
+        for_votes: Map<u64, u256>,
+        against_votes: Map<u64, u256>,
+        abstain_votes: Map<u64, u256>,
+        executed: Map<u64, bool>,
+        has_voted: Map<(u64, ContractAddress), bool>,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        ProposalCreated: ProposalCreated,
+        VoteCast: VoteCast,
+        ProposalExecuted: ProposalExecuted,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct ProposalCreated {
+        #[key]
+        id: u64,
+        #[key]
+        proposer: ContractAddress,
+        target: ContractAddress,
+        selector: felt252,
+        calldata: Span<felt252>,
+        vote_start: u64,
+        vote_end: u64,
+        description: ByteArray,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct VoteCast {
+        #[key]
+        id: u64,
+        #[key]
+        voter: ContractAddress,
+        support: u8,
+        weight: u256,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct ProposalExecuted {
+        #[key]
+        id: u64,
+    }
+
+    #[constructor]
+    fn constructor(ref self: ContractState, token: ContractAddress) {
+        self.token.write(token);
+    }
+
+    #[generate_trait]
+    #[abi(per_item)]
+    impl GovernorImpl of GovernorTrait {
+        #[external(v0)]
+        fn token(self: @ContractState) -> ContractAddress {
+            self.token.read()
+        }
+
+        #[external(v0)]
+        fn proposal_count(self: @ContractState) -> u64 {
+            self.proposal_count.read()
+        }
+
+        #[external(v0)]
+        fn quorum(self: @ContractState) -> u256 {
+            QUORUM
+        }
+
+        #[external(v0)]
+        fn proposal_votes(self: @ContractState, id: u64) -> (u256, u256, u256) {
+            (self.against_votes.read(id), self.for_votes.read(id), self.abstain_votes.read(id))
+        }
+
+        #[external(v0)]
+        fn state(self: @ContractState, id: u64) -> u8 {
+            assert(id < self.proposal_count.read(), 'Governor: unknown proposal');
+            let now = get_block_timestamp();
+            if self.executed.read(id) {
+                EXECUTED
+            } else if now < self.vote_start.read(id) {
+                PENDING
+            } else if now < self.vote_end.read(id) {
+                ACTIVE
+            } else if self._succeeded(id) {
+                SUCCEEDED
+            } else {
+                DEFEATED
+            }
+        }
+
+        #[external(v0)]
+        fn propose(
+            ref self: ContractState,
+            target: ContractAddress,
+            selector: felt252,
+            calldata: Span<felt252>,
+            description: ByteArray,
+        ) -> u64 {
+            let proposer = get_caller_address();
+            let now = get_block_timestamp();
+            if PROPOSAL_THRESHOLD > 0 {
+                let votes = self._votes().get_past_votes(proposer, now - 1);
+                assert(votes >= PROPOSAL_THRESHOLD, 'Governor: below threshold');
+            }
+
+            let id = self.proposal_count.read();
+            let vote_start = now + VOTING_DELAY;
+            let vote_end = vote_start + VOTING_PERIOD;
+            self.proposal_count.write(id + 1);
+            self.proposer.write(id, proposer);
+            self.target.write(id, target);
+            self.selector.write(id, selector);
+            self.calldata_hash.write(id, poseidon_hash_span(calldata));
+            self.vote_start.write(id, vote_start);
+            self.vote_end.write(id, vote_end);
+
+            self
+                .emit(
+                    ProposalCreated {
+                        id, proposer, target, selector, calldata, vote_start, vote_end, description,
+                    },
+                );
+            id
+        }
+
+        #[external(v0)]
+        fn cast_vote(ref self: ContractState, id: u64, support: u8) -> u256 {
+            assert(self.state(id) == ACTIVE, 'Governor: vote not active');
+            let voter = get_caller_address();
+            assert(!self.has_voted.read((id, voter)), 'Governor: already voted');
+
+            // Snapshot just before voting opened, so tokens moved afterwards do not count
+            let weight = self._votes().get_past_votes(voter, self.vote_start.read(id) - 1);
+            self.has_voted.write((id, voter), true);
+            if support == AGAINST {
+                self.against_votes.write(id, self.against_votes.read(id) + weight);
+            } else if support == FOR {
+                self.for_votes.write(id, self.for_votes.read(id) + weight);
+            } else if support == ABSTAIN {
+                self.abstain_votes.write(id, self.abstain_votes.read(id) + weight);
+            } else {
+                panic!("Governor: invalid vote type");
+            }
+
+            self.emit(VoteCast { id, voter, support, weight });
+            weight
+        }
+
+        #[external(v0)]
+        fn execute(ref self: ContractState, id: u64, calldata: Span<felt252>) -> Span<felt252> {
+            assert(self.state(id) == SUCCEEDED, 'Governor: not succeeded');
+            assert(
+                poseidon_hash_span(calldata) == self.calldata_hash.read(id),
+                'Governor: calldata mismatch',
+            );
+
+            self.executed.write(id, true);
+            let result = call_contract_syscall(self.target.read(id), self.selector.read(id), calldata)
+                .unwrap_syscall();
+            self.emit(ProposalExecuted { id });
+            result
+        }
+    }
+
+    #[generate_trait]
+    impl InternalImpl of InternalTrait {
+        fn _votes(self: @ContractState) -> IVotesDispatcher {
+            IVotesDispatcher { contract_address: self.token.read() }
+        }
+
+        fn _succeeded(self: @ContractState, id: u64) -> bool {
+            let for_votes = self.for_votes.read(id);
+            for_votes > self.against_votes.read(id)
+                && for_votes + self.abstain_votes.read(id) >= QUORUM
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT
+// Generated by StarkFinder from the `erc1155` template.
+// Compatible with OpenZeppelin Contracts for Cairo 1.0.0
+
+#[starknet::contract]
+mod Golden {
+    use openzeppelin::access::ownable::OwnableComponent;
+    use openzeppelin::introspection::src5::SRC5Component;
+    use openzeppelin::token::erc1155::{ERC1155Component, ERC1155HooksEmptyImpl};
+    use starknet::ContractAddress;
+    use starknet::get_caller_address;
+
+    component!(path: ERC1155Component, storage: erc1155, event: ERC1155Event);
+    component!(path: SRC5Component, storage: src5, event: SRC5Event);
+    component!(path: OwnableComponent, storage: ownable, event: OwnableEvent);
+
+    #[abi(embed_v0)]
+    impl ERC1155MixinImpl = ERC1155Component::ERC1155MixinImpl<ContractState>;
+    impl ERC1155InternalImpl = ERC1155Component::InternalImpl<ContractState>;
+
+    #[abi(embed_v0)]
+    impl OwnableMixinImpl = OwnableComponent::OwnableMixinImpl<ContractState>;
+    impl OwnableInternalImpl = OwnableComponent::InternalImpl<ContractState>;
+
+    #[storage]
+    struct Storage {
+        #[substorage(v0)]
+        erc1155: ERC1155Component::Storage,
+        #[substorage(v0)]
+        src5: SRC5Component::Storage,
+        #[substorage(v0)]
+        ownable: OwnableComponent::Storage,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        #[flat]
+        ERC1155Event: ERC1155Component::Event,
+        #[flat]
+        SRC5Event: SRC5Component::Event,
+        #[flat]
+        OwnableEvent: OwnableComponent::Event,
+    }
+
+    #[constructor]
+    fn constructor(ref self: ContractState, owner: ContractAddress) {
+        self.erc1155.initializer("ipfs://golden/{id}.json");
+        self.ownable.initializer(owner);
+    }
+
+    #[generate_trait]
+    #[abi(per_item)]
+    impl ExternalImpl of ExternalTrait {
+        #[external(v0)]
+        fn mint(
+            ref self: ContractState,
+            account: ContractAddress,
+            token_id: u256,
+            value: u256,
+            data: Span<felt252>,
+        ) {
+            self.ownable.assert_only_owner();
+            self.erc1155.mint_with_acceptance_check(account, token_id, value, data);
+        }
+
+        #[external(v0)]
+        fn batch_mint(
+            ref self: ContractState,
+            account: ContractAddress,
+            token_ids: Span<u256>,
+            values: Span<u256>,
+            data: Span<felt252>,
+        ) {
+            self.ownable.assert_only_owner();
+            self.erc1155.batch_mint_with_acceptance_check(account, token_ids, values, data);
+        }
+
+        #[external(v0)]
+        fn burn(ref self: ContractState, account: ContractAddress, token_id: u256, value: u256) {
+            let caller = get_caller_address();
+            if account != caller {
+                assert(
+                    self.erc1155.is_approved_for_all(account, caller),
+                    ERC1155Component::Errors::UNAUTHORIZED,
+                );
+            }
+            self.erc1155.burn(account, token_id, value);
+        }
+    }
+}
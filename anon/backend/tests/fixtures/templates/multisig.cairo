@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MIT
+// Generated by StarkFinder from the `multisig` template.
+//
+// Owners submit calls, confirm them, and anyone can execute a call once it
+// has `THRESHOLD` confirmations. Only a hash of the calldata is stored; it is
+// passed again on execution.
+
+#[starknet::contract]
+mod Golden {
+    use core::num::traits::Zero;
+    use core::poseidon::poseidon_hash_span;
+    use starknet::storage::{
+        Map, StorageMapReadAccess, StorageMapWriteAccess, StoragePointerReadAccess,
+        StoragePointerWriteAccess,
+    };
+    use starknet::syscalls::call_contract_syscall;
+    use starknet::{ContractAddress, SyscallResultTrait, get_caller_address};
+
+    const THRESHOLD: u32 = 3;
+
+    #[storage]
+    struct Storage {
+        is_owner: Map<ContractAddress, bool>,
+        owner_count: u32,
+        tx_count: u64,
+        tx_to: Map<u64, ContractAddress>,
+        tx_selector: Map<u64, felt252>,
+        tx_calldata_hash: Map<u64, felt252>,
+        tx_confirmations: Map<u64, u32>,
+        tx_executed: Map<u64, bool>,
+        confirmed: Map<(u64, ContractAddress), bool>,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        Submitted: Submitted,
+        Confirmed: Confirmed,
+        Revoked: Revoked,
+        Executed: Executed,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Submitted {
+        #[key]
+        id: u64,
+        #[key]
+        owner: ContractAddress,
+        to: ContractAddress,
+        selector: felt252,
+        calldata: Span<felt252>,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Confirmed {
+        #[key]
+        id: u64,
+        #[key]
+        owner: ContractAddress,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Revoked {
+        #[key]
+        id: u64,
+        #[key]
+        owner: ContractAddress,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Executed {
+        #[key]
+        id: u64,
+    }
+
+    #[constructor]
+    fn constructor(ref self: ContractState, owners: Span<ContractAddress>) {
+        assert(owners.len() >= THRESHOLD, 'Multisig: too few owners');
+        for owner in owners {
+            let owner = *owner;
+            assert(!owner.is_zero(), 'Multisig: zero owner');
+            assert(!self.is_owner.read(owner), 'Multisig: duplicate owner');
+            self.is_owner.write(owner, true);
+        };
+        self.owner_count.write(owners.len());
+    }
+
+    #[generate_trait]
+    #[abi(per_item)]
+    impl MultisigImpl of MultisigTrait {
+        #[external(v0)]
+        fn is_owner(self: @ContractState, account: ContractAddress) -> bool {
+            self.is_owner.read(account)
+        }
+
+        #[external(v0)]
+        fn owner_count(self: @ContractState) -> u32 {
+            self.owner_count.read()
+        }
+
+        #[external(v0)]
+        fn threshold(self: @ContractState) -> u32 {
+            THRESHOLD
+        }
+
+        #[external(v0)]
+        fn transaction_count(self: @ContractState) -> u64 {
+            self.tx_count.read()
+        }
+
+        #[external(v0)]
+        fn confirmations(self: @ContractState, id: u64) -> u32 {
+            self.tx_confirmations.read(id)
+        }
+
+        #[external(v0)]
+        fn is_executed(self: @ContractState, id: u64) -> bool {
+            self.tx_executed.read(id)
+        }
+
+        #[external(v0)]
+        fn submit(
+            ref self: ContractState,
+            to: ContractAddress,
+            selector: felt252,
+            calldata: Span<felt252>,
+        ) -> u64 {
+            let owner = self._assert_owner();
+            let id = self.tx_count.read();
+            self.tx_count.write(id + 1);
+            self.tx_to.write(id, to);
+            self.tx_selector.write(id, selector);
+            self.tx_calldata_hash.write(id, poseidon_hash_span(calldata));
+            self.emit(Submitted { id, owner, to, selector, calldata });
+
+            self.confirm(id);
+            id
+        }
+
+        #[external(v0)]
+        fn confirm(ref self: ContractState, id: u64) {
+            let owner = self._assert_owner();
+            self._assert_pending(id);
+            assert(!self.confirmed.read((id, owner)), 'Multisig: already confirmed');
+
+            self.confirmed.write((id, owner), true);
+            self.tx_confirmations.write(id, self.tx_confirmations.read(id) + 1);
+            self.emit(Confirmed { id, owner });
+        }
+
+        #[external(v0)]
+        fn revoke(ref self: ContractState, id: u64) {
+            let owner = self._assert_owner();
+            self._assert_pending(id);
+            assert(self.confirmed.read((id, owner)), 'Multisig: not confirmed');
+
+            self.confirmed.write((id, owner), false);
+            self.tx_confirmations.write(id, self.tx_confirmations.read(id) - 1);
+            self.emit(Revoked { id, owner });
+        }
+
+        #[external(v0)]
+        fn execute(ref self: ContractState, id: u64, calldata: Span<felt252>) -> Span<felt252> {
+            self._assert_pending(id);
+            assert(self.tx_confirmations.read(id) >= THRESHOLD, 'Multisig: not enough confirms');
+            assert(
+                poseidon_hash_span(calldata) == self.tx_calldata_hash.read(id),
+                'Multisig: calldata mismatch',
+            );
+
+            self.tx_executed.write(id, true);
+            let result = call_contract_syscall(
+                self.tx_to.read(id), self.tx_selector.read(id), calldata,
+            )
+                .unwrap_syscall();
+            self.emit(Executed { id });
+            result
+        }
+    }
+
+    #[generate_trait]
+    impl InternalImpl of InternalTrait {
+        fn _assert_owner(self: @ContractState) -> ContractAddress {
+            let caller = get_caller_address();
+            assert(self.is_owner.read(caller), 'Multisig: caller not owner');
+            caller
+        }
+
+        fn _assert_pending(self: @ContractState, id: u64) {
+            assert(id < self.tx_count.read(), 'Multisig: unknown transaction');
+            assert(!self.tx_executed.read(id), 'Multisig: already executed');
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+// Generated by StarkFinder from the `ownable_counter` template.
+// Compatible with OpenZeppelin Contracts for Cairo 1.0.0
+
+#[starknet::contract]
+mod Golden {
+    use openzeppelin::access::ownable::OwnableComponent;
+    use starknet::ContractAddress;
+    use starknet::storage::{StoragePointerReadAccess, StoragePointerWriteAccess};
+
+    component!(path: OwnableComponent, storage: ownable, event: OwnableEvent);
+
+    #[abi(embed_v0)]
+    impl OwnableMixinImpl = OwnableComponent::OwnableMixinImpl<ContractState>;
+    impl OwnableInternalImpl = OwnableComponent::InternalImpl<ContractState>;
+
+    const INITIAL_VALUE: u64 = 10;
+    const STEP: u64 = 5;
+
+    #[storage]
+    struct Storage {
+        count: u64,
+        #[substorage(v0)]
+        ownable: OwnableComponent::Storage,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        CountChanged: CountChanged,
+        #[flat]
+        OwnableEvent: OwnableComponent::Event,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct CountChanged {
+        old_value: u64,
+        new_value: u64,
+    }
+
+    #[constructor]
+    fn constructor(ref self: ContractState, owner: ContractAddress) {
+        self.ownable.initializer(owner);
+        self.count.write(INITIAL_VALUE);
+    }
+
+    #[generate_trait]
+    #[abi(per_item)]
+    impl CounterImpl of CounterTrait {
+        #[external(v0)]
+        fn get_count(self: @ContractState) -> u64 {
+            self.count.read()
+        }
+
+        #[external(v0)]
+        fn increment(ref self: ContractState) {
+            self.ownable.assert_only_owner();
+            let count = self.count.read();
+            self._set(count + STEP);
+        }
+
+        #[external(v0)]
+        fn decrement(ref self: ContractState) {
+            self.ownable.assert_only_owner();
+            let count = self.count.read();
+            assert(count >= STEP, 'Counter: underflow');
+            self._set(count - STEP);
+        }
+
+        #[external(v0)]
+        fn reset(ref self: ContractState) {
+            self.ownable.assert_only_owner();
+            self._set(INITIAL_VALUE);
+        }
+    }
+
+    #[generate_trait]
+    impl InternalImpl of InternalTrait {
+        fn _set(ref self: ContractState, new_value: u64) {
+            let old_value = self.count.read();
+            self.count.write(new_value);
+            self.emit(CountChanged { old_value, new_value });
+        }
+    }
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT
+// Generated by StarkFinder from the `staking` template.
+// Compatible with OpenZeppelin Contracts for Cairo 1.0.0
+//
+// Stakers earn `REWARD_RATE` reward tokens per second, shared pro rata. The
+// contract must be funded with reward tokens; claims fail once it runs dry.
+
+#[starknet::contract]
+mod Golden {
+    use openzeppelin::token::erc20::interface::{IERC20Dispatcher, IERC20DispatcherTrait};
+    use starknet::storage::{
+        Map, StorageMapReadAccess, StorageMapWriteAccess, StoragePointerReadAccess,
+        StoragePointerWriteAccess,
+    };
+    use starknet::{ContractAddress, get_block_timestamp, get_caller_address, get_contract_address};
+
+    const REWARD_RATE: u256 = 1000000000000000;
+    const PRECISION: u256 = 1000000000000000000;
+
+    #[storage]
+    struct Storage {
+        staking_token: ContractAddress,
+        reward_token: ContractAddress,
+        total_staked: u256,
+        balances: Map<ContractAddress, u256>,
+        reward_per_token_stored: u256,
+        last_update_time: u64,
+        user_reward_per_token_paid: Map<ContractAddress, u256>,
+        rewards: Map<ContractAddress, u256>,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        Staked: Staked,
+        Withdrawn: Withdrawn,
+        RewardPaid: RewardPaid,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Staked {
+        #[key]
+        user: ContractAddress,
+        amount: u256,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Withdrawn {
+        #[key]
+        user: ContractAddress,
+        amount: u256,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct RewardPaid {
+        #[key]
+        user: ContractAddress,
+        reward: u256,
+    }
+
+    #[constructor]
+    fn constructor(
+        ref self: ContractState, staking_token: ContractAddress, reward_token: ContractAddress,
+    ) {
+        self.staking_token.write(staking_token);
+        self.reward_token.write(reward_token);
+        self.last_update_time.write(get_block_timestamp());
+    }
+
+    #[generate_trait]
+    #[abi(per_item)]
+    impl StakingImpl of StakingTrait {
+        #[external(v0)]
+        fn total_staked(self: @ContractState) -> u256 {
+            self.total_staked.read()
+        }
+
+        #[external(v0)]
+        fn balance_of(self: @ContractState, account: ContractAddress) -> u256 {
+            self.balances.read(account)
+        }
+
+        #[external(v0)]
+        fn reward_per_token(self: @ContractState) -> u256 {
+            let total = self.total_staked.read();
+            if total == 0 {
+                return self.reward_per_token_stored.read();
+            }
+            let elapsed: u256 = (get_block_timestamp() - self.last_update_time.read()).into();
+            self.reward_per_token_stored.read() + elapsed * REWARD_RATE * PRECISION / total
+        }
+
+        #[external(v0)]
+        fn earned(self: @ContractState, account: ContractAddress) -> u256 {
+            let paid = self.user_reward_per_token_paid.read(account);
+            self.balances.read(account) * (self.reward_per_token() - paid) / PRECISION
+                + self.rewards.read(account)
+        }
+
+        #[external(v0)]
+        fn stake(ref self: ContractState, amount: u256) {
+            assert(amount > 0, 'Staking: zero amount');
+            let caller = get_caller_address();
+            self._update_reward(caller);
+
+            self.total_staked.write(self.total_staked.read() + amount);
+            self.balances.write(caller, self.balances.read(caller) + amount);
+            let ok = IERC20Dispatcher { contract_address: self.staking_token.read() }
+                .transfer_from(caller, get_contract_address(), amount);
+            assert(ok, 'Staking: transfer failed');
+
+            self.emit(Staked { user: caller, amount });
+        }
+
+        #[external(v0)]
+        fn withdraw(ref self: ContractState, amount: u256) {
+            assert(amount > 0, 'Staking: zero amount');
+            let caller = get_caller_address();
+            self._update_reward(caller);
+
+            let balance = self.balances.read(caller);
+            assert(balance >= amount, 'Staking: insufficient balance');
+            self.total_staked.write(self.total_staked.read() - amount);
+            self.balances.write(caller, balance - amount);
+            let ok = IERC20Dispatcher { contract_address: self.staking_token.read() }
+                .transfer(caller, amount);
+            assert(ok, 'Staking: transfer failed');
+
+            self.emit(Withdrawn { user: caller, amount });
+        }
+
+        #[external(v0)]
+        fn claim_rewards(ref self: ContractState) {
+            let caller = get_caller_address();
+            self._update_reward(caller);
+
+            let reward = self.rewards.read(caller);
+            if reward > 0 {
+                self.rewards.write(caller, 0);
+                let ok = IERC20Dispatcher { contract_address: self.reward_token.read() }
+                    .transfer(caller, reward);
+                assert(ok, 'Staking: transfer failed');
+                self.emit(RewardPaid { user: caller, reward });
+            }
+        }
+    }
+
+    #[generate_trait]
+    impl InternalImpl of InternalTrait {
+        fn _update_reward(ref self: ContractState, account: ContractAddress) {
+            let reward_per_token = self.reward_per_token();
+            self.reward_per_token_stored.write(reward_per_token);
+            self.last_update_time.write(get_block_timestamp());
+            self.rewards.write(account, self.earned(account));
+            self.user_reward_per_token_paid.write(account, reward_per_token);
+        }
+    }
+}
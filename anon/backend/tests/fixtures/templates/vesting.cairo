@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+// Generated by StarkFinder from the `vesting` template.
+// Compatible with OpenZeppelin Contracts for Cairo 1.0.0
+//
+// Vests the full balance of any ERC20 sent to this contract linearly to the
+// beneficiary between `start + CLIFF` and `start + DURATION`.
+
+#[starknet::contract]
+mod Golden {
+    use core::num::traits::Zero;
+    use openzeppelin::token::erc20::interface::{IERC20Dispatcher, IERC20DispatcherTrait};
+    use starknet::storage::{
+        Map, StorageMapReadAccess, StorageMapWriteAccess, StoragePointerReadAccess,
+        StoragePointerWriteAccess,
+    };
+    use starknet::{ContractAddress, get_block_timestamp, get_contract_address};
+
+    const CLIFF: u64 = 2592000;
+    const DURATION: u64 = 31536000;
+
+    #[storage]
+    struct Storage {
+        beneficiary: ContractAddress,
+        start: u64,
+        released: Map<ContractAddress, u256>,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        TokensReleased: TokensReleased,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct TokensReleased {
+        #[key]
+        token: ContractAddress,
+        amount: u256,
+    }
+
+    #[constructor]
+    fn constructor(ref self: ContractState, beneficiary: ContractAddress, start: u64) {
+        assert(!beneficiary.is_zero(), 'Vesting: zero beneficiary');
+        self.beneficiary.write(beneficiary);
+        self.start.write(start);
+    }
+
+    #[generate_trait]
+    #[abi(per_item)]
+    impl VestingImpl of VestingTrait {
+        #[external(v0)]
+        fn beneficiary(self: @ContractState) -> ContractAddress {
+            self.beneficiary.read()
+        }
+
+        #[external(v0)]
+        fn start(self: @ContractState) -> u64 {
+            self.start.read()
+        }
+
+        #[external(v0)]
+        fn cliff(self: @ContractState) -> u64 {
+            self.start.read() + CLIFF
+        }
+
+        #[external(v0)]
+        fn end(self: @ContractState) -> u64 {
+            self.start.read() + DURATION
+        }
+
+        #[external(v0)]
+        fn released(self: @ContractState, token: ContractAddress) -> u256 {
+            self.released.read(token)
+        }
+
+        #[external(v0)]
+        fn vested_amount(self: @ContractState, token: ContractAddress, timestamp: u64) -> u256 {
+            let balance = IERC20Dispatcher { contract_address: token }
+                .balance_of(get_contract_address());
+            let total = balance + self.released.read(token);
+            let start = self.start.read();
+
+            if timestamp < start + CLIFF {
+                0
+            } else if timestamp >= start + DURATION {
+                total
+            } else {
+                let elapsed: u256 = (timestamp - start).into();
+                total * elapsed / DURATION.into()
+            }
+        }
+
+        #[external(v0)]
+        fn releasable(self: @ContractState, token: ContractAddress) -> u256 {
+            self.vested_amount(token, get_block_timestamp()) - self.released.read(token)
+        }
+
+        #[external(v0)]
+        fn release(ref self: ContractState, token: ContractAddress) {
+            let amount = self.releasable(token);
+            assert(amount > 0, 'Vesting: nothing to release');
+
+            self.released.write(token, self.released.read(token) + amount);
+            let ok = IERC20Dispatcher { contract_address: token }
+                .transfer(self.beneficiary.read(), amount);
+            assert(ok, 'Vesting: transfer failed');
+
+            self.emit(TokensReleased { token, amount });
+        }
+    }
+}
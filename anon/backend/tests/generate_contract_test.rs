@@ -35,15 +35,15 @@ async fn create_test_server() -> (TestServer, PgPool) {
         .await
         .expect("Failed to run migrations");
 
-    let state = AppState { pool: pool.clone() };
+    let state = AppState::new(pool.clone()).unwrap();
     let app = backend::create_app(state);
 
     let server = TestServer::new(app).unwrap();
     (server, pool)
 }
 
-// Test helper to create a test user
-async fn create_test_user(pool: &PgPool) -> i64 {
+// Test helper to create a test user; returns its id and an access token
+async fn create_test_user(pool: &PgPool) -> (i64, String) {
     use std::time::{SystemTime, UNIX_EPOCH};
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -60,7 +60,7 @@ async fn create_test_user(pool: &PgPool) -> i64 {
         .await
         .expect("Failed to create test user");
 
-    user_id.0
+    (user_id.0, create_jwt_token(&wallet))
 }
 
 // Test helper to clean up test data
@@ -77,7 +77,7 @@ async fn cleanup_test_data(pool: &PgPool) {
 #[tokio::test]
 async fn test_generate_contract_success() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
@@ -89,10 +89,14 @@ async fn test_generate_contract_success() {
             "symbol": "TTK",
             "decimals": 18
         },
-        "template_id": "token_v1"
+        "template_id": "erc20"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::CREATED);
 
@@ -103,7 +107,11 @@ async fn test_generate_contract_success() {
     assert_eq!(response_body["contract_type"], "token");
     assert_eq!(response_body["contract_name"], "MyToken");
     assert_eq!(response_body["description"], "A test token contract");
-    assert_eq!(response_body["template_id"], "token_v1");
+    assert_eq!(response_body["template_id"], "erc20");
+    let generated_code = response_body["generated_code"].as_str().unwrap();
+    assert!(generated_code.contains("mod MyToken {"));
+    assert!(generated_code.contains("const DECIMALS: u8 = 18;"));
+    assert!(generated_code.contains("\"TestToken\""));
     assert_eq!(response_body["status"], "generated");
     assert!(!response_body["generated_code"].as_str().unwrap().is_empty());
     assert!(response_body["contract_id"].as_i64().unwrap() > 0);
@@ -127,15 +135,19 @@ async fn test_generate_contract_success() {
 #[tokio::test]
 async fn test_generate_contract_minimal_request() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
-        "contract_type": "simple",
+        "contract_type": "counter",
         "contract_name": "SimpleContract"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::CREATED);
 
@@ -143,11 +155,12 @@ async fn test_generate_contract_minimal_request() {
 
     // Verify minimal fields work
     assert_eq!(response_body["user_id"], user_id);
-    assert_eq!(response_body["contract_type"], "simple");
+    assert_eq!(response_body["contract_type"], "counter");
     assert_eq!(response_body["contract_name"], "SimpleContract");
     assert_eq!(response_body["description"], Value::Null);
     assert_eq!(response_body["parameters"], Value::Null);
-    assert_eq!(response_body["template_id"], Value::Null);
+    // Resolved from the contract_type alias
+    assert_eq!(response_body["template_id"], "ownable_counter");
     assert_eq!(response_body["status"], "generated");
 
     cleanup_test_data(&pool).await;
@@ -156,14 +169,19 @@ async fn test_generate_contract_minimal_request() {
 #[tokio::test]
 async fn test_generate_contract_user_not_found() {
     let (server, _pool) = create_test_server().await;
+    // Signed in but never registered
+    let token = create_jwt_token("0x0123456789abcdef");
 
     let request_body = json!({
-        "user_id": 99999,
         "contract_type": "token",
         "contract_name": "MyToken"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
 
@@ -174,14 +192,18 @@ async fn test_generate_contract_user_not_found() {
 #[tokio::test]
 async fn test_generate_contract_missing_contract_type() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
         "contract_name": "MyToken"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
 
@@ -194,14 +216,18 @@ async fn test_generate_contract_missing_contract_type() {
 #[tokio::test]
 async fn test_generate_contract_missing_contract_name() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
         "contract_type": "token"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
 
@@ -214,7 +240,7 @@ async fn test_generate_contract_missing_contract_name() {
 #[tokio::test]
 async fn test_generate_contract_empty_contract_type() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
@@ -222,7 +248,11 @@ async fn test_generate_contract_empty_contract_type() {
         "contract_name": "MyToken"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 
@@ -234,7 +264,7 @@ async fn test_generate_contract_empty_contract_type() {
 #[tokio::test]
 async fn test_generate_contract_empty_contract_name() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
@@ -242,7 +272,11 @@ async fn test_generate_contract_empty_contract_name() {
         "contract_name": ""
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 
@@ -254,7 +288,7 @@ async fn test_generate_contract_empty_contract_name() {
 #[tokio::test]
 async fn test_generate_contract_contract_type_too_long() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let long_contract_type = "a".repeat(101);
     let request_body = json!({
@@ -263,7 +297,11 @@ async fn test_generate_contract_contract_type_too_long() {
         "contract_name": "MyToken"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 
@@ -278,7 +316,7 @@ async fn test_generate_contract_contract_type_too_long() {
 #[tokio::test]
 async fn test_generate_contract_contract_name_too_long() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let long_contract_name = "a".repeat(201);
     let request_body = json!({
@@ -287,7 +325,11 @@ async fn test_generate_contract_contract_name_too_long() {
         "contract_name": long_contract_name
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 
@@ -302,7 +344,7 @@ async fn test_generate_contract_contract_name_too_long() {
 #[tokio::test]
 async fn test_generate_contract_description_too_long() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let long_description = "a".repeat(1001);
     let request_body = json!({
@@ -312,7 +354,11 @@ async fn test_generate_contract_description_too_long() {
         "description": long_description
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 
@@ -327,7 +373,7 @@ async fn test_generate_contract_description_too_long() {
 #[tokio::test]
 async fn test_generate_contract_template_id_too_long() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let long_template_id = "a".repeat(101);
     let request_body = json!({
@@ -337,7 +383,11 @@ async fn test_generate_contract_template_id_too_long() {
         "template_id": long_template_id
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 
@@ -352,7 +402,7 @@ async fn test_generate_contract_template_id_too_long() {
 #[tokio::test]
 async fn test_generate_contract_with_whitespace_only() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
@@ -360,7 +410,11 @@ async fn test_generate_contract_with_whitespace_only() {
         "contract_name": "MyToken"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 
@@ -372,16 +426,21 @@ async fn test_generate_contract_with_whitespace_only() {
 #[tokio::test]
 async fn test_generate_contract_multiple_contracts_same_user() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     // Generate first contract
     let request_body_1 = json!({
         "user_id": user_id,
         "contract_type": "token",
-        "contract_name": "FirstToken"
+        "contract_name": "FirstToken",
+        "parameters": {"name": "First Token", "symbol": "FST"}
     });
 
-    let response_1 = server.post("/generate").json(&request_body_1).await;
+    let response_1 = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body_1)
+        .await;
 
     assert_eq!(response_1.status_code(), StatusCode::CREATED);
     let contract_1: Value = response_1.json();
@@ -390,10 +449,15 @@ async fn test_generate_contract_multiple_contracts_same_user() {
     let request_body_2 = json!({
         "user_id": user_id,
         "contract_type": "nft",
-        "contract_name": "MyNFT"
+        "contract_name": "MyNFT",
+        "parameters": {"name": "My NFT", "symbol": "MNFT"}
     });
 
-    let response_2 = server.post("/generate").json(&request_body_2).await;
+    let response_2 = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body_2)
+        .await;
 
     assert_eq!(response_2.status_code(), StatusCode::CREATED);
     let contract_2: Value = response_2.json();
@@ -419,15 +483,19 @@ async fn test_generate_contract_multiple_contracts_same_user() {
 #[tokio::test]
 async fn test_generate_contract_generated_code_structure() {
     let (server, pool) = create_test_server().await;
-    let user_id = create_test_user(&pool).await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
         "user_id": user_id,
-        "contract_type": "custom",
+        "contract_type": "ownable_counter",
         "contract_name": "CustomContract"
     });
 
-    let response = server.post("/generate").json(&request_body).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
 
     assert_eq!(response.status_code(), StatusCode::CREATED);
 
@@ -436,48 +504,256 @@ async fn test_generate_contract_generated_code_structure() {
 
     // Verify the generated code contains expected elements
     assert!(generated_code.contains("#[starknet::contract]"));
-    assert!(generated_code.contains("mod customcontract"));
+    assert!(generated_code.contains("mod CustomContract {"));
     assert!(generated_code.contains("struct Storage"));
     assert!(generated_code.contains("fn constructor"));
-    assert!(generated_code.contains("CustomContract"));
-    assert!(generated_code.contains("custom"));
+    assert!(!generated_code.contains("TODO"));
 
     cleanup_test_data(&pool).await;
 }
 
+#[tokio::test]
+async fn test_generate_contract_unknown_contract_type() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "custom",
+            "contract_name": "CustomContract"
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let error_body: Value = response.json();
+    assert_eq!(error_body["error"], "unknown contract_type");
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "token",
+            "contract_name": "MyToken",
+            "template_id": "erc721"
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let error_body: Value = response.json();
+    assert_eq!(
+        error_body["error"],
+        "template_id does not match contract_type"
+    );
+}
+
+#[tokio::test]
+async fn test_generate_contract_invalid_parameters() {
+    let (server, pool) = create_test_server().await;
+    let (user_id, token) = create_test_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "erc20",
+            "contract_name": "MyToken",
+            "parameters": {"decimals": 300, "symbol": "", "mintable": "yes"}
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let error_body: Value = response.json();
+    assert_eq!(error_body["error"], "validation failed");
+    assert_eq!(
+        error_body["details"],
+        json!([
+            {"field": "parameters.name", "message": "is required"},
+            {"field": "parameters.decimals", "message": "must fit in u8"},
+            {"field": "parameters.mintable", "message": "must be a boolean"}
+        ])
+    );
+
+    // Nothing is stored for a rejected request
+    let count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM generated_contracts WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(count.0, 0);
+}
+
+#[tokio::test]
+async fn test_generate_contract_returns_scarb_config() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "erc20",
+            "contract_name": "Gold Token",
+            "parameters": {"name": "Gold", "symbol": "GLD"},
+            "cairo_version": "2.10.1"
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let body: Value = response.json();
+    let scarb_config = body["scarb_config"].as_str().unwrap();
+    assert!(scarb_config.contains("name = \"gold_token\""));
+    assert!(scarb_config.contains("cairo-version = \"2.10.1\""));
+    assert!(scarb_config.contains("starknet = \"2.10.1\""));
+    assert!(scarb_config.contains("openzeppelin = \"1.0.0\""));
+    assert!(scarb_config.contains("[[target.starknet-contract]]"));
+
+    let (stored,): (Option<String>,) =
+        sqlx::query_as("SELECT scarb_config FROM generated_contracts WHERE id = $1")
+            .bind(body["contract_id"].as_i64().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(stored.as_deref(), Some(scarb_config));
+}
+
+#[tokio::test]
+async fn test_generate_contract_rejects_unsupported_cairo_version() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+
+    for (cairo_version, error) in [
+        ("2.6.0", "unsupported cairo_version"),
+        ("latest", "invalid cairo_version"),
+    ] {
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&json!({
+                "contract_type": "erc20",
+                "contract_name": "Gold",
+                "cairo_version": cairo_version
+            }))
+            .await;
+
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+        let body: Value = response.json();
+        assert_eq!(body["error"], error);
+    }
+}
+
 #[tokio::test]
 async fn test_generate_contract_invalid_json() {
-    let (server, _pool) = create_test_server().await;
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
 
     let response = server
         .post("/generate")
+        .authorization_bearer(&token)
         .json(&json!({"invalid": "json"}))
         .await;
 
     assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    cleanup_test_data(&pool).await;
 }
 
 #[tokio::test]
-async fn test_generate_contract_missing_user_id() {
+async fn test_generate_contract_without_user_id_uses_token() {
     let (server, pool) = create_test_server().await;
+    let (user_id, token) = create_test_user(&pool).await;
 
     let request_body = json!({
+        "contract_type": "token",
+        "contract_name": "MyToken",
+        "parameters": {"name": "My Token", "symbol": "MTK"}
+    });
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request_body)
+        .await;
+
+    // The owner comes from the token
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let response_body: Value = response.json();
+    assert_eq!(response_body["user_id"], user_id);
+
+    cleanup_test_data(&pool).await;
+}
+
+#[tokio::test]
+async fn test_generate_contract_requires_auth() {
+    let (server, pool) = create_test_server().await;
+    let (user_id, _) = create_test_user(&pool).await;
+
+    let request_body = json!({
+        "user_id": user_id,
         "contract_type": "token",
         "contract_name": "MyToken"
     });
 
     let response = server.post("/generate").json(&request_body).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
 
-    // This should fail due to missing user_id field
-    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    let response = server
+        .post("/generate")
+        .authorization_bearer("not-a-jwt")
+        .json(&request_body)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM generated_contracts WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(count.0, 0);
+
+    cleanup_test_data(&pool).await;
+}
+
+#[tokio::test]
+async fn test_generate_contract_rejects_other_users_id() {
+    let (server, pool) = create_test_server().await;
+    let (victim_id, _) = create_test_user(&pool).await;
+    let (_, attacker_token) = create_test_user(&pool).await;
+
+    let request_body = json!({
+        "user_id": victim_id,
+        "contract_type": "token",
+        "contract_name": "MyToken"
+    });
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&attacker_token)
+        .json(&request_body)
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let error_body: Value = response.json();
+    assert_eq!(error_body["error"], "user_id does not match token");
+
+    let count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM generated_contracts WHERE user_id = $1")
+            .bind(victim_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(count.0, 0);
 
     cleanup_test_data(&pool).await;
 }
 
 // Test helper to create JWT token for a wallet
 fn create_jwt_token(wallet: &str) -> String {
-    let secret = backend::libs::jwt::secret_from_env();
-    backend::libs::jwt::encode(wallet, &secret).unwrap()
+    let keys = backend::libs::jwt::KeyRing::from_env().unwrap();
+    backend::libs::jwt::encode(wallet, &keys).unwrap()
 }
 
 // Test helper to create a test user with profile
@@ -513,6 +789,7 @@ async fn create_test_user_with_profile(pool: &PgPool) -> (i64, String) {
 async fn test_list_generated_contracts_success() {
     let (server, pool) = create_test_server().await;
     let (user_id, wallet) = create_test_user_with_profile(&pool).await;
+    let token = create_jwt_token(&wallet);
 
     // Create some test contracts
     let contract_data = vec![
@@ -520,14 +797,19 @@ async fn test_list_generated_contracts_success() {
             "token",
             "MyToken",
             Some("A test token"),
-            Some(json!({"symbol": "MTK"})),
+            Some(json!({"name": "My Token", "symbol": "MTK"})),
+        ),
+        (
+            "nft",
+            "MyNFT",
+            None,
+            Some(json!({"name": "My NFT", "symbol": "MNFT"})),
         ),
-        ("nft", "MyNFT", None, None),
         (
-            "custom",
+            "counter",
             "CustomContract",
             Some("Custom implementation"),
-            Some(json!({"version": "1.0"})),
+            Some(json!({"step": 2})),
         ),
     ];
 
@@ -542,7 +824,11 @@ async fn test_list_generated_contracts_success() {
             "parameters": parameters
         });
 
-        let response = server.post("/generate").json(&request_body).await;
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&request_body)
+            .await;
         assert_eq!(response.status_code(), StatusCode::CREATED);
 
         let contract: Value = response.json();
@@ -550,7 +836,6 @@ async fn test_list_generated_contracts_success() {
     }
 
     // Test listing contracts
-    let token = create_jwt_token(&wallet);
     let response = server
         .get("/generated_contracts")
         .authorization_bearer(&token)
@@ -615,21 +900,26 @@ async fn test_list_generated_contracts_empty() {
 async fn test_list_generated_contracts_pagination() {
     let (server, pool) = create_test_server().await;
     let (user_id, wallet) = create_test_user_with_profile(&pool).await;
+    let token = create_jwt_token(&wallet);
 
     // Create 5 test contracts
     for i in 0..5 {
         let request_body = json!({
             "user_id": user_id,
             "contract_type": "token",
-            "contract_name": format!("Token{}", i)
+            "contract_name": format!("Token{}", i),
+            "parameters": {"name": format!("Token {}", i), "symbol": "TKN"}
         });
 
-        let response = server.post("/generate").json(&request_body).await;
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&request_body)
+            .await;
         assert_eq!(response.status_code(), StatusCode::CREATED);
     }
 
     // Test pagination with limit 2
-    let token = create_jwt_token(&wallet);
     let response = server
         .get("/generated_contracts?limit=2")
         .authorization_bearer(&token)
@@ -704,11 +994,9 @@ async fn test_list_generated_contracts_invalid_cursor() {
         .authorization_bearer(&token)
         .await;
 
-    // Should handle gracefully - either return empty or first page
-    assert_eq!(response.status_code(), StatusCode::OK);
-
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
     let response_body: Value = response.json();
-    assert!(response_body["items"].is_array());
+    assert_eq!(response_body["error"], "invalid cursor");
 
     cleanup_test_data(&pool).await;
 }
@@ -717,21 +1005,25 @@ async fn test_list_generated_contracts_invalid_cursor() {
 async fn test_list_generated_contracts_limit_bounds() {
     let (server, pool) = create_test_server().await;
     let (user_id, wallet) = create_test_user_with_profile(&pool).await;
+    let token = create_jwt_token(&wallet);
 
     // Create some contracts
     for i in 0..10 {
         let request_body = json!({
             "user_id": user_id,
             "contract_type": "token",
-            "contract_name": format!("Token{}", i)
+            "contract_name": format!("Token{}", i),
+            "parameters": {"name": format!("Token {}", i), "symbol": "TKN"}
         });
 
-        let response = server.post("/generate").json(&request_body).await;
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&request_body)
+            .await;
         assert_eq!(response.status_code(), StatusCode::CREATED);
     }
 
-    let token = create_jwt_token(&wallet);
-
     // Test minimum limit (should default to 1)
     let response = server
         .get("/generated_contracts?limit=0")
@@ -764,16 +1056,23 @@ async fn test_list_generated_contracts_user_isolation() {
     // Create two users
     let (user1_id, wallet1) = create_test_user_with_profile(&pool).await;
     let (user2_id, wallet2) = create_test_user_with_profile(&pool).await;
+    let token1 = create_jwt_token(&wallet1);
+    let token2 = create_jwt_token(&wallet2);
 
     // Create contracts for user1
     for i in 0..3 {
         let request_body = json!({
             "user_id": user1_id,
             "contract_type": "token",
-            "contract_name": format!("User1Token{}", i)
+            "contract_name": format!("User1Token{}", i),
+            "parameters": {"name": "User Token", "symbol": "UTK"}
         });
 
-        let response = server.post("/generate").json(&request_body).await;
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token1)
+            .json(&request_body)
+            .await;
         assert_eq!(response.status_code(), StatusCode::CREATED);
     }
 
@@ -782,15 +1081,19 @@ async fn test_list_generated_contracts_user_isolation() {
         let request_body = json!({
             "user_id": user2_id,
             "contract_type": "nft",
-            "contract_name": format!("User2NFT{}", i)
+            "contract_name": format!("User2NFT{}", i),
+            "parameters": {"name": "User NFT", "symbol": "UNFT"}
         });
 
-        let response = server.post("/generate").json(&request_body).await;
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token2)
+            .json(&request_body)
+            .await;
         assert_eq!(response.status_code(), StatusCode::CREATED);
     }
 
     // Test user1 can only see their contracts
-    let token1 = create_jwt_token(&wallet1);
     let response1 = server
         .get("/generated_contracts")
         .authorization_bearer(&token1)
@@ -808,7 +1111,6 @@ async fn test_list_generated_contracts_user_isolation() {
     }
 
     // Test user2 can only see their contracts
-    let token2 = create_jwt_token(&wallet2);
     let response2 = server
         .get("/generated_contracts")
         .authorization_bearer(&token2)
@@ -827,3 +1129,526 @@ async fn test_list_generated_contracts_user_isolation() {
 
     cleanup_test_data(&pool).await;
 }
+
+#[tokio::test]
+async fn test_openapi_publishes_parameter_schemas() {
+    let (server, _pool) = create_test_server().await;
+
+    let doc: Value = server.get("/api-docs/openapi.json").await.json();
+    let schemas = &doc["components"]["schemas"];
+    assert_eq!(schemas["Erc20Parameters"]["type"], "object");
+    assert_eq!(
+        schemas["Erc20Parameters"]["properties"]["decimals"]["default"],
+        18
+    );
+    assert!(schemas["DaoGovernorParameters"]["properties"]["quorum"].is_object());
+}
+
+async fn generate_erc20(server: &TestServer, token: &str) -> i64 {
+    let response = server
+        .post("/generate")
+        .authorization_bearer(token)
+        .json(&json!({
+            "contract_type": "erc20",
+            "contract_name": "Gold Token",
+            "parameters": {"name": "Gold", "symbol": "GLD", "mintable": true}
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    response.json::<Value>()["contract_id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_download_archive_zip() {
+    use std::io::Read;
+
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+    let id = generate_erc20(&server, &token).await;
+    let (stored,): (String,) =
+        sqlx::query_as("SELECT scarb_config FROM generated_contracts WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    // Renaming keeps the package and module the code was generated with
+    let response = server
+        .patch(&format!("/generated_contracts/{}", id))
+        .authorization_bearer(&token)
+        .json(&json!({"contract_name": "Silver Token"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/archive", id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.header("content-type"), "application/zip");
+    assert_eq!(
+        response.header("content-disposition"),
+        "attachment; filename=\"gold_token.zip\""
+    );
+
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(response.as_bytes().to_vec())).unwrap();
+    let mut read = |name: &str| {
+        let mut s = String::new();
+        zip.by_name(name).unwrap().read_to_string(&mut s).unwrap();
+        s
+    };
+    assert!(read("gold_token/src/lib.cairo").contains("mod GoldToken {"));
+    assert!(read("gold_token/tests/test_contract.cairo").contains("declare(\"GoldToken\")"));
+    assert!(read("gold_token/README.md").contains("`erc20` template"));
+    let manifest = read("gold_token/Scarb.toml");
+    assert!(manifest.starts_with(&stored));
+    assert!(manifest.contains("openzeppelin = \"1.0.0\""));
+    assert!(manifest.contains("[dev-dependencies]\nsnforge_std = "));
+    assert!(manifest.contains("test = \"snforge test\""));
+}
+
+#[tokio::test]
+async fn test_download_archive_tar_gz() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+    let id = generate_erc20(&server, &token).await;
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/archive", id))
+        .add_query_param("format", "tar.gz")
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.header("content-type"), "application/gzip");
+
+    let bytes = response.as_bytes().to_vec();
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(&bytes[..]));
+    let paths: Vec<String> = tar
+        .entries()
+        .unwrap()
+        .map(|e| e.unwrap().path().unwrap().display().to_string())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "gold_token/Scarb.toml",
+            "gold_token/src/lib.cairo",
+            "gold_token/tests/test_contract.cairo",
+            "gold_token/README.md"
+        ]
+    );
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/archive", id))
+        .add_query_param("format", "rar")
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_download_archive_only_for_owner() {
+    let (server, pool) = create_test_server().await;
+    let (_, owner_token) = create_test_user(&pool).await;
+    let (_, other_token) = create_test_user(&pool).await;
+    let id = generate_erc20(&server, &owner_token).await;
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/archive", id))
+        .authorization_bearer(&other_token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/archive", id))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_get_generated_contract() {
+    let (server, pool) = create_test_server().await;
+    let (user_id, token) = create_test_user(&pool).await;
+    let (_, other_token) = create_test_user(&pool).await;
+    let id = generate_erc20(&server, &token).await;
+    let path = format!("/generated_contracts/{}", id);
+
+    let response = server.get(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let contract: Value = response.json();
+    assert_eq!(contract["contract_id"], id);
+    assert_eq!(contract["user_id"], user_id);
+    assert_eq!(contract["contract_name"], "Gold Token");
+    assert_eq!(
+        contract["parameters"],
+        json!({"name": "Gold", "symbol": "GLD", "mintable": true})
+    );
+    assert!(
+        contract["generated_code"]
+            .as_str()
+            .unwrap()
+            .contains("mod GoldToken {")
+    );
+
+    let response = server.get(&path).authorization_bearer(&other_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server
+        .get("/generated_contracts/999999999")
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server.get(&path).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_update_generated_contract() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+    let (_, other_token) = create_test_user(&pool).await;
+    let id = generate_erc20(&server, &token).await;
+    let path = format!("/generated_contracts/{}", id);
+
+    let response = server
+        .patch(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"contract_name": "Silver Token", "description": "Renamed"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let contract: Value = response.json();
+    assert_eq!(contract["contract_name"], "Silver Token");
+    assert_eq!(contract["description"], "Renamed");
+    assert_eq!(contract["status"], "generated");
+
+    // Omitted fields are kept, null clears the description
+    let contract: Value = server
+        .patch(&path)
+        .authorization_bearer(&token)
+        .json(&json!({"description": null, "status": "archived"}))
+        .await
+        .json();
+    assert_eq!(contract["contract_name"], "Silver Token");
+    assert_eq!(contract["description"], Value::Null);
+    assert_eq!(contract["status"], "archived");
+
+    for body in [
+        json!({}),
+        json!({"contract_name": "  "}),
+        json!({"contract_name": "a".repeat(201)}),
+        json!({"description": "a".repeat(1001)}),
+        json!({"status": "compiled"}),
+    ] {
+        let response = server
+            .patch(&path)
+            .authorization_bearer(&token)
+            .json(&body)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST, "{}", body);
+    }
+
+    let response = server
+        .patch(&path)
+        .authorization_bearer(&other_token)
+        .json(&json!({"contract_name": "Stolen"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Queued generations belong to the worker until it is done
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let response = server
+        .patch(&format!("/generated_contracts/{}", job["contract_id"]))
+        .authorization_bearer(&token)
+        .json(&json!({"contract_name": "D"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_archive_keeps_contract_status() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+    let id = generate_erc20(&server, &token).await;
+    let path = format!("/generated_contracts/{}", id);
+    let set_status = |status: &'static str| {
+        server
+            .patch(&path)
+            .authorization_bearer(&token)
+            .json(&json!({ "status": status }))
+    };
+
+    // Archived and back, a generated contract is generated again
+    let contract: Value = set_status("archived").await.json();
+    assert_eq!(contract["status"], "archived");
+    let contract: Value = set_status("generated").await.json();
+    assert_eq!(contract["status"], "generated");
+
+    let response = server
+        .post(&format!("{}/deployments", path))
+        .authorization_bearer(&token)
+        .json(&json!({
+            "network": "sepolia",
+            "contract_address": format!("0x{:x}", rand::random::<u64>()),
+            "class_hash": "0xabc",
+            "transaction_hash": "0xdef"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    // Unarchiving what is not archived changes nothing
+    let contract: Value = set_status("generated").await.json();
+    assert_eq!(contract["status"], "deployed");
+    let contract: Value = set_status("archived").await.json();
+    assert_eq!(contract["status"], "archived");
+    let contract: Value = set_status("archived").await.json();
+    assert_eq!(contract["status"], "archived");
+    let contract: Value = set_status("generated").await.json();
+    assert_eq!(contract["status"], "deployed");
+
+    // A queued generation cannot be archived until it is done
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let response = server
+        .patch(&format!("/generated_contracts/{}", job["contract_id"]))
+        .authorization_bearer(&token)
+        .json(&json!({"status": "archived"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_delete_generated_contract() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+    let (_, other_token) = create_test_user(&pool).await;
+    let id = generate_erc20(&server, &token).await;
+    let path = format!("/generated_contracts/{}", id);
+
+    let response = server
+        .delete(&path)
+        .authorization_bearer(&other_token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server.delete(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    // Gone from every route, but kept in the database
+    for sub in ["", "/archive", "/revisions"] {
+        let response = server
+            .get(&format!("{}{}", path, sub))
+            .authorization_bearer(&token)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND, "{}", sub);
+    }
+    let list: Value = server
+        .get("/generated_contracts")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"], json!([]));
+    let response = server.delete(&path).authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let (deleted,): (bool,) =
+        sqlx::query_as("SELECT deleted_at IS NOT NULL FROM generated_contracts WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(deleted);
+
+    // A queued generation is cancelled with its contract
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let response = server
+        .delete(&format!("/generated_contracts/{}", job["contract_id"]))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    let (status,): (String,) = sqlx::query_as("SELECT status FROM generation_jobs WHERE id = $1")
+        .bind(job["id"].as_i64().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "failed");
+    let (status,): (String,) =
+        sqlx::query_as("SELECT status FROM generated_contracts WHERE id = $1")
+            .bind(job["contract_id"].as_i64().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "failed");
+}
+
+// Follows `next_cursor` from `query` to the end and returns the names in order
+async fn list_all_names(server: &TestServer, token: &str, query: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut path = format!("/generated_contracts?limit=2&{}", query);
+        if let Some(c) = &cursor {
+            path.push_str(&format!("&cursor={}", c));
+        }
+        let response = server.get(&path).authorization_bearer(token).await;
+        assert_eq!(response.status_code(), StatusCode::OK, "{}", path);
+        let body: Value = response.json();
+        for item in body["items"].as_array().unwrap() {
+            names.push(item["contract_name"].as_str().unwrap().to_string());
+        }
+        match body["next_cursor"].as_str() {
+            Some(c) => cursor = Some(c.to_string()),
+            None => return names,
+        }
+    }
+}
+
+async fn sorted_names(server: &TestServer, token: &str, query: &str) -> Vec<String> {
+    let mut names = list_all_names(server, token, query).await;
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn test_list_generated_contracts_filters_and_search() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+
+    for (contract_type, name, description) in [
+        ("erc20", "Gold Token", Some("Shiny reward points")),
+        ("erc20", "Silver Coin", None),
+        ("counter", "Click Counter", Some("Counts clicks_total")),
+        ("nft", "Art Pieces", Some("Gallery of tokens")),
+    ] {
+        let parameters = (contract_type != "counter").then(|| json!({"name": name, "symbol": "X"}));
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&json!({
+                "contract_type": contract_type,
+                "contract_name": name,
+                "description": description,
+                "parameters": parameters
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+    let archived: Value = server
+        .get("/generated_contracts?contract_type=counter")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    server
+        .patch(&format!(
+            "/generated_contracts/{}",
+            archived["items"][0]["id"]
+        ))
+        .authorization_bearer(&token)
+        .json(&json!({"status": "archived"}))
+        .await;
+
+    let names = |query: &'static str| sorted_names(&server, &token, query);
+    assert_eq!(
+        names("contract_type=erc20").await,
+        ["Gold Token", "Silver Coin"]
+    );
+    assert_eq!(names("status=archived").await, ["Click Counter"]);
+    assert_eq!(names("template_id=erc721").await, ["Art Pieces"]);
+    assert_eq!(
+        names("contract_type=erc20&template_id=erc721").await,
+        Vec::<String>::new()
+    );
+    // Whole words through full-text search, any substring through ILIKE
+    assert_eq!(names("q=shiny").await, ["Gold Token"]);
+    assert_eq!(names("q=token").await, ["Art Pieces", "Gold Token"]);
+    assert_eq!(names("q=ilver").await, ["Silver Coin"]);
+    // LIKE wildcards are literal
+    assert_eq!(names("q=s_t").await, ["Click Counter"]);
+    assert_eq!(names("q=%25").await, Vec::<String>::new());
+
+    assert_eq!(names("created_after=2000-01-01T00:00:00Z").await.len(), 4);
+    assert_eq!(
+        names("created_before=2000-01-01T00:00:00Z").await,
+        Vec::<String>::new()
+    );
+
+    let response = server
+        .get(&format!("/generated_contracts?q={}", "a".repeat(201)))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_list_generated_contracts_sorting() {
+    let (server, pool) = create_test_server().await;
+    let (_, token) = create_test_user(&pool).await;
+
+    // Equal names and equal creation times make ids break the ties
+    let mut ids = Vec::new();
+    for name in ["Beta", "alpha", "Gamma", "Beta", "Delta"] {
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&json!({"contract_type": "counter", "contract_name": name}))
+            .await;
+        ids.push(response.json::<Value>()["contract_id"].as_i64().unwrap());
+    }
+    sqlx::query("UPDATE generated_contracts SET created_at = '2024-01-01' WHERE id = ANY($1)")
+        .bind(&ids[1..4])
+        .execute(&pool)
+        .await
+        .unwrap();
+    // Touching Beta makes it the most recently updated
+    server
+        .patch(&format!("/generated_contracts/{}", ids[0]))
+        .authorization_bearer(&token)
+        .json(&json!({"description": "touched"}))
+        .await;
+
+    let created_desc = list_all_names(&server, &token, "").await;
+    assert_eq!(created_desc, ["Delta", "Beta", "Beta", "Gamma", "alpha"]);
+    let created_asc = list_all_names(&server, &token, "order=asc").await;
+    assert_eq!(created_asc, ["alpha", "Gamma", "Beta", "Beta", "Delta"]);
+
+    let updated = list_all_names(&server, &token, "sort=updated_at").await;
+    assert_eq!(updated[0], "Beta");
+    assert_eq!(updated.len(), 5);
+
+    let mut by_name = list_all_names(&server, &token, "sort=contract_name").await;
+    let mut expected = by_name.clone();
+    expected.sort();
+    assert_eq!(by_name, expected);
+    by_name.reverse();
+    assert_eq!(
+        list_all_names(&server, &token, "sort=contract_name&order=desc").await,
+        by_name
+    );
+
+    // A cursor only continues the listing it came from
+    let page: Value = server
+        .get("/generated_contracts?limit=2&sort=contract_name")
+        .authorization_bearer(&token)
+        .await
+        .json();
+    let response = server
+        .get(&format!(
+            "/generated_contracts?limit=2&cursor={}",
+            page["next_cursor"].as_str().unwrap()
+        ))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,221 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{Json, http::StatusCode};
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use backend::libs::generator::{GeneratorKind, Generators, LlmGenerator};
+use common::create_user;
+
+// Test helper to create a test server generating with `generators`
+async fn create_test_server(generators: Generators) -> (TestServer, PgPool) {
+    let (server, _, pool) =
+        common::create_test_server_with(|state| state.generators = Arc::new(generators)).await;
+    (server, pool)
+}
+
+const LLM_REPLY: &str = "Sure! Here is the contract:\n\n```cairo\n#[starknet::contract]\nmod Raffle {\n    #[storage]\n    struct Storage {}\n}\n```\n";
+
+// Minimal OpenAI-compatible endpoint answering every chat completion with
+// `reply`; returns its base URL and the last request body it saw.
+async fn spawn_mock_llm(status: StatusCode, reply: &'static str) -> (String, Arc<Mutex<Value>>) {
+    let seen = Arc::new(Mutex::new(Value::Null));
+    let seen_by_handler = seen.clone();
+    let app = axum::Router::new().route(
+        "/v1/chat/completions",
+        axum::routing::post(move |Json(req): Json<Value>| {
+            let seen = seen_by_handler.clone();
+            async move {
+                *seen.lock().unwrap() = req;
+                (
+                    status,
+                    Json(json!({
+                        "id": "chatcmpl-test",
+                        "object": "chat.completion",
+                        "choices": [{
+                            "index": 0,
+                            "message": {"role": "assistant", "content": reply},
+                            "finish_reason": "stop"
+                        }]
+                    })),
+                )
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/v1", addr), seen)
+}
+
+fn with_llm(base_url: &str) -> Generators {
+    Generators {
+        llm: Some(Arc::new(LlmGenerator::new(
+            base_url,
+            "test-model",
+            Some("sk-test".into()),
+            std::time::Duration::from_secs(5),
+        ))),
+        ..Generators::default()
+    }
+}
+
+#[tokio::test]
+async fn test_llm_generates_free_form_contract() {
+    let (url, seen) = spawn_mock_llm(StatusCode::OK, LLM_REPLY).await;
+    let (server, pool) = create_test_server(with_llm(&url)).await;
+    let token = create_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "raffle",
+            "contract_name": "Raffle",
+            "description": "Weekly raffle paid in STRK",
+            "parameters": {"ticket_price": "10"},
+            "generator": "llm"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let body: Value = response.json();
+    assert_eq!(body["generator"], "llm");
+    assert_eq!(body["template_id"], Value::Null);
+    assert_eq!(
+        body["generated_code"],
+        "#[starknet::contract]\nmod Raffle {\n    #[storage]\n    struct Storage {}\n}\n"
+    );
+    assert!(
+        body["scarb_config"]
+            .as_str()
+            .unwrap()
+            .contains("name = \"raffle\"")
+    );
+
+    let request = seen.lock().unwrap().clone();
+    assert_eq!(request["model"], "test-model");
+    let prompt = request["messages"][1]["content"].as_str().unwrap();
+    assert!(prompt.contains("Weekly raffle paid in STRK"));
+    assert!(prompt.contains("\"ticket_price\":\"10\""));
+}
+
+#[tokio::test]
+async fn test_generator_selected_per_template() {
+    let (url, _) = spawn_mock_llm(StatusCode::OK, LLM_REPLY).await;
+    let mut generators = with_llm(&url);
+    generators
+        .by_template
+        .insert("dao_governor".into(), GeneratorKind::Llm);
+    let (server, pool) = create_test_server(generators).await;
+    let token = create_user(&pool).await;
+
+    let generate = |contract_type: &'static str, generator: Option<&'static str>| {
+        let mut body = json!({"contract_type": contract_type, "contract_name": "Raffle"});
+        if let Some(g) = generator {
+            body["generator"] = g.into();
+        }
+        server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&body)
+    };
+
+    let body: Value = generate("dao", None).await.json();
+    assert_eq!(body["generator"], "llm");
+    assert_eq!(body["template_id"], "dao_governor");
+
+    let body: Value = generate("counter", None).await.json();
+    assert_eq!(body["generator"], "template");
+
+    // The request wins over the per-template choice
+    let body: Value = generate("dao", Some("template")).await.json();
+    assert_eq!(body["generator"], "template");
+    assert!(
+        body["generated_code"]
+            .as_str()
+            .unwrap()
+            .contains("VOTING_PERIOD")
+    );
+}
+
+#[tokio::test]
+async fn test_llm_errors() {
+    let (server, pool) = create_test_server(Generators::default()).await;
+    let token = create_user(&pool).await;
+    let request = json!({
+        "contract_type": "erc20",
+        "contract_name": "Gold",
+        "parameters": {"name": "Gold", "symbol": "GLD"},
+        "generator": "llm"
+    });
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let (url, _) = spawn_mock_llm(StatusCode::OK, "I cannot write that contract.").await;
+    let (server, _) = create_test_server(with_llm(&url)).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_GATEWAY);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "llm reply contained no cairo contract");
+
+    let (url, _) = spawn_mock_llm(StatusCode::TOO_MANY_REQUESTS, LLM_REPLY).await;
+    let (server, _) = create_test_server(with_llm(&url)).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_GATEWAY);
+
+    // Replies are not read past the limit
+    let (url, _) = spawn_mock_llm(StatusCode::OK, LLM_REPLY).await;
+    let mut llm = LlmGenerator::new(&url, "test-model", None, std::time::Duration::from_secs(5));
+    llm.max_reply_bytes = 100;
+    let (server, _) = create_test_server(Generators {
+        llm: Some(Arc::new(llm)),
+        ..Generators::default()
+    })
+    .await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&request)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_GATEWAY);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "llm reply too large");
+}
+
+#[tokio::test]
+async fn test_llm_request_still_validates_template_parameters() {
+    let (url, seen) = spawn_mock_llm(StatusCode::OK, LLM_REPLY).await;
+    let (server, pool) = create_test_server(with_llm(&url)).await;
+    let token = create_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "erc20",
+            "contract_name": "Gold",
+            "parameters": {"decimals": "lots"},
+            "generator": "llm"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    // Rejected before calling out
+    assert_eq!(*seen.lock().unwrap(), Value::Null);
+}
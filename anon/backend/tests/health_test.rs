@@ -11,7 +11,7 @@ async fn create_test_app(pool: PgPool) -> axum::Router {
         .route("/health", axum::routing::get(health))
         .route("/healthz", axum::routing::get(healthz))
         .route("/db/health", axum::routing::get(db_health))
-        .with_state(AppState { pool })
+        .with_state(AppState::new(pool).unwrap())
 }
 
 #[tokio::test]
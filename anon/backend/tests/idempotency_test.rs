@@ -0,0 +1,260 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use backend::libs::idempotency::{self, Claim, StoredResponse};
+use common::{create_user, random_wallet, token_for};
+
+// Test helper to create a test server remembering keys for `ttl`
+async fn create_test_server(ttl: Duration) -> (TestServer, PgPool) {
+    let (server, _, pool) =
+        common::create_test_server_with(|state| state.idempotency_ttl = ttl).await;
+    (server, pool)
+}
+
+fn key() -> String {
+    format!("key-{:032x}", rand::random::<u128>())
+}
+
+async fn contract_count(server: &TestServer, token: &str) -> usize {
+    let list: Value = server
+        .get("/generated_contracts")
+        .authorization_bearer(token)
+        .await
+        .json();
+    list["items"].as_array().unwrap().len()
+}
+
+#[tokio::test]
+async fn test_generate_retry_is_replayed() {
+    let (server, pool) = create_test_server(Duration::from_secs(3600)).await;
+    let token = create_user(&pool).await;
+    let key = key();
+    let body = json!({"contract_type": "counter", "contract_name": "C"});
+
+    let first = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .add_header("Idempotency-Key", &key)
+        .json(&body)
+        .await;
+    assert_eq!(first.status_code(), StatusCode::CREATED);
+    assert!(first.maybe_header("idempotent-replayed").is_none());
+
+    let retry = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .add_header("Idempotency-Key", &key)
+        .json(&body)
+        .await;
+    assert_eq!(retry.status_code(), StatusCode::CREATED);
+    assert_eq!(retry.header("idempotent-replayed"), "true");
+    assert_eq!(retry.header("content-type"), "application/json");
+    assert_eq!(retry.json::<Value>(), first.json::<Value>());
+    assert_eq!(contract_count(&server, &token).await, 1);
+
+    // Same key, another request
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .add_header("Idempotency-Key", &key)
+        .json(&json!({"contract_type": "counter", "contract_name": "D"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Keys are per user
+    let other = create_user(&pool).await;
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&other)
+        .add_header("Idempotency-Key", &key)
+        .json(&body)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    assert!(response.maybe_header("idempotent-replayed").is_none());
+
+    // Without a key every request runs
+    for _ in 0..2 {
+        server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .json(&body)
+            .await;
+    }
+    assert_eq!(contract_count(&server, &token).await, 3);
+}
+
+#[tokio::test]
+async fn test_idempotency_errors_are_not_stored() {
+    let (server, pool) = create_test_server(Duration::from_secs(3600)).await;
+    let token = create_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .add_header("Idempotency-Key", "not a key")
+        .json(&json!({"contract_type": "counter", "contract_name": "C"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post("/generate")
+        .add_header("Idempotency-Key", key())
+        .json(&json!({"contract_type": "counter", "contract_name": "C"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Conflicts depend on the moment rather than the request: not stored
+    let wallet = random_wallet();
+    let unregistered = token_for(&pool, &wallet).await;
+    server
+        .post("/register")
+        .authorization_bearer(&unregistered)
+        .json(&json!({"wallet": wallet}))
+        .await
+        .assert_status(StatusCode::CREATED);
+    let conflict_key = key();
+    let response = server
+        .post("/register")
+        .authorization_bearer(&unregistered)
+        .add_header("Idempotency-Key", &conflict_key)
+        .json(&json!({"wallet": wallet}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    let (stored,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM idempotency_keys WHERE key = $1")
+        .bind(&conflict_key)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+
+    // Nor are 5xx answers, so the request can be retried
+    let key = key();
+    for _ in 0..2 {
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .add_header("Idempotency-Key", &key)
+            .json(&json!({"contract_type": "counter", "contract_name": "C", "generator": "llm"}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.maybe_header("idempotent-replayed").is_none());
+    }
+    let (stored,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM idempotency_keys WHERE key = $1")
+        .bind(&key)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+}
+
+#[tokio::test]
+async fn test_taken_over_claim_cannot_settle_key() {
+    let (_, pool) = create_test_server(Duration::from_secs(3600)).await;
+    let wallet = random_wallet();
+    let key = key();
+    let ttl = Duration::from_secs(3600);
+    let response = StoredResponse {
+        status: 201,
+        headers: Vec::new(),
+        body: b"first".to_vec(),
+    };
+
+    let Claim::New(first) = idempotency::claim(&pool, &wallet, &key, b"hash", ttl)
+        .await
+        .unwrap()
+    else {
+        panic!("key should be new");
+    };
+
+    // The first request went silent long enough for a retry to take over
+    sqlx::query(
+        "UPDATE idempotency_keys SET created_at = NOW() - INTERVAL '1 hour' WHERE key = $1",
+    )
+    .bind(&key)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let Claim::New(second) = idempotency::claim(&pool, &wallet, &key, b"hash", ttl)
+        .await
+        .unwrap()
+    else {
+        panic!("stale key should be claimed again");
+    };
+
+    idempotency::complete(&pool, &wallet, &key, first, &response)
+        .await
+        .unwrap();
+    idempotency::release(&pool, &wallet, &key, first)
+        .await
+        .unwrap();
+    assert_eq!(
+        idempotency::claim(&pool, &wallet, &key, b"hash", ttl)
+            .await
+            .unwrap(),
+        Claim::InProgress
+    );
+
+    idempotency::complete(&pool, &wallet, &key, second, &response)
+        .await
+        .unwrap();
+    assert_eq!(
+        idempotency::claim(&pool, &wallet, &key, b"hash", ttl)
+            .await
+            .unwrap(),
+        Claim::Replay(response)
+    );
+}
+
+#[tokio::test]
+async fn test_keys_expire() {
+    let (server, pool) = create_test_server(Duration::ZERO).await;
+    let token = create_user(&pool).await;
+    let key = key();
+
+    for _ in 0..2 {
+        let response = server
+            .post("/generate")
+            .authorization_bearer(&token)
+            .add_header("Idempotency-Key", &key)
+            .json(&json!({"contract_type": "counter", "contract_name": "C"}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert!(response.maybe_header("idempotent-replayed").is_none());
+    }
+    assert_eq!(contract_count(&server, &token).await, 2);
+}
+
+#[tokio::test]
+async fn test_register_retry_is_replayed() {
+    let (server, pool) = create_test_server(Duration::from_secs(3600)).await;
+    let wallet = random_wallet();
+    let token = token_for(&pool, &wallet).await;
+    let key = key();
+
+    let first = server
+        .post("/register")
+        .authorization_bearer(&token)
+        .add_header("Idempotency-Key", &key)
+        .json(&json!({"wallet": wallet}))
+        .await;
+    assert_eq!(first.status_code(), StatusCode::CREATED);
+
+    // Without the key the retry would be a 409
+    let retry = server
+        .post("/register")
+        .authorization_bearer(&token)
+        .add_header("Idempotency-Key", &key)
+        .json(&json!({"wallet": wallet}))
+        .await;
+    assert_eq!(retry.status_code(), StatusCode::CREATED);
+    assert_eq!(retry.header("idempotent-replayed"), "true");
+    assert_eq!(retry.json::<Value>(), first.json::<Value>());
+    // The replay is a request of its own
+    assert_ne!(retry.header("x-request-id"), first.header("x-request-id"));
+}
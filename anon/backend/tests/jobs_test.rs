@@ -0,0 +1,635 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{Json, http::StatusCode};
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use backend::libs::{
+    db::AppState,
+    error::ApiError,
+    generator::{ContractGenerator, GenerationRequest, Generators, LlmGenerator},
+    jobs::{self, JobQueue},
+};
+use common::create_user;
+
+// Test helper to create a test server generating with `generators` and
+// queueing on `queue`, with no jobs of earlier runs left
+async fn create_test_server(
+    generators: Generators,
+    queue: JobQueue,
+) -> (TestServer, AppState, PgPool) {
+    let (server, state, pool) = common::create_test_server_with(|state| {
+        state.generators = Arc::new(generators);
+        state.jobs = Arc::new(queue);
+    })
+    .await;
+    common::clear_jobs(&pool).await;
+    (server, state, pool)
+}
+
+// LLM endpoint streaming a contract in a few deltas
+async fn spawn_streaming_llm() -> String {
+    let app = axum::Router::new().route(
+        "/chat/completions",
+        axum::routing::post(|Json(req): Json<Value>| async move {
+            assert_eq!(req["stream"], true);
+            let deltas = [
+                "```cairo\n",
+                "#[starknet::contract]\n",
+                "mod Lotto {\n    #[storage]\n    struct Storage {}\n}\n",
+                "```",
+            ];
+            let mut body = String::new();
+            for delta in deltas {
+                let chunk = json!({"choices": [{"index": 0, "delta": {"content": delta}}]});
+                body.push_str(&format!("data: {}\n\n", chunk));
+            }
+            body.push_str("data: [DONE]\n\n");
+            (
+                [(axum::http::header::CONTENT_TYPE, "text/event-stream")],
+                body,
+            )
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+// (event, data) pairs of an SSE body
+fn parse_events(body: &str) -> Vec<(String, Value)> {
+    body.split("\n\n")
+        .filter_map(|block| {
+            let mut name = None;
+            let mut data = None;
+            for line in block.lines() {
+                if let Some(v) = line.strip_prefix("event:") {
+                    name = Some(v.trim().to_string());
+                } else if let Some(v) = line.strip_prefix("data:") {
+                    data = Some(serde_json::from_str(v.trim()).unwrap());
+                }
+            }
+            Some((name?, data?))
+        })
+        .collect()
+}
+
+// LLM endpoint that always fails
+async fn spawn_failing_llm() -> String {
+    spawn_failing_llm_after(Duration::ZERO).await
+}
+
+// LLM endpoint that fails after `delay`
+async fn spawn_failing_llm_after(delay: Duration) -> String {
+    let app = axum::Router::new().route(
+        "/chat/completions",
+        axum::routing::post(move || async move {
+            tokio::time::sleep(delay).await;
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": {"message": "overloaded"}})),
+            )
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+// LLM endpoint answering with a contract after `delay`
+async fn spawn_slow_llm(delay: Duration) -> String {
+    let app = axum::Router::new().route(
+        "/chat/completions",
+        axum::routing::post(move || async move {
+            tokio::time::sleep(delay).await;
+            let reply = "```cairo\n#[starknet::contract]\nmod Lotto {}\n```";
+            Json(json!({"choices": [{"index": 0, "message": {"content": reply}}]}))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_async_generation_completes() {
+    let (server, state, pool) =
+        create_test_server(Generators::default(), JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "erc20",
+            "contract_name": "Gold",
+            "parameters": {"name": "Gold", "symbol": "GLD"},
+            "async": true
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+    let job: Value = response.json();
+    let job_id = job["id"].as_i64().unwrap();
+    let contract_id = job["contract_id"].as_i64().unwrap();
+    assert_eq!(response.header("location"), format!("/jobs/{}", job_id));
+    assert_eq!(job["status"], "queued");
+    assert_eq!(job["attempts"], 0);
+    assert_eq!(job["contract"], Value::Null);
+
+    let (status, code): (String, String) =
+        sqlx::query_as("SELECT status, generated_code FROM generated_contracts WHERE id = $1")
+            .bind(contract_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "queued");
+    assert_eq!(code, "");
+
+    // Nothing to download yet
+    let response = server
+        .get(&format!("/generated_contracts/{}/archive", contract_id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+    assert!(jobs::run_next(&state).await.unwrap());
+    assert!(!jobs::run_next(&state).await.unwrap());
+
+    let response = server
+        .get(&format!("/jobs/{}", job_id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let job: Value = response.json();
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["attempts"], 1);
+    assert_eq!(job["error"], Value::Null);
+    assert_eq!(job["contract"]["contract_id"], contract_id);
+    assert_eq!(job["contract"]["status"], "generated");
+    assert!(
+        job["contract"]["generated_code"]
+            .as_str()
+            .unwrap()
+            .contains("\"GLD\"")
+    );
+    assert!(
+        job["contract"]["scarb_config"]
+            .as_str()
+            .unwrap()
+            .contains("openzeppelin")
+    );
+}
+
+#[tokio::test]
+async fn test_async_generation_retries_then_fails() {
+    let url = spawn_failing_llm().await;
+    let generators = Generators {
+        llm: Some(Arc::new(LlmGenerator::new(
+            &url,
+            "test-model",
+            None,
+            Duration::from_secs(5),
+        ))),
+        ..Generators::default()
+    };
+    let queue = JobQueue {
+        max_attempts: 2,
+        retry_backoff: Duration::from_secs(60),
+        ..JobQueue::default()
+    };
+    let (server, state, pool) = create_test_server(generators, queue).await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "lottery",
+            "contract_name": "Lotto",
+            "generator": "llm",
+            "async": true
+        }))
+        .await
+        .json();
+    let job_id = job["id"].as_i64().unwrap();
+    let contract_id = job["contract_id"].as_i64().unwrap();
+
+    // First failure is retried later
+    assert!(jobs::run_next(&state).await.unwrap());
+    let job: Value = server
+        .get(&format!("/jobs/{}", job_id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(job["status"], "queued");
+    assert_eq!(job["attempts"], 1);
+    assert_eq!(job["error"], "llm generator failed");
+    let run_at: chrono::DateTime<chrono::Utc> =
+        serde_json::from_value(job["run_at"].clone()).unwrap();
+    assert!(run_at > chrono::Utc::now() + chrono::Duration::seconds(30));
+    // Not due yet
+    assert!(!jobs::run_next(&state).await.unwrap());
+
+    sqlx::query("UPDATE generation_jobs SET run_at = NOW() WHERE id = $1")
+        .bind(job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(jobs::run_next(&state).await.unwrap());
+
+    let job: Value = server
+        .get(&format!("/jobs/{}", job_id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(job["status"], "failed");
+    assert_eq!(job["attempts"], 2);
+
+    let (status, error): (String, Option<String>) =
+        sqlx::query_as("SELECT status, error FROM generated_contracts WHERE id = $1")
+            .bind(contract_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "failed");
+    assert_eq!(error.as_deref(), Some("llm generator failed"));
+}
+
+#[tokio::test]
+async fn test_async_generation_validates_up_front() {
+    let (server, _, pool) = create_test_server(Generators::default(), JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "erc20",
+            "contract_name": "Gold",
+            "parameters": {"decimals": "lots"},
+            "async": true
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // No LLM configured
+    let response = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "lottery",
+            "contract_name": "Lotto",
+            "generator": "llm",
+            "async": true
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let (jobs,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM generation_jobs")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(jobs, 0);
+}
+
+#[tokio::test]
+async fn test_job_of_another_user_is_not_found() {
+    let (server, _, pool) = create_test_server(Generators::default(), JobQueue::default()).await;
+    let owner = create_user(&pool).await;
+    let other = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&owner)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let path = format!("/jobs/{}", job["id"]);
+
+    let response = server.get(&path).authorization_bearer(&other).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server.get(&path).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let response = server.get("/jobs/0").authorization_bearer(&owner).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_concurrent_claims_take_a_job_once() {
+    let (server, _, pool) = create_test_server(Generators::default(), JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+
+    let lock_timeout = Duration::from_secs(600);
+    let (a, b) = tokio::join!(
+        jobs::claim(&pool, lock_timeout),
+        jobs::claim(&pool, lock_timeout)
+    );
+    let claimed: Vec<_> = [a.unwrap(), b.unwrap()].into_iter().flatten().collect();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].attempts, 1);
+
+    let (status,): (String,) =
+        sqlx::query_as("SELECT status FROM generated_contracts WHERE id = $1")
+            .bind(claimed[0].contract_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "generating");
+
+    // A worker that went silent loses its job
+    assert!(jobs::claim(&pool, lock_timeout).await.unwrap().is_none());
+    let reclaimed = jobs::claim(&pool, Duration::ZERO).await.unwrap().unwrap();
+    assert_eq!(reclaimed.id, claimed[0].id);
+    assert_eq!(reclaimed.attempts, 2);
+}
+
+#[tokio::test]
+async fn test_exhausted_stale_job_is_failed_not_reclaimed() {
+    let (server, _, pool) = create_test_server(Generators::default(), JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let job_id = job["id"].as_i64().unwrap();
+    sqlx::query("UPDATE generation_jobs SET max_attempts = 1 WHERE id = $1")
+        .bind(job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let claimed = jobs::claim(&pool, Duration::from_secs(600))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.attempts, 1);
+
+    // The worker went silent on its last attempt
+    assert!(jobs::claim(&pool, Duration::ZERO).await.unwrap().is_none());
+    let job: Value = server
+        .get(&format!("/jobs/{}", job_id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(job["status"], "failed");
+    assert_eq!(job["attempts"], 1);
+    let (status,): (String,) =
+        sqlx::query_as("SELECT status FROM generated_contracts WHERE id = $1")
+            .bind(claimed.contract_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "failed");
+}
+
+#[tokio::test]
+async fn test_superseded_attempt_does_not_settle_job() {
+    let url = spawn_failing_llm_after(Duration::from_millis(500)).await;
+    let generators = Generators {
+        llm: Some(Arc::new(LlmGenerator::new(
+            &url,
+            "test-model",
+            None,
+            Duration::from_secs(5),
+        ))),
+        ..Generators::default()
+    };
+    let (server, state, pool) = create_test_server(generators, JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "lottery",
+            "contract_name": "Lotto",
+            "generator": "llm",
+            "async": true
+        }))
+        .await
+        .json();
+    let job_id = job["id"].as_i64().unwrap();
+
+    // A second claim takes the job over while the first attempt still runs
+    let first = tokio::spawn({
+        let state = state.clone();
+        async move { jobs::run_next(&state).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let second = jobs::claim(&pool, Duration::ZERO).await.unwrap().unwrap();
+    assert_eq!(second.id, job_id);
+    assert_eq!(second.attempts, 2);
+    assert!(first.await.unwrap().unwrap());
+
+    // The first attempt's failure was dropped
+    let job: Value = server
+        .get(&format!("/jobs/{}", job_id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(job["status"], "running");
+    assert_eq!(job["attempts"], 2);
+    assert!(job["error"].is_null());
+}
+
+#[tokio::test]
+async fn test_running_job_of_deleted_contract_is_dropped() {
+    let url = spawn_slow_llm(Duration::from_millis(500)).await;
+    let generators = Generators {
+        llm: Some(Arc::new(LlmGenerator::new(
+            &url,
+            "test-model",
+            None,
+            Duration::from_secs(5),
+        ))),
+        ..Generators::default()
+    };
+    let (server, state, pool) = create_test_server(generators, JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "lottery",
+            "contract_name": "Lotto",
+            "generator": "llm",
+            "async": true
+        }))
+        .await
+        .json();
+    let running = tokio::spawn({
+        let state = state.clone();
+        async move { jobs::run_next(&state).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let response = server
+        .delete(&format!("/generated_contracts/{}", job["contract_id"]))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    assert!(running.await.unwrap().unwrap());
+
+    let (status, error): (String, Option<String>) =
+        sqlx::query_as("SELECT status, last_error FROM generation_jobs WHERE id = $1")
+            .bind(job["id"].as_i64().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "failed");
+    assert_eq!(error.as_deref(), Some("contract deleted"));
+    let (revisions,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM contract_revisions WHERE contract_id = $1")
+            .bind(job["contract_id"].as_i64().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(revisions, 0);
+}
+
+#[tokio::test]
+async fn test_job_events_of_finished_job() {
+    let (server, state, pool) =
+        create_test_server(Generators::default(), JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    assert!(jobs::run_next(&state).await.unwrap());
+
+    let response = server
+        .get(&format!("/jobs/{}/events", job["id"]))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(
+        response
+            .header("content-type")
+            .to_str()
+            .unwrap()
+            .starts_with("text/event-stream")
+    );
+    assert!(response.maybe_header("x-request-id").is_some());
+
+    let events = parse_events(&response.text());
+    let names: Vec<&str> = events.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["status", "done"]);
+    assert_eq!(events[0].1["status"], "succeeded");
+    assert_eq!(events[1].1["contract_id"], job["contract_id"]);
+    assert_eq!(events[1].1["status"], "generated");
+
+    let other = create_user(&pool).await;
+    let response = server
+        .get(&format!("/jobs/{}/events", job["id"]))
+        .authorization_bearer(&other)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_job_events_stream_generator_output() {
+    let url = spawn_streaming_llm().await;
+    let generators = Generators {
+        llm: Some(Arc::new(LlmGenerator::new(
+            &url,
+            "test-model",
+            None,
+            Duration::from_secs(5),
+        ))),
+        ..Generators::default()
+    };
+    let (server, state, pool) = create_test_server(generators, JobQueue::default()).await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({
+            "contract_type": "lottery",
+            "contract_name": "Lotto",
+            "generator": "llm",
+            "async": true
+        }))
+        .await
+        .json();
+
+    let stream = server
+        .get(&format!("/jobs/{}/events", job["id"]))
+        .authorization_bearer(&token);
+    let worker = async {
+        // Let the stream subscribe first
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(jobs::run_next(&state).await.unwrap());
+    };
+    let (response, _) = tokio::join!(stream, worker);
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let events = parse_events(&response.text());
+    let statuses: Vec<&str> = events
+        .iter()
+        .filter(|(n, _)| n == "status")
+        .map(|(_, d)| d["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, ["queued", "running", "succeeded"]);
+
+    let output: String = events
+        .iter()
+        .filter(|(n, _)| n == "output")
+        .map(|(_, d)| d["text"].as_str().unwrap())
+        .collect();
+    assert!(output.starts_with("```cairo\n#[starknet::contract]"));
+
+    // The generated code is audited before the job is done
+    let (_, audit) = events.iter().find(|(n, _)| n == "audit").unwrap();
+    assert_eq!(audit["contract_id"], job["contract_id"]);
+    assert!(audit["findings"].is_array());
+
+    let (name, done) = events.last().unwrap();
+    assert_eq!(name, "done");
+    assert_eq!(done["generator"], "llm");
+    assert_eq!(
+        done["generated_code"],
+        "#[starknet::contract]\nmod Lotto {\n    #[storage]\n    struct Storage {}\n}\n"
+    );
+}
+
+#[tokio::test]
+async fn test_streamed_reply_is_capped() {
+    let url = spawn_streaming_llm().await;
+    let mut llm = LlmGenerator::new(&url, "test-model", None, Duration::from_secs(5));
+    let request = GenerationRequest {
+        contract_type: "lottery",
+        template: None,
+        contract_name: "Lotto",
+        description: None,
+        parameters: None,
+    };
+    assert!(llm.generate_streaming(&request, &|_| {}).await.is_ok());
+
+    llm.max_reply_bytes = 100;
+    let result = llm.generate_streaming(&request, &|_| {}).await;
+    assert!(matches!(
+        result,
+        Err(ApiError::BadGateway("llm reply too large"))
+    ));
+}
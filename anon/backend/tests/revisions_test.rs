@@ -0,0 +1,267 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use backend::libs::{db::AppState, jobs};
+use common::create_user;
+
+// Test helper to create a test server with no jobs queued
+async fn create_test_server() -> (TestServer, AppState, PgPool) {
+    let (server, state, pool) = common::create_test_server_with(|_| {}).await;
+    common::clear_jobs(&pool).await;
+    (server, state, pool)
+}
+
+async fn generate(server: &TestServer, token: &str) -> Value {
+    server
+        .post("/generate")
+        .authorization_bearer(token)
+        .json(&json!({
+            "contract_type": "counter",
+            "contract_name": "C",
+            "parameters": {"step": 5}
+        }))
+        .await
+        .json()
+}
+
+async fn contract_row(pool: &PgPool, id: i64) -> (String, String, chrono::DateTime<chrono::Utc>) {
+    sqlx::query_as(
+        "SELECT generated_code, status, updated_at FROM generated_contracts WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_edit_and_regenerate_keep_history() {
+    let (server, _, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let contract = generate(&server, &token).await;
+    let id = contract["contract_id"].as_i64().unwrap();
+    let original = contract["generated_code"].as_str().unwrap().to_string();
+
+    let list: Value = server
+        .get(&format!("/generated_contracts/{}/revisions", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"].as_array().unwrap().len(), 1);
+    assert_eq!(list["items"][0]["revision"], 1);
+    assert_eq!(list["items"][0]["source"], "generated");
+
+    // Hand edit
+    sqlx::query("UPDATE generated_contracts SET status = 'compiled' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, _, before) = contract_row(&pool, id).await;
+    let edited = original.replace("const STEP: u64 = 5;", "const STEP: u64 = 7;");
+    let response = server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&token)
+        .json(&json!({"generated_code": edited, "message": "bigger steps"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let revision: Value = response.json();
+    assert_eq!(revision["revision"], 2);
+    assert_eq!(revision["source"], "edited");
+    assert_eq!(revision["message"], "bigger steps");
+    assert!(
+        revision["scarb_config"]
+            .as_str()
+            .unwrap()
+            .contains("openzeppelin")
+    );
+
+    let (code, status, after) = contract_row(&pool, id).await;
+    assert_eq!(code, edited);
+    assert_eq!(status, "generated");
+    assert!(after > before);
+
+    // Regenerate with new parameters
+    let response = server
+        .post(&format!("/generated_contracts/{}/regenerate", id))
+        .authorization_bearer(&token)
+        .json(&json!({"parameters": {"step": 9}}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let revision: Value = response.json();
+    assert_eq!(revision["revision"], 3);
+    assert_eq!(revision["source"], "regenerated");
+    assert!(
+        revision["generated_code"]
+            .as_str()
+            .unwrap()
+            .contains("const STEP: u64 = 9;")
+    );
+    let (parameters,): (Value,) =
+        sqlx::query_as("SELECT parameters FROM generated_contracts WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(parameters, json!({"step": 9}));
+
+    let first: Value = server
+        .get(&format!("/generated_contracts/{}/revisions/1", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(first["generated_code"], original.as_str());
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/diff", id))
+        .add_query_params(json!({"from": 1, "to": 2}))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(
+        response
+            .header("content-type")
+            .to_str()
+            .unwrap()
+            .starts_with("text/x-diff")
+    );
+    let diff = response.text();
+    assert!(
+        diff.starts_with("--- src/lib.cairo (revision 1)\n+++ src/lib.cairo (revision 2)\n@@ ")
+    );
+    assert!(diff.contains("\n-    const STEP: u64 = 5;\n+    const STEP: u64 = 7;\n"));
+
+    // `to` defaults to the latest revision
+    let diff = server
+        .get(&format!("/generated_contracts/{}/diff", id))
+        .add_query_params(json!({"from": 2}))
+        .authorization_bearer(&token)
+        .await
+        .text();
+    assert!(diff.contains("+++ src/lib.cairo (revision 3)\n"));
+    assert!(diff.contains("+    const STEP: u64 = 9;\n"));
+}
+
+#[tokio::test]
+async fn test_queued_generation_becomes_first_revision() {
+    let (server, state, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+
+    let job: Value = server
+        .post("/generate")
+        .authorization_bearer(&token)
+        .json(&json!({"contract_type": "counter", "contract_name": "C", "async": true}))
+        .await
+        .json();
+    let id = job["contract_id"].as_i64().unwrap();
+
+    // Nothing to edit while the job has not run
+    let response = server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&token)
+        .json(&json!({"generated_code": "mod A {}"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    let list: Value = server
+        .get(&format!("/generated_contracts/{}/revisions", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"], json!([]));
+
+    assert!(jobs::run_next(&state).await.unwrap());
+    let list: Value = server
+        .get(&format!("/generated_contracts/{}/revisions", id))
+        .authorization_bearer(&token)
+        .await
+        .json();
+    assert_eq!(list["items"][0]["revision"], 1);
+    assert_eq!(list["items"][0]["source"], "generated");
+}
+
+#[tokio::test]
+async fn test_edit_keeps_deployed_and_archived_status() {
+    let (server, _, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+
+    for status in ["deployed", "archived"] {
+        let id = generate(&server, &token).await["contract_id"]
+            .as_i64()
+            .unwrap();
+        sqlx::query("UPDATE generated_contracts SET status = $2 WHERE id = $1")
+            .bind(id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let response = server
+            .put(&format!("/generated_contracts/{}/code", id))
+            .authorization_bearer(&token)
+            .json(&json!({"generated_code": "mod A {}"}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(contract_row(&pool, id).await.1, status);
+    }
+}
+
+#[tokio::test]
+async fn test_revision_errors() {
+    let (server, _, pool) = create_test_server().await;
+    let token = create_user(&pool).await;
+    let id = generate(&server, &token).await["contract_id"]
+        .as_i64()
+        .unwrap();
+
+    let response = server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&token)
+        .json(&json!({"generated_code": "  "}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .get(&format!("/generated_contracts/{}/revisions/9", id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server
+        .get(&format!("/generated_contracts/{}/diff", id))
+        .add_query_params(json!({"from": 1, "to": 9}))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server
+        .get(&format!("/generated_contracts/{}/diff", id))
+        .authorization_bearer(&token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["error"],
+        "from and to must be revision numbers"
+    );
+
+    // Someone else's contract looks missing
+    let other = create_user(&pool).await;
+    let response = server
+        .get(&format!("/generated_contracts/{}/revisions", id))
+        .authorization_bearer(&other)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server
+        .put(&format!("/generated_contracts/{}/code", id))
+        .authorization_bearer(&other)
+        .json(&json!({"generated_code": "mod A {}"}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server
+        .post(&format!("/generated_contracts/{}/regenerate", id))
+        .authorization_bearer(&other)
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use axum::http::{StatusCode, header};
+use serde_json::Value;
+
+use common::create_test_server;
+
+#[tokio::test]
+async fn test_list_templates() {
+    let (server, _) = create_test_server().await;
+
+    let response = server.get("/templates").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let body: Value = response.json();
+    let items = body["items"].as_array().unwrap();
+    let ids: Vec<&str> = items.iter().map(|t| t["id"].as_str().unwrap()).collect();
+    for id in [
+        "erc20",
+        "erc721",
+        "erc1155",
+        "ownable_counter",
+        "vesting",
+        "staking",
+        "multisig",
+        "dao_governor",
+    ] {
+        assert!(ids.contains(&id), "missing template {}", id);
+    }
+
+    let erc20 = items.iter().find(|t| t["id"] == "erc20").unwrap();
+    assert_eq!(erc20["version"], "1.0.0");
+    assert!(erc20["cairo_versions"].is_string());
+    assert!(
+        erc20["aliases"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("token"))
+    );
+    assert_eq!(erc20["parameters_schema"]["type"], "object");
+    // Examples are only on the detail endpoint
+    assert!(erc20.get("example").is_none());
+}
+
+#[tokio::test]
+async fn test_get_template_includes_example() {
+    let (server, _) = create_test_server().await;
+
+    let response = server.get("/templates/multisig").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let body: Value = response.json();
+    assert_eq!(body["id"], "multisig");
+    assert_eq!(
+        body["parameters_schema"]["properties"]["threshold"]["default"],
+        2
+    );
+    assert_eq!(body["example"]["parameters"]["threshold"], "2");
+    let code = body["example"]["code"].as_str().unwrap();
+    assert!(code.contains("mod MyContract {"));
+    assert!(code.contains("const THRESHOLD: u32 = 2;"));
+}
+
+#[tokio::test]
+async fn test_get_unknown_template() {
+    let (server, _) = create_test_server().await;
+
+    let response = server.get("/templates/erc9999").await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "template not found");
+}
+
+#[tokio::test]
+async fn test_catalog_etag_revalidation() {
+    let (server, _) = create_test_server().await;
+
+    for path in ["/templates", "/templates/erc20"] {
+        let response = server.get(path).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let etag = response.header(header::ETAG);
+        assert!(etag.to_str().unwrap().starts_with('"'));
+
+        let response = server
+            .get(path)
+            .add_header(header::IF_NONE_MATCH, etag.clone())
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.header(header::ETAG), etag);
+        assert!(response.text().is_empty());
+
+        let response = server
+            .get(path)
+            .add_header(header::IF_NONE_MATCH, "\"stale\"")
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+
+    // Each template has its own ETag
+    let a = server.get("/templates/erc20").await.header(header::ETAG);
+    let b = server.get("/templates/erc721").await.header(header::ETAG);
+    assert_ne!(a, b);
+}
@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+
+use backend::libs::templates::{self, TEMPLATES};
+
+// Non-default parameters per template, so every conditional branch that can
+// differ from the defaults shows up in at least one golden file
+fn golden_params(id: &str) -> Value {
+    match id {
+        "erc20" => json!({
+            "name": "Golden",
+            "symbol": "GLD",
+            "decimals": 6,
+            "initial_supply": "1000000000000",
+            "mintable": true,
+            "burnable": true
+        }),
+        "erc721" => json!({
+            "name": "Golden",
+            "symbol": "GNFT",
+            "base_uri": "ipfs://golden/",
+            "burnable": true
+        }),
+        "erc1155" => json!({"base_uri": "ipfs://golden/{id}.json", "burnable": true}),
+        "ownable_counter" => json!({"initial_value": 10, "step": 5, "owner_only_increment": true}),
+        "vesting" => json!({"cliff_seconds": 2592000, "duration_seconds": 31536000}),
+        "staking" => json!({"reward_rate": "1000000000000000"}),
+        "multisig" => json!({"threshold": 3}),
+        "dao_governor" => json!({
+            "voting_delay_seconds": 86400,
+            "voting_period_seconds": 604800,
+            "quorum": "4000000000000000000000",
+            "proposal_threshold": "1000000000000000000"
+        }),
+        other => panic!("no golden parameters for template {}", other),
+    }
+}
+
+fn fixture_path(id: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/templates")
+        .join(format!("{}.cairo", id))
+}
+
+// Rendered output must match `tests/fixtures/templates/<id>.cairo`. After an
+// intended template change, regenerate with `UPDATE_GOLDEN=1 cargo test --test
+// templates_test` and review the diff.
+#[test]
+fn test_templates_match_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    for template in TEMPLATES {
+        let params = golden_params(template.id);
+        let code = templates::render(template, "Golden", Some(&params))
+            .unwrap_or_else(|e| panic!("{} failed to render: {:?}", template.id, e));
+        let path = fixture_path(template.id);
+
+        if update {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &code).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing golden file {}", path.display()));
+        assert_eq!(
+            code,
+            expected,
+            "{} does not match {}",
+            template.id,
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn test_rendered_code_has_no_template_leftovers() {
+    for template in TEMPLATES {
+        let code =
+            templates::render(template, "Golden", Some(&golden_params(template.id))).unwrap();
+        assert!(
+            !code.contains("{{") && !code.contains("{%"),
+            "{}",
+            template.id
+        );
+        assert!(
+            !code.contains("\n\n\n"),
+            "{} has stray blank lines",
+            template.id
+        );
+        assert!(code.ends_with("}\n"), "{}", template.id);
+    }
+}